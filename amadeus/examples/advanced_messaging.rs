@@ -102,7 +102,7 @@ impl Plugin for ReceiverPlugin {
             let ctx = Arc::new(MessageContext::new(dc_arc, plugin_name, plugin_uid, tx));
 
             // 1. 订阅广播
-            let mut public_rx = ctx.subscribe("demo.public").await;
+            let mut public_rx = ctx.subscribe("demo.public").await?;
             
             // 2. 开启定向接收
             let mut direct_rx = ctx.enable_direct_messaging().await;
@@ -111,10 +111,10 @@ impl Plugin for ReceiverPlugin {
                 loop {
                     tokio::select! {
                         Ok(msg) = public_rx.recv() => {
-                            info!("[Receiver] Got PUBLIC message: {:?} from {:?}", msg.payload, msg.source);
+                            info!("[Receiver] Got PUBLIC message: {}", msg.redacted());
                         }
                         Some(msg) = direct_rx.recv() => {
-                            info!("[Receiver] Got DIRECT message: {:?} from {:?}", msg.payload, msg.source);
+                            info!("[Receiver] Got DIRECT message: {}", msg.redacted());
                         }
                     }
                 }
@@ -155,20 +155,20 @@ impl Plugin for BystanderPlugin {
             let ctx = Arc::new(MessageContext::new(dc_arc, plugin_name, plugin_uid, tx));
             
             // 订阅同样的广播话题
-            let mut public_rx = ctx.subscribe("demo.public").await;
+            let mut public_rx = ctx.subscribe("demo.public").await?;
             // 试图订阅定向话题（但这不起作用，因为定向是点对点的，除非它是广播）
             // 但我们可以订阅同名广播话题来看是否泄露
-            let mut direct_leak_rx = ctx.subscribe("demo.direct").await;
+            let mut direct_leak_rx = ctx.subscribe("demo.direct").await?;
 
             tokio::spawn(async move {
                 loop {
                     tokio::select! {
                         Ok(msg) = public_rx.recv() => {
-                            info!("[Bystander] Got PUBLIC message: {:?}", msg.payload);
+                            info!("[Bystander] Got PUBLIC message: {}", msg.redacted());
                         }
                         Ok(msg) = direct_leak_rx.recv() => {
                             // 如果收到这层，说明 Direct 消息被广播泄露了！
-                            tracing::error!("[Bystander] ALARM! Saw DIRECT message: {:?}", msg.payload);
+                            tracing::error!("[Bystander] ALARM! Saw DIRECT message: {}", msg.redacted());
                         }
                     }
                 }
@@ -42,26 +42,26 @@ impl Plugin for InteractorPlugin {
             let ctx_clone = ctx.clone();
 
             // 订阅 Core System 的回复
-            let mut created_rx = ctx.subscribe("system.memo.created").await;
-            let mut list_rx = ctx.subscribe("system.memo.list.reply").await;
-            let mut remind_rx = ctx.subscribe("system.memo.remind").await;
+            let mut created_rx = ctx.subscribe("system.memo.created").await?;
+            let mut list_rx = ctx.subscribe("system.memo.list.reply").await?;
+            let mut remind_rx = ctx.subscribe("system.memo.remind").await?;
 
             // 启动接收循环
             tokio::spawn(async move {
                 loop {
                     tokio::select! {
                         Ok(msg) = created_rx.recv() => {
-                            info!("✅ Memo Created: {}", msg.payload);
+                            info!("✅ Memo Created: {}", msg.redacted());
                             // 创建成功后，请求列表
                             tokio::time::sleep(Duration::from_millis(500)).await;
                             let list_req = Message::new("system.memo.list", serde_json::json!({}));
                             let _ = ctx_clone.send(list_req).await;
                         }
                         Ok(msg) = list_rx.recv() => {
-                            info!("📋 Memo List: {}", msg.payload);
+                            info!("📋 Memo List: {}", msg.redacted());
                         }
                         Ok(msg) = remind_rx.recv() => {
-                            info!("⏰ REMINDER TRIGGERED: {}", msg.payload);
+                            info!("⏰ REMINDER TRIGGERED: {}", msg.redacted());
                         }
                     }
                 }
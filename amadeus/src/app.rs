@@ -1,6 +1,9 @@
 use crate::core::messaging::message_manager::MessageManager;
-use crate::plugin::{Plugin, PluginRegistry};
+use crate::plugin::{Plugin, PluginRegistry, DEFAULT_STOP_TIMEOUT};
+use crate::plugins::{AppConfig, PluginFactories};
 use anyhow::Result;
+use std::future::Future;
+use std::time::Duration;
 
 /// Amadeus 应用构建器
 /// 
@@ -10,46 +13,59 @@ pub struct App {
     message_manager: Option<MessageManager>,
     show_metadata: bool,
     show_startup_message: bool,
+    /// 停止时每个插件的最长等待时间
+    stop_timeout: Duration,
+    #[cfg(feature = "iceoryx2")]
+    ipc_bridge: IpcBridgeConfig,
+}
+
+/// IPC 桥（Iceoryx2 分发器插件）的构建参数
+#[cfg(feature = "iceoryx2")]
+#[derive(Default)]
+struct IpcBridgeConfig {
+    /// 设置后表示已启用 IPC 桥
+    node_name: Option<String>,
+    service_name: Option<String>,
+    public_key_pem: Option<String>,
 }
 
 impl App {
-    /// 创建新的应用实例，自动加载所有启用的插件
+    /// 创建新的应用实例，使用默认配置自动加载所有启用的插件
     pub fn new() -> Self {
-        let registry = PluginRegistry::with_enabled_plugins(
-            crate::plugins::get_all_plugins()
-        );
-        
-        Self {
-            registry,
-            message_manager: None,
-            show_metadata: false,
-            show_startup_message: true,
-        }
+        Self::from_config(&AppConfig::default())
+    }
+
+    /// 使用指定的应用配置创建应用，插件由内置的插件工厂构建
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::from_factories(&PluginFactories::default(), config)
+    }
+
+    /// 使用指定的插件工厂注册表和应用配置创建应用
+    pub fn from_factories(factories: &PluginFactories, config: &AppConfig) -> Self {
+        Self::from_registry(PluginRegistry::with_enabled_plugins(factories.build(config)))
     }
 
     /// 使用自定义插件列表创建应用
     pub fn with_plugins(plugins: Vec<Box<dyn Plugin>>) -> Self {
-        let registry = PluginRegistry::with_enabled_plugins(plugins);
-        
-        Self {
-            registry,
-            message_manager: None,
-            show_metadata: false,
-            show_startup_message: true,
-        }
+        Self::from_registry(PluginRegistry::with_enabled_plugins(plugins))
     }
 
     /// 加载所有插件（无论是否启用）
     pub fn with_all_plugins() -> Self {
-        let registry = PluginRegistry::with_all_plugins(
-            crate::plugins::get_all_plugins()
-        );
-        
+        Self::from_registry(PluginRegistry::with_all_plugins(
+            crate::plugins::get_all_plugins(&AppConfig::default())
+        ))
+    }
+
+    fn from_registry(registry: PluginRegistry) -> Self {
         Self {
             registry,
             message_manager: None,
             show_metadata: false,
             show_startup_message: true,
+            stop_timeout: DEFAULT_STOP_TIMEOUT,
+            #[cfg(feature = "iceoryx2")]
+            ipc_bridge: IpcBridgeConfig::default(),
         }
     }
 
@@ -82,13 +98,91 @@ impl App {
         self
     }
 
+    /// 设置停止时每个插件的最长等待时间（默认 [`DEFAULT_STOP_TIMEOUT`]）
+    pub fn with_stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+
+    /// 启用 IPC 桥：以 `node_name` 注册 Iceoryx2 分发器插件
+    ///
+    /// 会替换插件列表中已有的分发器，服务名默认为 `service_names::AMADEUS_SERVICE`
+    #[cfg(feature = "iceoryx2")]
+    pub fn enable_ipc_bridge(mut self, node_name: impl Into<String>) -> Self {
+        self.ipc_bridge.node_name = Some(node_name.into());
+        self.install_ipc_bridge();
+        self
+    }
+
+    /// 设置 IPC 桥使用的服务名（可在 `enable_ipc_bridge` 之前或之后调用）
+    #[cfg(feature = "iceoryx2")]
+    pub fn with_ipc_service(mut self, service_name: impl Into<String>) -> Self {
+        self.ipc_bridge.service_name = Some(service_name.into());
+        self.install_ipc_bridge();
+        self
+    }
+
+    /// 设置外部进程的 RSA 公钥（PEM），用于加密发往 IPC 桥的消息
+    #[cfg(feature = "iceoryx2")]
+    pub fn with_ipc_public_key(mut self, public_key_pem: impl Into<String>) -> Self {
+        self.ipc_bridge.public_key_pem = Some(public_key_pem.into());
+        self.install_ipc_bridge();
+        self
+    }
+
+    /// 按当前配置重建分发器插件；IPC 桥未启用时不做任何事
+    #[cfg(feature = "iceoryx2")]
+    fn install_ipc_bridge(&mut self) {
+        use crate::plugins::iceoryx2_dispatcher::Iceoryx2DispatcherPlugin;
+
+        let Some(node_name) = self.ipc_bridge.node_name.clone() else {
+            return;
+        };
+
+        let mut plugin = match &self.ipc_bridge.service_name {
+            Some(service) => Iceoryx2DispatcherPlugin::with_service(node_name, service.clone()),
+            None => Iceoryx2DispatcherPlugin::new(node_name),
+        };
+        if let Some(pem) = &self.ipc_bridge.public_key_pem {
+            plugin = plugin.with_public_key(pem.clone());
+        }
+
+        let name = plugin.metadata().name.clone();
+        self.registry.unregister(&name);
+        self.registry.register(plugin);
+    }
+
     /// 获取插件注册表的可变引用
     pub fn registry_mut(&mut self) -> &mut PluginRegistry {
         &mut self.registry
     }
 
-    /// 异步运行应用
-    pub async fn run_async(mut self) -> Result<()> {
+    /// 异步运行应用，直到收到 Ctrl+C
+    pub async fn run_async(self) -> Result<()> {
+        self.run_until(async {
+            match tokio::signal::ctrl_c().await {
+                Ok(()) => tracing::info!("收到停止信号，正在关闭..."),
+                Err(err) => tracing::error!("监听信号失败: {}", err),
+            }
+        })
+        .await
+    }
+
+    /// 异步运行应用，直到 `shutdown` 完成
+    ///
+    /// 启动流程：init -> 设置消息订阅 -> 启动消息循环 -> start -> 等待 `shutdown`
+    ///
+    /// 停止流程（顺序有保证）：
+    /// 1. 按相反顺序调用插件 `stop_async`（每个插件最多等待 `stop_timeout`），外部输入（分发器插件）随之停止；此时消息循环仍在运行，
+    ///    插件可以在 `stop` 中发出最后的消息
+    /// 2. 关闭消息入口并排空消息循环中剩余的消息
+    /// 3. 关闭分发中心，订阅者收到关闭信号
+    ///
+    /// 有插件停止失败时仍会完成第 2、3 步，之后返回汇总的错误
+    pub async fn run_until<F>(mut self, shutdown: F) -> Result<()>
+    where
+        F: Future<Output = ()>,
+    {
         if self.show_startup_message {
             tracing::info!("=== Amadeus 插件系统启动 ===");
         }
@@ -103,6 +197,9 @@ impl App {
             }
         }
 
+        // 先初始化插件，之后的消息订阅可以依赖 init 的结果
+        self.registry.init_all()?;
+
         // 如果启用了消息系统，设置插件的消息订阅并启动分发器
         if let Some(ref mut msg_mgr) = self.message_manager {
             // 设置所有插件的消息订阅
@@ -113,23 +210,21 @@ impl App {
             msg_mgr.start_message_loop();
         }
 
-        // 执行插件启动流程
-        self.registry.startup()?;
+        // 启动插件
+        self.registry.start_all()?;
 
         // 保持运行，直到收到停止信号
         tracing::info!("服务正在运行... (按 Ctrl+C 停止)");
-        match tokio::signal::ctrl_c().await {
-            Ok(()) => tracing::info!("收到停止信号，正在关闭..."),
-            Err(err) => tracing::error!("监听信号失败: {}", err),
-        }
+        shutdown.await;
 
-        // 执行插件停止流程
-        self.registry.shutdown()?;
+        // 执行插件停止流程，单个插件超时或失败不会阻塞整体关闭
+        let stopped = self.registry.shutdown_async(self.stop_timeout).await;
 
-        // 停止分发器和消息循环
+        // 插件全部停止后再排空消息循环并关闭分发中心，即使有插件停止失败
         if let Some(ref mut msg_mgr) = self.message_manager {
-            msg_mgr.stop_message_loop().await;
+            msg_mgr.shutdown().await;
         }
+        stopped?;
 
         if self.show_startup_message {
             tracing::info!("=== Amadeus 插件系统已关闭 ===");
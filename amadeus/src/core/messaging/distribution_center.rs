@@ -1,7 +1,155 @@
 use super::message::{Message, MessageType};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 
+/// 死信队列的最大长度，超出后丢弃最旧的死信
+const DEAD_LETTER_CAPACITY: usize = 1024;
+
+/// 每个插件默认允许的活跃订阅数
+pub const DEFAULT_SUBSCRIPTION_LIMIT: usize = 256;
+
+/// 默认的消息 payload 上限（序列化后的字节数）：不限制
+///
+/// 不限制时发送和分发都不会序列化 payload 来计算大小
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = usize::MAX;
+
+/// 超限消息的拒绝原因
+pub(crate) fn oversized_reason(message: &Message, size: usize, limit: usize) -> String {
+    format!(
+        "消息 payload 过大 (类型: {}, {} 字节，上限 {} 字节)",
+        message.message_type.as_str(), size, limit
+    )
+}
+
+/// 广播通道溢出策略
+///
+/// tokio broadcast 在接收者落后时会覆盖最旧的消息，此策略决定被覆盖的消息如何处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverflowPolicy {
+    /// 跳过被覆盖的消息，仅计数（默认行为）
+    #[default]
+    SkipAndCount,
+    /// 将被覆盖的消息放入死信队列
+    DeadLetter,
+    /// 将订阅者标记为降级状态
+    ///
+    /// 每个使用此策略的订阅者拥有独立的广播通道，只有落后的那个订阅者会被标记
+    MarkDegraded,
+}
+
+/// 分区订阅：同一消费组的多个订阅者按路由键分摊同一类型的消息
+///
+/// 每个订阅者只接收 [`Message::partition`] 等于自身 `index` 的消息；
+/// 组内所有订阅者须使用相同的 `count`，各自的 `index` 覆盖 `0..count` 时每条消息恰好被处理一次
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    /// 消费组名称
+    pub group: String,
+    /// 组内分区总数
+    pub count: usize,
+    /// 本订阅者负责的分区（从 0 开始）
+    pub index: usize,
+}
+
+impl Partition {
+    pub fn new(group: impl Into<String>, count: usize, index: usize) -> Self {
+        Self { group: group.into(), count, index }
+    }
+
+    /// 消息是否属于本分区
+    pub fn contains(&self, message: &Message) -> bool {
+        message.partition(self.count) == self.index
+    }
+}
+
+/// 死信 - 未能成功投递的消息
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    /// 原始消息
+    pub message: Message,
+    /// 进入死信队列的原因
+    pub reason: String,
+}
+
+/// 单个消息类型的广播通道及其溢出状态
+///
+/// 每种溢出策略使用独立的广播通道，从而能判断落后的接收者使用的是哪种策略；
+/// MarkDegraded 订阅者再按插件各用一个通道，从而能判断具体是哪个插件落后
+struct TopicChannel {
+    /// 溢出策略到对应广播发送器的映射（不含 MarkDegraded）
+    senders: HashMap<OverflowPolicy, broadcast::Sender<Message>>,
+    /// MarkDegraded 订阅者（插件名）到其独立广播发送器的映射
+    monitored: HashMap<String, broadcast::Sender<Message>>,
+    /// 订阅者（插件名）到其溢出策略的映射
+    subscriber_policies: HashMap<String, OverflowPolicy>,
+    /// 分发时更新的状态；分发只持有主题表的读锁，因此单独加锁
+    state: std::sync::Mutex<TopicState>,
+}
+
+struct TopicState {
+    /// 最近发送给 DeadLetter 订阅者的消息，用于找回被覆盖的消息
+    recent: VecDeque<Message>,
+    /// 因接收者落后而被覆盖的消息数
+    dropped: u64,
+    /// 最近一次发布或订阅的时间，用于清理长期闲置的主题
+    last_activity: Instant,
+}
+
+/// 一次主题分发产生的溢出结果，在释放主题表的锁之后处理
+#[derive(Default)]
+struct TopicOverflow {
+    /// 被覆盖、需要放入死信队列的消息
+    evicted: Option<Message>,
+    /// 需要标记为降级的订阅者
+    degraded: Vec<String>,
+}
+
+impl TopicChannel {
+    fn new() -> Self {
+        Self {
+            senders: HashMap::new(),
+            monitored: HashMap::new(),
+            subscriber_policies: HashMap::new(),
+            state: std::sync::Mutex::new(TopicState {
+                recent: VecDeque::new(),
+                dropped: 0,
+                last_activity: Instant::now(),
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, TopicState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 获取订阅者应使用的广播发送器，不存在时创建
+    ///
+    /// MarkDegraded 订阅者使用自己的发送器，顺带清理接收端已全部丢弃的同类发送器
+    fn sender(&mut self, plugin_name: &str, policy: OverflowPolicy, capacity: usize) -> &broadcast::Sender<Message> {
+        if policy == OverflowPolicy::MarkDegraded {
+            self.monitored.retain(|_, sender| sender.receiver_count() > 0);
+            return self
+                .monitored
+                .entry(plugin_name.to_string())
+                .or_insert_with(|| broadcast::channel(capacity).0);
+        }
+        self.senders
+            .entry(policy)
+            .or_insert_with(|| broadcast::channel(capacity).0)
+    }
+
+    /// 所有策略下的接收者总数
+    fn receiver_count(&self) -> usize {
+        self.senders
+            .values()
+            .chain(self.monitored.values())
+            .map(broadcast::Sender::receiver_count)
+            .sum()
+    }
+}
+
 /// 分发中心 - 负责消息的路由和分发
 /// 
 /// 使用 tokio::sync::broadcast 实现发布-订阅模式（进程内通信）：
@@ -12,17 +160,31 @@ use tokio::sync::broadcast;
 /// 注意：此组件用于进程内通信（插件之间）。
 /// 进程间通信由 Dispatcher（如 Iceoryx2Dispatcher）处理。
 pub struct DistributionCenter {
-    /// 消息类型到广播发送器的映射
-    /// 使用 broadcast channel 实现一对多的消息分发
-    channels: std::sync::Arc<tokio::sync::RwLock<HashMap<MessageType, broadcast::Sender<Message>>>>,
+    /// 消息类型到广播通道的映射
+    /// 使用 broadcast channel 实现一对多的消息分发；分发只取读锁，订阅变更时才取写锁
+    channels: std::sync::Arc<tokio::sync::RwLock<HashMap<MessageType, TopicChannel>>>,
     /// 插件ID到定向消息发送器的映射
     direct_channels: std::sync::Arc<tokio::sync::RwLock<HashMap<String, tokio::sync::mpsc::Sender<Message>>>>,
+    /// 消息类型到希望在定向通道上接收该类型广播的插件ID
+    direct_interests: std::sync::Arc<tokio::sync::RwLock<HashMap<MessageType, HashSet<String>>>>,
     /// 全局订阅者（接收所有广播消息）
     global_subscribers: std::sync::Arc<tokio::sync::RwLock<Vec<tokio::sync::broadcast::Sender<Message>>>>,
     /// 插件名称到其订阅的消息类型的映射（用于取消订阅）
     plugin_subscriptions: std::sync::Arc<tokio::sync::RwLock<HashMap<String, Vec<MessageType>>>>,
+    /// (消息类型, 消费组) 到该组分区总数的映射，用于发现组内分区数不一致的配置
+    partition_groups: std::sync::Arc<tokio::sync::RwLock<HashMap<(MessageType, String), usize>>>,
+    /// 死信队列
+    dead_letters: std::sync::Arc<tokio::sync::RwLock<VecDeque<DeadLetter>>>,
+    /// 被标记为降级的订阅者（插件名）
+    degraded_subscribers: std::sync::Arc<tokio::sync::RwLock<HashSet<String>>>,
     /// 广播通道的容量（默认 1024）
     channel_capacity: usize,
+    /// 新订阅默认使用的溢出策略
+    overflow_policy: OverflowPolicy,
+    /// 每个插件允许的活跃订阅数上限（所有克隆共享）
+    subscription_limit: std::sync::Arc<AtomicUsize>,
+    /// 消息 payload 序列化后的字节数上限（所有克隆共享）
+    max_payload_size: std::sync::Arc<AtomicUsize>,
 }
 
 impl DistributionCenter {
@@ -36,26 +198,138 @@ impl DistributionCenter {
         Self {
             channels: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             direct_channels: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            direct_interests: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             global_subscribers: std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new())),
             plugin_subscriptions: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            partition_groups: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            dead_letters: std::sync::Arc::new(tokio::sync::RwLock::new(VecDeque::new())),
+            degraded_subscribers: std::sync::Arc::new(tokio::sync::RwLock::new(HashSet::new())),
             channel_capacity: capacity,
+            overflow_policy: OverflowPolicy::default(),
+            subscription_limit: std::sync::Arc::new(AtomicUsize::new(DEFAULT_SUBSCRIPTION_LIMIT)),
+            max_payload_size: std::sync::Arc::new(AtomicUsize::new(DEFAULT_MAX_PAYLOAD_SIZE)),
+        }
+    }
+
+    /// 设置新订阅默认使用的溢出策略
+    ///
+    /// 策略在订阅时记录，之后修改默认策略不会影响已有订阅
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// 获取默认溢出策略
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// 设置每个插件允许的活跃订阅数上限
+    pub fn with_subscription_limit(self, limit: usize) -> Self {
+        self.set_subscription_limit(limit);
+        self
+    }
+
+    /// 运行时修改每个插件的订阅数上限（对所有克隆生效，已有订阅不受影响）
+    pub fn set_subscription_limit(&self, limit: usize) {
+        self.subscription_limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// 获取每个插件的订阅数上限
+    pub fn subscription_limit(&self) -> usize {
+        self.subscription_limit.load(Ordering::Relaxed)
+    }
+
+    /// 设置消息 payload 的字节数上限（对所有克隆生效）
+    pub fn set_max_payload_size(&self, limit: usize) {
+        self.max_payload_size.store(limit, Ordering::Relaxed);
+    }
+
+    /// 获取消息 payload 的字节数上限
+    pub fn max_payload_size(&self) -> usize {
+        self.max_payload_size.load(Ordering::Relaxed)
+    }
+
+    /// 消息 payload 超过上限时返回其字节数；未设置上限时不计算大小，直接返回 `None`
+    pub fn oversized_payload(&self, message: &Message) -> Option<usize> {
+        let limit = self.max_payload_size();
+        if limit == usize::MAX {
+            return None;
+        }
+        let size = message.payload_size();
+        (size > limit).then_some(size)
+    }
+
+    /// 检查消息 payload 是否超过上限，超过时返回说明原因的错误
+    pub fn check_payload_size(&self, message: &Message) -> anyhow::Result<()> {
+        match self.oversized_payload(message) {
+            Some(size) => Err(anyhow::anyhow!(oversized_reason(message, size, self.max_payload_size()))),
+            None => Ok(()),
         }
     }
 
     /// 订阅所有消息（全局订阅）
     pub async fn subscribe_all(&self, _plugin_name: impl Into<String>) -> tokio::sync::broadcast::Receiver<Message> {
         let mut globals = self.global_subscribers.write().await;
+        // 顺带清理接收端已被丢弃的全局订阅
+        globals.retain(|sender| sender.receiver_count() > 0);
         let (tx, rx) = tokio::sync::broadcast::channel(self.channel_capacity);
         globals.push(tx);
         rx
     }
 
+    /// 关闭分发中心
+    ///
+    /// 丢弃所有广播、全局和定向通道的发送端：订阅者在收完已缓冲的消息后会收到关闭信号，
+    /// 插件的消息处理循环随之退出。死信与统计信息保留以便排查
+    pub async fn shutdown(&self) {
+        self.channels.write().await.clear();
+        self.direct_channels.write().await.clear();
+        self.direct_interests.write().await.clear();
+        self.global_subscribers.write().await.clear();
+        self.plugin_subscriptions.write().await.clear();
+        self.partition_groups.write().await.clear();
+    }
+
     /// 注册定向消息通道
     pub async fn register_direct_channel(&self, plugin_id: impl Into<String>, sender: tokio::sync::mpsc::Sender<Message>) {
         let mut channels = self.direct_channels.write().await;
         channels.insert(plugin_id.into(), sender);
     }
 
+    /// 注销定向消息通道，同时清除该插件登记的定向兴趣
+    pub async fn unregister_direct_channel(&self, plugin_id: &str) {
+        let mut channels = self.direct_channels.write().await;
+        channels.remove(plugin_id);
+        drop(channels);
+
+        let mut interests = self.direct_interests.write().await;
+        interests.retain(|_, plugins| {
+            plugins.remove(plugin_id);
+            !plugins.is_empty()
+        });
+    }
+
+    /// 登记定向兴趣：该类型的广播消息也投递到插件的定向通道
+    ///
+    /// 插件只需一个 mpsc 接收器即可同时处理定向消息和选定的广播，不会像 broadcast 接收器那样落后丢消息。
+    /// 定向通道已满时消息进入死信队列，不会阻塞分发
+    pub async fn register_direct_interest(&self, plugin_id: impl Into<String>, message_type: impl Into<MessageType>) {
+        let mut interests = self.direct_interests.write().await;
+        interests.entry(message_type.into()).or_default().insert(plugin_id.into());
+    }
+
+    /// 取消定向兴趣
+    pub async fn unregister_direct_interest(&self, plugin_id: &str, message_type: &MessageType) {
+        let mut interests = self.direct_interests.write().await;
+        if let Some(plugins) = interests.get_mut(message_type) {
+            plugins.remove(plugin_id);
+            if plugins.is_empty() {
+                interests.remove(message_type);
+            }
+        }
+    }
+
     /// 发送定向消息
     pub async fn send_direct(&self, plugin_id: &str, message: Message) -> anyhow::Result<()> {
         let channels = self.direct_channels.read().await;
@@ -74,46 +348,188 @@ impl DistributionCenter {
     /// - `plugin_name`: 插件名称
     /// 
     /// # 返回值
-    /// 返回一个接收器，用于接收该类型的消息
+    /// 返回一个接收器，用于接收该类型的消息；插件的活跃订阅数达到上限时返回错误
     pub async fn subscribe(
         &self,
         message_type: impl Into<MessageType>,
         plugin_name: impl Into<String>,
-    ) -> broadcast::Receiver<Message> {
+    ) -> anyhow::Result<broadcast::Receiver<Message>> {
+        self.subscribe_with_policy(message_type, plugin_name, self.overflow_policy)
+            .await
+    }
+
+    /// 登记消息类型上的一个分区订阅
+    ///
+    /// `count` 为 0、`index` 越界，或与同组已登记的分区总数不一致时返回错误
+    /// （不一致会导致部分消息被重复处理或无人处理）
+    pub async fn register_partition(&self, message_type: impl Into<MessageType>, partition: &Partition) -> anyhow::Result<()> {
+        let message_type = message_type.into();
+        if partition.count == 0 || partition.index >= partition.count {
+            anyhow::bail!(
+                "无效的分区 {}/{} (消费组: {})",
+                partition.index, partition.count, partition.group
+            );
+        }
+        let mut groups = self.partition_groups.write().await;
+        let count = *groups
+            .entry((message_type.clone(), partition.group.clone()))
+            .or_insert(partition.count);
+        if count != partition.count {
+            anyhow::bail!(
+                "消费组 {} 在 {} 上的分区数为 {}，与请求的 {} 不一致",
+                partition.group, message_type.as_str(), count, partition.count
+            );
+        }
+        Ok(())
+    }
+
+    /// 使用指定的溢出策略订阅消息类型
+    ///
+    /// 插件的活跃订阅数达到上限，或该插件已以其他策略订阅同一类型时返回错误
+    pub async fn subscribe_with_policy(
+        &self,
+        message_type: impl Into<MessageType>,
+        plugin_name: impl Into<String>,
+        policy: OverflowPolicy,
+    ) -> anyhow::Result<broadcast::Receiver<Message>> {
         let message_type = message_type.into();
         let plugin_name = plugin_name.into();
+        let limit = self.subscription_limit();
 
-        // 获取或创建该消息类型的广播通道
+        // 达到上限时先清理已失效的订阅（清理自身需要写锁，须在下面加锁之前完成）
+        if self.subscription_count(&plugin_name).await >= limit {
+            self.prune_subscriptions().await;
+        }
+
+        // 按主题表、订阅记录的顺序加锁：上限检查与登记在同一组写锁下完成，并发订阅不会超限
         let mut channels = self.channels.write().await;
-        let sender = channels
+        let mut plugin_subs = self.plugin_subscriptions.write().await;
+        if plugin_subs.get(&plugin_name).map_or(0, Vec::len) >= limit {
+            tracing::warn!("[分发中心] 插件 {} 的订阅数已达上限 ({})，拒绝订阅 {}", plugin_name, limit, message_type.as_str());
+            anyhow::bail!("插件 {} 的订阅数已达上限 ({})", plugin_name, limit);
+        }
+        // 同一插件在同一主题上只能使用一种溢出策略，否则降级标记会落到错误的订阅上
+        if let Some(existing) = channels
+            .get(&message_type)
+            .and_then(|topic| topic.subscriber_policies.get(&plugin_name))
+            .filter(|existing| **existing != policy)
+        {
+            anyhow::bail!(
+                "插件 {} 已以 {:?} 策略订阅 {}，不能再以 {:?} 策略订阅",
+                plugin_name, existing, message_type.as_str(), policy
+            );
+        }
+
+        // 获取或创建该消息类型的广播通道
+        let topic = channels
             .entry(message_type.clone())
-            .or_insert_with(|| {
-                broadcast::channel(self.channel_capacity).0
-            })
-            .clone();
+            .or_insert_with(TopicChannel::new);
+        topic.subscriber_policies.insert(plugin_name.clone(), policy);
+        topic.state().last_activity = Instant::now();
+        let receiver = topic.sender(&plugin_name, policy, self.channel_capacity).subscribe();
 
         // 记录插件的订阅
-        let mut plugin_subs = self.plugin_subscriptions.write().await;
         plugin_subs
             .entry(plugin_name)
             .or_insert_with(Vec::new)
             .push(message_type);
 
         // 返回接收器
-        sender.subscribe()
+        Ok(receiver)
+    }
+
+    /// 获取插件当前记录的订阅数
+    pub async fn subscription_count(&self, plugin_name: &str) -> usize {
+        self.plugin_subscriptions
+            .read()
+            .await
+            .get(plugin_name)
+            .map_or(0, Vec::len)
+    }
+
+    /// 清理接收端已全部丢弃的订阅
+    ///
+    /// broadcast 接收器被丢弃时无法得知属于哪个插件，因此以消息类型为粒度清理：
+    /// 没有任何接收者的消息类型会被移除，并从所有插件的订阅记录中删除
+    ///
+    /// # 返回值
+    /// 返回被移除的消息类型数量
+    pub async fn prune_subscriptions(&self) -> usize {
+        let removed = self.remove_topics_where(|topic| topic.receiver_count() == 0).await;
+        if removed > 0 {
+            tracing::debug!("[分发中心] 已清理 {} 个无接收者的订阅主题", removed);
+        }
+        removed
+    }
+
+    /// 清理闲置的主题：没有接收者，且超过 `max_idle` 没有发布或订阅
+    ///
+    /// 与 [`Self::prune_subscriptions`] 不同，刚失去接收者的活跃主题会被保留，
+    /// 适合在长期运行的进程中定期调用以回收内存
+    ///
+    /// # 返回值
+    /// 返回被移除的消息类型数量
+    pub async fn prune_idle(&self, max_idle: Duration) -> usize {
+        let removed = self
+            .remove_topics_where(|topic| {
+                topic.receiver_count() == 0 && topic.state().last_activity.elapsed() >= max_idle
+            })
+            .await;
+        if removed > 0 {
+            tracing::debug!("[分发中心] 已清理 {} 个闲置主题 (闲置超过 {:?})", removed, max_idle);
+        }
+        removed
+    }
+
+    /// 移除满足条件的主题，并从所有插件的订阅记录中删除
+    async fn remove_topics_where<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(&TopicChannel) -> bool,
+    {
+        let mut channels = self.channels.write().await;
+        let closed: HashSet<MessageType> = channels
+            .iter()
+            .filter(|(_, topic)| predicate(topic))
+            .map(|(message_type, _)| message_type.clone())
+            .collect();
+        if closed.is_empty() {
+            return 0;
+        }
+        channels.retain(|message_type, _| !closed.contains(message_type));
+
+        let mut plugin_subs = self.plugin_subscriptions.write().await;
+        for types in plugin_subs.values_mut() {
+            types.retain(|t| !closed.contains(t));
+        }
+        plugin_subs.retain(|_, types| !types.is_empty());
+
+        closed.len()
     }
 
     /// 取消订阅消息类型
     pub async fn unsubscribe(&self, plugin_name: &str, message_type: &MessageType) {
+        let mut channels = self.channels.write().await;
+        if let Some(topic) = channels.get_mut(message_type) {
+            topic.subscriber_policies.remove(plugin_name);
+        }
+
         let mut plugin_subs = self.plugin_subscriptions.write().await;
         
         if let Some(types) = plugin_subs.get_mut(plugin_name) {
             types.retain(|t| t != message_type);
+            if types.is_empty() {
+                plugin_subs.remove(plugin_name);
+            }
         }
     }
 
     /// 取消插件的所有订阅
     pub async fn unsubscribe_all(&self, plugin_name: &str) {
+        let mut channels = self.channels.write().await;
+        for topic in channels.values_mut() {
+            topic.subscriber_policies.remove(plugin_name);
+        }
+
         let mut plugin_subs = self.plugin_subscriptions.write().await;
         plugin_subs.remove(plugin_name);
     }
@@ -127,28 +543,176 @@ impl DistributionCenter {
         
         // 1. 发送给特定类型的订阅者
         let channels = self.channels.read().await;
-        if let Some(sender) = channels.get(&message.message_type) {
-            count += sender.receiver_count();
-            let _ = sender.send(message.clone());
+        let overflow = channels.get(&message.message_type).map(|topic| {
+            count += topic.receiver_count();
+            self.send_to_topic(topic, message)
+        });
+        drop(channels);
+        if let Some(overflow) = overflow {
+            self.apply_overflow(message, overflow).await;
         }
 
         // 2. 发送给全局订阅者
         let globals = self.global_subscribers.read().await;
+        let mut has_closed = false;
         for sender in globals.iter() {
-            count += sender.receiver_count();
+            let receivers = sender.receiver_count();
+            if receivers == 0 {
+                has_closed = true;
+                continue;
+            }
+            count += receivers;
             let _ = sender.send(message.clone());
         }
+        drop(globals);
+        if has_closed {
+            // 接收端已被丢弃的全局订阅在短暂的写锁下清理
+            self.global_subscribers.write().await.retain(|sender| sender.receiver_count() > 0);
+        }
+
+        // 3. 发送给登记了定向兴趣的插件
+        count += self.send_to_direct_interests(message).await;
 
         count
     }
 
+    /// 将广播消息投递到登记了该类型定向兴趣的插件，返回成功投递的数量
+    async fn send_to_direct_interests(&self, message: &Message) -> usize {
+        let interests = self.direct_interests.read().await;
+        let Some(plugins) = interests.get(&message.message_type) else {
+            return 0;
+        };
+        let channels = self.direct_channels.read().await;
+        let mut delivered = 0;
+        let mut overflowed = Vec::new();
+        for plugin_id in plugins {
+            let Some(sender) = channels.get(plugin_id) else {
+                continue;
+            };
+            match sender.try_send(message.clone()) {
+                Ok(()) => delivered += 1,
+                Err(tokio::sync::mpsc::error::TrySendError::Full(message)) => overflowed.push((plugin_id.clone(), message)),
+                Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => {}
+            }
+        }
+        drop(channels);
+        drop(interests);
+
+        for (plugin_id, message) in overflowed {
+            self.push_dead_letter(message, format!("direct channel of {} is full", plugin_id)).await;
+        }
+        delivered
+    }
+
+    /// 发送消息到主题的各个策略通道
+    ///
+    /// 某个通道已满（即将覆盖其落后接收者尚未读取的最旧消息）时，按该通道的策略记录溢出结果
+    fn send_to_topic(&self, topic: &TopicChannel, message: &Message) -> TopicOverflow {
+        // broadcast 通道的实际容量会向上取整为 2 的幂
+        let capacity = self.channel_capacity.next_power_of_two();
+        let is_full = |sender: &broadcast::Sender<Message>| sender.receiver_count() > 0 && sender.len() >= capacity;
+        let mut state = topic.state();
+        let mut overflow = TopicOverflow::default();
+        state.last_activity = Instant::now();
+
+        let mut lagged = false;
+        for (policy, sender) in topic.senders.iter() {
+            if !is_full(sender) {
+                continue;
+            }
+            lagged = true;
+            // 通道中保存的是最近发送的 capacity 条消息，即将被覆盖的就是其中最旧的一条
+            if *policy == OverflowPolicy::DeadLetter {
+                overflow.evicted = state.recent.pop_front();
+            }
+        }
+        for (plugin, sender) in topic.monitored.iter() {
+            if is_full(sender) {
+                lagged = true;
+                overflow.degraded.push(plugin.clone());
+            }
+        }
+        if lagged {
+            state.dropped += 1;
+        }
+
+        for (policy, sender) in topic.senders.iter() {
+            if sender.send(message.clone()).is_ok() && *policy == OverflowPolicy::DeadLetter {
+                state.recent.push_back(message.clone());
+                while state.recent.len() > capacity {
+                    state.recent.pop_front();
+                }
+            }
+        }
+        for sender in topic.monitored.values() {
+            let _ = sender.send(message.clone());
+        }
+        overflow
+    }
+
+    /// 处理主题分发的溢出结果：放入死信队列、标记降级的订阅者
+    async fn apply_overflow(&self, message: &Message, overflow: TopicOverflow) {
+        if let Some(evicted) = overflow.evicted {
+            self.push_dead_letter(evicted, "subscriber lagged behind").await;
+        }
+        if !overflow.degraded.is_empty() {
+            let mut degraded = self.degraded_subscribers.write().await;
+            for plugin in overflow.degraded {
+                if degraded.insert(plugin.clone()) {
+                    tracing::warn!("[分发中心] 订阅者 {} 处理过慢，已标记为降级 (类型: {})", plugin, message.message_type.as_str());
+                }
+            }
+        }
+    }
+
+    /// 将消息放入死信队列
+    pub async fn push_dead_letter(&self, message: Message, reason: impl Into<String>) {
+        let reason = reason.into();
+        tracing::warn!("[分发中心] 消息进入死信队列 (类型: {}): {}", message.message_type.as_str(), reason);
+        let mut dead_letters = self.dead_letters.write().await;
+        if dead_letters.len() >= DEAD_LETTER_CAPACITY {
+            dead_letters.pop_front();
+        }
+        dead_letters.push_back(DeadLetter { message, reason });
+    }
+
+    /// 获取死信队列中的所有消息（不清空）
+    pub async fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.read().await.iter().cloned().collect()
+    }
+
+    /// 取出并清空死信队列
+    pub async fn take_dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.write().await.drain(..).collect()
+    }
+
+    /// 获取某个消息类型因接收者落后而被覆盖的消息数
+    pub async fn dropped_count(&self, message_type: &MessageType) -> u64 {
+        self.channels
+            .read()
+            .await
+            .get(message_type)
+            .map(|t| t.state().dropped)
+            .unwrap_or(0)
+    }
+
+    /// 判断订阅者是否被标记为降级
+    pub async fn is_degraded(&self, plugin_name: &str) -> bool {
+        self.degraded_subscribers.read().await.contains(plugin_name)
+    }
+
+    /// 清除订阅者的降级标记
+    pub async fn clear_degraded(&self, plugin_name: &str) {
+        self.degraded_subscribers.write().await.remove(plugin_name);
+    }
+
     /// 获取订阅统计信息
     pub async fn get_subscription_stats(&self) -> HashMap<String, usize> {
         let channels = self.channels.read().await;
         let mut stats = HashMap::new();
         
-        for (message_type, sender) in channels.iter() {
-            stats.insert(message_type.as_str().to_string(), sender.receiver_count());
+        for (message_type, topic) in channels.iter() {
+            stats.insert(message_type.as_str().to_string(), topic.receiver_count());
         }
         
         stats
@@ -175,9 +739,16 @@ impl Clone for DistributionCenter {
         Self {
             channels: std::sync::Arc::clone(&self.channels),
             direct_channels: std::sync::Arc::clone(&self.direct_channels),
+            direct_interests: std::sync::Arc::clone(&self.direct_interests),
             global_subscribers: std::sync::Arc::clone(&self.global_subscribers),
             plugin_subscriptions: std::sync::Arc::clone(&self.plugin_subscriptions),
+            partition_groups: std::sync::Arc::clone(&self.partition_groups),
+            dead_letters: std::sync::Arc::clone(&self.dead_letters),
+            degraded_subscribers: std::sync::Arc::clone(&self.degraded_subscribers),
             channel_capacity: self.channel_capacity,
+            overflow_policy: self.overflow_policy,
+            subscription_limit: std::sync::Arc::clone(&self.subscription_limit),
+            max_payload_size: std::sync::Arc::clone(&self.max_payload_size),
         }
     }
 }
@@ -1,17 +1,55 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use crate::core::UserContext;
 
+/// 元数据键：请求方希望接收定向回复的通道ID
+pub const REPLY_TO_METADATA_KEY: &str = "reply_to";
+
+/// 元数据键：分区订阅使用的路由键，未设置时回退到用户ID
+pub const PARTITION_KEY_METADATA_KEY: &str = "partition_key";
+
+/// 元数据键：[`Message::seal`] 写入的完整性校验值
+pub const SEAL_METADATA_KEY: &str = "seal";
+
+/// payload 为普通 JSON（未设置 `content_type` 时的默认含义）
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+/// payload 为 base64 编码的二进制数据（JSON 字符串）
+pub const CONTENT_TYPE_OCTET_STREAM: &str = "application/octet-stream";
+/// payload 为纯文本（JSON 字符串）
+pub const CONTENT_TYPE_TEXT: &str = "text/plain";
+
+/// 是否规范化消息类型（去除首尾空白并转为小写），默认关闭
+static CANONICALIZE_MESSAGE_TYPES: AtomicBool = AtomicBool::new(false);
+
+/// 全局开启或关闭消息类型规范化
+///
+/// 开启后 `MessageType::new`、`From` 转换以及反序列化得到的类型都会去除首尾空白并转为小写，
+/// 因此 `"System.Memo.Create "` 与订阅的 `system.memo.create` 匹配。
+/// 这会改变匹配语义：仅大小写不同的类型被视为同一类型。只影响开启之后创建的类型，
+/// 应在订阅和收发消息之前设置
+pub fn set_message_type_canonicalization(enabled: bool) {
+    CANONICALIZE_MESSAGE_TYPES.store(enabled, Ordering::Relaxed);
+}
+
+/// 当前是否规范化消息类型
+pub fn message_type_canonicalization() -> bool {
+    CANONICALIZE_MESSAGE_TYPES.load(Ordering::Relaxed)
+}
+
 /// 消息类型标识符
 /// 插件通过消息类型来订阅感兴趣的消息
+///
+/// 开启 [`set_message_type_canonicalization`] 后构造时会规范化
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String")]
 pub struct MessageType(pub String);
 
 impl MessageType {
     /// 创建新的消息类型
     pub fn new(ty: impl Into<String>) -> Self {
-        Self(ty.into())
+        Self::from(ty.into())
     }
 
     /// 获取消息类型字符串
@@ -22,18 +60,24 @@ impl MessageType {
 
 impl From<&str> for MessageType {
     fn from(s: &str) -> Self {
-        Self(s.to_string())
+        Self::from(s.to_string())
     }
 }
 
 impl From<String> for MessageType {
     fn from(s: String) -> Self {
-        Self(s)
+        if message_type_canonicalization() {
+            Self(s.trim().to_lowercase())
+        } else {
+            Self(s)
+        }
     }
 }
 
 /// 消息优先级
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+///
+/// 反序列化时同时接受数字（0-3）和名称（`"low"`、`"normal"`、`"high"`、`"critical"`，不区分大小写）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum MessagePriority {
     Low = 0,
     Normal = 1,
@@ -47,6 +91,90 @@ impl Default for MessagePriority {
     }
 }
 
+impl MessagePriority {
+    /// 由数字等级（0-3）转换，超出范围时返回 `None`
+    pub fn from_level(level: i64) -> Option<Self> {
+        match level {
+            0 => Some(Self::Low),
+            1 => Some(Self::Normal),
+            2 => Some(Self::High),
+            3 => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    /// 数字等级（0-3）
+    pub fn level(self) -> i32 {
+        self as i32
+    }
+
+    /// 小写名称，如 `"high"`
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for MessagePriority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+            other => Err(anyhow::anyhow!("unknown priority: {}", other)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MessagePriority {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PriorityVisitor;
+
+        impl serde::de::Visitor<'_> for PriorityVisitor {
+            type Value = MessagePriority;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a priority level 0-3 or one of low/normal/high/critical")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                MessagePriority::from_level(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                i64::try_from(v)
+                    .ok()
+                    .and_then(MessagePriority::from_level)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(PriorityVisitor)
+    }
+}
+
+/// 将数字或名称形式的优先级反序列化为数字等级，用于 `Option<i32>` 类型的优先级字段
+///
+/// 配合 `#[serde(default, deserialize_with = "...")]` 使用
+pub fn deserialize_priority_level<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<MessagePriority>::deserialize(deserializer)?.map(MessagePriority::level))
+}
+
 /// 消息来源
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MessageSource {
@@ -59,7 +187,9 @@ pub enum MessageSource {
 }
 
 /// 统一的消息格式
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `Debug` 输出与 [`Message::redacted`] 使用相同的脱敏规则（见 `redaction` 模块）
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Message {
     /// 消息类型
     pub message_type: MessageType,
@@ -80,6 +210,15 @@ pub struct Message {
     pub metadata: std::collections::HashMap<String, String>,
     /// 用户上下文（可选，用于权限控制）
     pub user_context: Option<UserContext>,
+    /// 投递次数（由消息管理器在分发时设置，首次投递为1，同一 message_id 重新投递时递增）
+    #[serde(default)]
+    pub delivery_attempt: u32,
+    /// payload 的内容类型（MIME 风格，如 `application/octet-stream`），`None` 表示 JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// 发送时已通过 payload 大小检查，消息循环据此跳过重复计算（仅在进程内有效）
+    #[serde(skip)]
+    pub(crate) payload_checked: bool,
 }
 
 impl Message {
@@ -98,9 +237,20 @@ impl Message {
             recipient: None,
             metadata: std::collections::HashMap::new(),
             user_context: None,
+            delivery_attempt: 0,
+            content_type: None,
+            payload_checked: false,
         }
     }
 
+    /// 由可序列化的引用创建消息，直接序列化为 payload，调用方无需先克隆出一个 `Value`
+    pub fn new_ref<T: Serialize + ?Sized>(
+        message_type: impl Into<MessageType>,
+        payload: &T,
+    ) -> serde_json::Result<Self> {
+        Ok(Self::new(message_type, serde_json::to_value(payload)?))
+    }
+
     /// 创建定向消息
     pub fn new_direct(
         target_id: impl Into<String>,
@@ -117,6 +267,9 @@ impl Message {
             recipient: Some(target_id.into()),
             metadata: std::collections::HashMap::new(),
             user_context: None,
+            delivery_attempt: 0,
+            content_type: None,
+            payload_checked: false,
         }
     }
 
@@ -136,6 +289,9 @@ impl Message {
             recipient: None,
             metadata: std::collections::HashMap::new(),
             user_context: None,
+            delivery_attempt: 0,
+            content_type: None,
+            payload_checked: false,
         }
     }
 
@@ -155,7 +311,50 @@ impl Message {
             recipient: None,
             metadata: std::collections::HashMap::new(),
             user_context: None,
+            delivery_attempt: 0,
+            content_type: None,
+            payload_checked: false,
+        }
+    }
+
+    /// 创建二进制消息：payload 为 base64 字符串，内容类型为 [`CONTENT_TYPE_OCTET_STREAM`]
+    pub fn binary(message_type: impl Into<MessageType>, data: &[u8]) -> Self {
+        use base64::Engine;
+        Self::new(message_type, serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(data)))
+            .with_content_type(CONTENT_TYPE_OCTET_STREAM)
+    }
+
+    /// 设置 payload 的内容类型
+    pub fn with_content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// payload 的内容类型，未设置时为 [`CONTENT_TYPE_JSON`]
+    pub fn content_type(&self) -> &str {
+        self.content_type.as_deref().unwrap_or(CONTENT_TYPE_JSON)
+    }
+
+    /// 判断 payload 是否为普通 JSON
+    pub fn is_json(&self) -> bool {
+        self.content_type() == CONTENT_TYPE_JSON
+    }
+
+    /// 解码二进制 payload；内容类型不是 [`CONTENT_TYPE_OCTET_STREAM`] 或不是合法 base64 时返回 `None`
+    pub fn binary_payload(&self) -> Option<Vec<u8>> {
+        use base64::Engine;
+        if self.content_type() != CONTENT_TYPE_OCTET_STREAM {
+            return None;
         }
+        base64::engine::general_purpose::STANDARD.decode(self.payload.as_str()?).ok()
+    }
+
+    /// 从借用的 payload 反序列化，不克隆 `Value`
+    ///
+    /// 与 `serde_json::from_value(msg.payload.clone())` 结果相同；
+    /// 目标类型中的 `&str` 字段会直接借用 payload 中的字符串
+    pub fn payload_as<'a, T: Deserialize<'a>>(&'a self) -> serde_json::Result<T> {
+        T::deserialize(&self.payload)
     }
 
     /// 设置优先级
@@ -176,6 +375,36 @@ impl Message {
         self
     }
 
+    /// 序列化后的 payload 字节数（不分配缓冲区）
+    pub fn payload_size(&self) -> usize {
+        struct Counter(usize);
+        impl std::io::Write for Counter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0 += buf.len();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut counter = Counter(0);
+        let _ = serde_json::to_writer(&mut counter, &self.payload);
+        counter.0
+    }
+
+    /// 用于日志的展示形式，敏感字段替换为 `***`
+    ///
+    /// 敏感字段来自按类型前缀配置的规则（见 [`super::register_sensitive_fields`]）
+    /// 以及元数据 `sensitive` 中列出的路径
+    pub fn redacted(&self) -> super::redaction::Redacted<'_> {
+        super::redaction::Redacted(self)
+    }
+
+    /// 判断是否为重新投递的消息
+    pub fn is_redelivery(&self) -> bool {
+        self.delivery_attempt > 1
+    }
+
     /// 判断是否为广播消息
     pub fn is_public(&self) -> bool {
         self.recipient.is_none()
@@ -186,6 +415,39 @@ impl Message {
         self.recipient.is_some()
     }
 
+    /// 获取定向回复的目标
+    ///
+    /// 优先使用元数据中的 `reply_to`，否则回退到来源插件名称
+    pub fn reply_target(&self) -> Option<String> {
+        if let Some(reply_to) = self.metadata.get(REPLY_TO_METADATA_KEY) {
+            return Some(reply_to.clone());
+        }
+        match &self.source {
+            MessageSource::Plugin(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// 分区订阅使用的路由键：元数据 `partition_key`，否则为用户ID
+    pub fn routing_key(&self) -> Option<&str> {
+        self.metadata
+            .get(PARTITION_KEY_METADATA_KEY)
+            .map(String::as_str)
+            .or_else(|| self.user_context.as_ref().map(|ctx| ctx.user.id.0.as_str()))
+    }
+
+    /// 消息在 `count` 个分区中所属的分区
+    ///
+    /// 路由键经 FNV-1a 哈希后取模，同一路由键总是落在同一分区（跨进程、跨版本稳定）；
+    /// 没有路由键的消息归入分区 0，保证每条消息恰好被一个分区处理
+    pub fn partition(&self, count: usize) -> usize {
+        let count = count.max(1);
+        match self.routing_key() {
+            Some(key) => (fnv1a(key.as_bytes()) % count as u64) as usize,
+            None => 0,
+        }
+    }
+
     /// 添加元数据
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
@@ -198,6 +460,42 @@ impl Message {
         self
     }
 
+    /// 封印消息：对不可变字段计算校验值并写入元数据 `seal`
+    ///
+    /// 覆盖 message_type、payload、priority、timestamp、message_id、recipient 和 content_type；
+    /// source、delivery_attempt、user_context 及其他元数据会在流转中被合法修改，不参与校验。
+    /// 用于调试消息在线程间传递时被意外修改的问题，默认不启用
+    pub fn seal(mut self) -> Self {
+        let checksum = self.seal_checksum();
+        self.metadata.insert(SEAL_METADATA_KEY.to_string(), checksum);
+        self
+    }
+
+    /// 校验封印：未封印时返回 `None`，封印后字段被修改时返回 `Some(false)`
+    pub fn verify_seal(&self) -> Option<bool> {
+        let seal = self.metadata.get(SEAL_METADATA_KEY)?;
+        Some(*seal == self.seal_checksum())
+    }
+
+    /// 不可变字段的 SHA-256（十六进制）
+    fn seal_checksum(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let fields = serde_json::json!([
+            self.message_type.as_str(),
+            self.payload,
+            self.priority.level(),
+            self.timestamp,
+            self.message_id,
+            self.recipient,
+            self.content_type,
+        ]);
+        Sha256::digest(fields.to_string().as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
     /// 获取当前时间戳（毫秒）
     fn current_timestamp() -> u64 {
         SystemTime::now()
@@ -228,3 +526,7 @@ pub enum MessageHandleResult {
     Failed(String),
 }
 
+/// 64 位 FNV-1a 哈希
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3))
+}
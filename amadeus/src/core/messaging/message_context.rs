@@ -1,8 +1,69 @@
-use super::distribution_center::DistributionCenter;
-use super::message::{Message, MessageType, MessageSource};
+use super::distribution_center::{oversized_reason, DistributionCenter, Partition};
+use super::message::{Message, MessageType, MessageSource, REPLY_TO_METADATA_KEY};
 use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use tokio::sync::mpsc::error::TrySendError as ChannelTrySendError;
+
+/// [`MessageContext::try_send`] 的错误，每种情况都返还原消息，便于调用方重试或丢弃
+#[derive(Debug)]
+pub enum TrySendError {
+    /// 通道已满
+    Full(Message),
+    /// 消息循环已停止
+    Closed(Message),
+    /// payload 超过分发中心的大小上限
+    PayloadTooLarge { message: Message, size: usize, limit: usize },
+}
+
+impl TrySendError {
+    /// 取回未发送的消息
+    pub fn into_inner(self) -> Message {
+        match self {
+            Self::Full(message) | Self::Closed(message) => message,
+            Self::PayloadTooLarge { message, .. } => message,
+        }
+    }
+}
+
+impl std::fmt::Display for TrySendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "消息通道已满"),
+            Self::Closed(_) => write!(f, "消息通道已关闭"),
+            Self::PayloadTooLarge { message, size, limit } => {
+                f.write_str(&oversized_reason(message, *size, *limit))
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrySendError {}
+
+impl From<ChannelTrySendError<Message>> for TrySendError {
+    fn from(e: ChannelTrySendError<Message>) -> Self {
+        match e {
+            ChannelTrySendError::Full(message) => Self::Full(message),
+            ChannelTrySendError::Closed(message) => Self::Closed(message),
+        }
+    }
+}
+
+/// 插件向自身 UID 发送定向消息时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelfAddressPolicy {
+    /// 照常投递（默认行为，与其他定向消息相同）
+    #[default]
+    Deliver,
+    /// 丢弃并记录警告
+    Ignore,
+    /// 只投递第一条发给自身的定向消息，之后的都丢弃并记录警告，避免回环
+    ///
+    /// 是否已投递由上下文记录（所有克隆共享），与消息本身无关，因此新构造的消息同样会被拦截
+    DeliverOnce,
+}
 
 /// 消息上下文
 /// 
@@ -14,6 +75,10 @@ pub struct MessageContext {
     plugin_uid: String,
     /// 消息发送通道（用于发送消息到分发中心）
     message_tx: tokio::sync::mpsc::Sender<Message>,
+    /// 发给自身 UID 的定向消息如何处理
+    self_address_policy: SelfAddressPolicy,
+    /// 是否已经投递过发给自身的定向消息（`DeliverOnce` 使用，所有克隆共享）
+    self_delivered: Arc<AtomicBool>,
 }
 
 impl MessageContext {
@@ -29,6 +94,37 @@ impl MessageContext {
             plugin_name: plugin_name.into(),
             plugin_uid: plugin_uid.into(),
             message_tx,
+            self_address_policy: SelfAddressPolicy::default(),
+            self_delivered: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 设置发给自身 UID 的定向消息的处理方式（默认照常投递）
+    ///
+    /// 收到定向消息后会回复发送方的对称插件应选择 `Ignore` 或 `DeliverOnce`，避免自发自收形成回环
+    pub fn with_self_address_policy(mut self, policy: SelfAddressPolicy) -> Self {
+        self.self_address_policy = policy;
+        self
+    }
+
+    /// 按 [`SelfAddressPolicy`] 检查发给自身的定向消息，返回 `false` 表示应丢弃
+    fn admit_self_addressed(&self, message: &Message) -> bool {
+        if message.recipient.as_deref() != Some(self.plugin_uid.as_str()) {
+            return true;
+        }
+        match self.self_address_policy {
+            SelfAddressPolicy::Deliver => true,
+            SelfAddressPolicy::Ignore => {
+                tracing::warn!("[{}] 忽略发给自身的定向消息: {}", self.plugin_name, message.message_type.as_str());
+                false
+            }
+            SelfAddressPolicy::DeliverOnce => {
+                if self.self_delivered.swap(true, Ordering::AcqRel) {
+                    tracing::warn!("[{}] 丢弃回环的自发消息: {}", self.plugin_name, message.message_type.as_str());
+                    return false;
+                }
+                true
+            }
         }
     }
 
@@ -39,12 +135,46 @@ impl MessageContext {
     /// 
     /// # 返回值
     /// - 返回一个广播接收器，用于接收该类型的公共消息
-    pub async fn subscribe(&self, message_type: impl Into<MessageType>) -> broadcast::Receiver<Message> {
+    /// - 插件的订阅数达到分发中心的上限时返回错误
+    pub async fn subscribe(&self, message_type: impl Into<MessageType>) -> anyhow::Result<broadcast::Receiver<Message>> {
         self.distribution_center
             .subscribe(message_type, &self.plugin_name)
             .await
     }
 
+    /// 订阅消息类型，只接收满足 `predicate` 的消息
+    ///
+    /// 过滤在返回的接收器中进行，不影响分发中心的广播开销；
+    /// 不满足条件的消息被直接跳过，不会唤醒调用方
+    pub async fn subscribe_filtered<F>(
+        &self,
+        message_type: impl Into<MessageType>,
+        predicate: F,
+    ) -> anyhow::Result<FilteredReceiver>
+    where
+        F: Fn(&Message) -> bool + Send + Sync + 'static,
+    {
+        let inner = self.subscribe(message_type).await?;
+        Ok(FilteredReceiver { inner, predicate: Box::new(predicate) })
+    }
+
+    /// 以分区方式订阅消息类型，只接收路由键落在 `partition` 上的消息
+    ///
+    /// 路由键取元数据 `partition_key`，否则为用户ID，见 [`Message::partition`]；
+    /// 分区参数无效或与同组其他订阅者的分区数不一致时返回错误
+    pub async fn subscribe_partitioned(
+        &self,
+        message_type: impl Into<MessageType>,
+        partition: Partition,
+    ) -> anyhow::Result<FilteredReceiver> {
+        let message_type = message_type.into();
+        self.distribution_center
+            .register_partition(message_type.clone(), &partition)
+            .await?;
+        self.subscribe_filtered(message_type, move |message| partition.contains(message))
+            .await
+    }
+
     /// 订阅所有公共消息
     /// 
     /// # 返回值
@@ -69,10 +199,25 @@ impl MessageContext {
         rx
     }
 
+    /// 让指定类型的广播消息也投递到本插件的定向通道（需先 [`enable_direct_messaging`](Self::enable_direct_messaging)）
+    pub async fn register_direct_interest(&self, message_type: impl Into<MessageType>) {
+        self.distribution_center
+            .register_direct_interest(&self.plugin_uid, message_type)
+            .await
+    }
+
     /// 发送消息
     /// 
     /// 消息会被分发中心路由给所有订阅了该消息类型的插件和分发器
+    /// payload 超过分发中心的大小上限时直接返回错误，消息不会进入队列
+    /// 发给自身 UID 的定向消息按 [`SelfAddressPolicy`] 处理，被丢弃时返回 `Ok`
     pub async fn send(&self, mut message: Message) -> Result<()> {
+        self.distribution_center.check_payload_size(&message)?;
+        message.payload_checked = true;
+        if !self.admit_self_addressed(&message) {
+            return Ok(());
+        }
+
         // 确保消息来源设置为当前插件
         message.source = MessageSource::Plugin(self.plugin_name.clone());
         
@@ -83,6 +228,82 @@ impl MessageContext {
         Ok(())
     }
 
+    /// 非阻塞发送消息
+    ///
+    /// 通道已满时立即返回 [`TrySendError::Full`]（携带原消息），适合突发发送大量消息的调用方；
+    /// payload 超限时与 [`send`](Self::send) 一样不进入队列，返回 [`TrySendError::PayloadTooLarge`]
+    #[allow(clippy::result_large_err)] // 错误中返还原消息，便于调用方重试
+    pub fn try_send(&self, mut message: Message) -> std::result::Result<(), TrySendError> {
+        if let Some(size) = self.distribution_center.oversized_payload(&message) {
+            let limit = self.distribution_center.max_payload_size();
+            return Err(TrySendError::PayloadTooLarge { message, size, limit });
+        }
+        message.payload_checked = true;
+        if !self.admit_self_addressed(&message) {
+            return Ok(());
+        }
+        message.source = MessageSource::Plugin(self.plugin_name.clone());
+        Ok(self.message_tx.try_send(message)?)
+    }
+
+    /// 发送消息，通道已满时最多等待 `timeout`，超时返回错误而不是无限阻塞
+    pub async fn send_timeout(&self, mut message: Message, timeout: Duration) -> Result<()> {
+        self.distribution_center.check_payload_size(&message)?;
+        message.payload_checked = true;
+        if !self.admit_self_addressed(&message) {
+            return Ok(());
+        }
+        message.source = MessageSource::Plugin(self.plugin_name.clone());
+        self.message_tx.send_timeout(message, timeout).await
+            .map_err(|e| anyhow::anyhow!("发送消息失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 发送定向请求并等待对应的定向回复
+    ///
+    /// 为本次请求生成新的 message_id，并注册一个临时的回复通道（写入元数据 `reply_to`），
+    /// 接收方应使用 [`Message::reply_target`] 作为回复目标，并保留请求的 message_id。
+    /// 临时通道在返回前注销，不会影响插件已启用的定向消息通道。
+    ///
+    /// # 参数
+    /// - `target`: 目标插件的 UID
+    /// - `message`: 请求消息
+    /// - `timeout`: 等待回复的超时时间
+    pub async fn request_direct(
+        &self,
+        target: impl Into<String>,
+        mut message: Message,
+        timeout: Duration,
+    ) -> Result<Message> {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let reply_channel = format!("{}#{}", self.plugin_uid, request_id);
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        self.distribution_center.register_direct_channel(&reply_channel, tx).await;
+
+        message.message_id = Some(request_id.clone());
+        message.recipient = Some(target.into());
+        message.metadata.insert(REPLY_TO_METADATA_KEY.to_string(), reply_channel.clone());
+
+        let result = async {
+            self.send(message).await?;
+            tokio::time::timeout(timeout, async {
+                while let Some(reply) = rx.recv().await {
+                    if reply.message_id.as_deref() == Some(request_id.as_str()) {
+                        return Ok(reply);
+                    }
+                }
+                Err(anyhow::anyhow!("回复通道已关闭"))
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("等待定向回复超时 (请求: {})", request_id))?
+        }
+        .await;
+
+        self.distribution_center.unregister_direct_channel(&reply_channel).await;
+        result
+    }
+
     /// 获取分发中心的引用
     pub fn distribution_center(&self) -> &Arc<DistributionCenter> {
         &self.distribution_center
@@ -98,6 +319,36 @@ impl MessageContext {
     }
 }
 
+/// 带过滤条件的广播接收器，由 [`MessageContext::subscribe_filtered`] 创建
+pub struct FilteredReceiver {
+    inner: broadcast::Receiver<Message>,
+    predicate: Box<dyn Fn(&Message) -> bool + Send + Sync>,
+}
+
+impl FilteredReceiver {
+    /// 接收下一条满足过滤条件的消息
+    ///
+    /// 错误语义与 `broadcast::Receiver::recv` 相同（落后时返回 `Lagged`，通道关闭时返回 `Closed`）
+    pub async fn recv(&mut self) -> std::result::Result<Message, broadcast::error::RecvError> {
+        loop {
+            let message = self.inner.recv().await?;
+            if (self.predicate)(&message) {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// 非阻塞地接收下一条满足过滤条件的消息，跳过所有不满足条件的已缓冲消息
+    pub fn try_recv(&mut self) -> std::result::Result<Message, broadcast::error::TryRecvError> {
+        loop {
+            let message = self.inner.try_recv()?;
+            if (self.predicate)(&message) {
+                return Ok(message);
+            }
+        }
+    }
+}
+
 impl Clone for MessageContext {
     fn clone(&self) -> Self {
         Self {
@@ -105,6 +356,8 @@ impl Clone for MessageContext {
             plugin_name: self.plugin_name.clone(),
             plugin_uid: self.plugin_uid.clone(),
             message_tx: self.message_tx.clone(),
+            self_address_policy: self.self_address_policy,
+            self_delivered: Arc::clone(&self.self_delivered),
         }
     }
 }
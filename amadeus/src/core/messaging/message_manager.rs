@@ -1,9 +1,61 @@
-use super::distribution_center::DistributionCenter;
+use super::distribution_center::{oversized_reason, DistributionCenter};
 use super::message::Message;
 use anyhow::Result;
-use std::sync::Arc;
+use super::message::MessageType;
+use crate::util::TtlLruCache;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// 元数据键：被别名改写前的原始消息类型
+pub const ORIGINAL_TYPE_METADATA_KEY: &str = "original_type";
+
+/// payload 超限的消息被拒绝时广播的事件类型
+pub const MESSAGE_REJECTED_TYPE: &str = "system.message.rejected";
+
+/// 停止消息循环时等待排空队列的最长时间，超时后强制终止
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 投递次数跟踪的 message_id 上限，超出后淘汰最久未投递的ID
+const DELIVERY_TRACKING_CAPACITY: usize = 4096;
+/// 投递次数记录的保留时间，超过后同一 message_id 视为首次投递
+const DELIVERY_TRACKING_TTL: Duration = Duration::from_secs(600);
+
+/// 消息拦截器：在分发前对每条消息调用，可修改消息，返回 `None` 则丢弃该消息
+pub type MessageInterceptor = Box<dyn Fn(Message) -> Option<Message> + Send + Sync>;
+
+/// 记录每条消息（按消息类型和 message_id 区分）的投递次数
+///
+/// 回复沿用请求的 message_id，按类型区分后不会被误计为重复投递
+struct DeliveryTracker {
+    attempts: TtlLruCache<(MessageType, String), u32>,
+}
+
+impl Default for DeliveryTracker {
+    fn default() -> Self {
+        Self {
+            attempts: TtlLruCache::new(DELIVERY_TRACKING_CAPACITY, DELIVERY_TRACKING_TTL),
+        }
+    }
+}
+
+impl DeliveryTracker {
+    /// 记录一次投递并返回本次的投递序号（从1开始），没有 message_id 或为空时总是 1
+    fn record(&mut self, message: &Message) -> u32 {
+        let Some(id) = message.message_id.as_deref().filter(|id| !id.is_empty()) else {
+            return 1;
+        };
+        let key = (message.message_type.clone(), id.to_string());
+        if let Some(attempt) = self.attempts.get_mut(&key) {
+            *attempt += 1;
+            return *attempt;
+        }
+        self.attempts.insert(key, 1);
+        1
+    }
+}
+
 /// 消息管理器 (插件中心核心组件)
 /// 
 /// 负责协调插件间的消息路由和分发
@@ -15,6 +67,14 @@ pub struct MessageManager {
     message_tx: mpsc::Sender<Message>,
     /// 消息处理任务句柄
     message_task_handle: Option<tokio::task::JoinHandle<()>>,
+    /// 通知消息循环停止接收并排空队列
+    drain_tx: Option<tokio::sync::oneshot::Sender<()>>,
+    /// 消息类型别名表（旧类型 -> 新类型），在消息进入分发前改写
+    aliases: Arc<RwLock<HashMap<String, String>>>,
+    /// 分发前的消息拦截器
+    interceptor: Arc<RwLock<Option<MessageInterceptor>>>,
+    /// 分发前校验已封印消息的完整性（见 [`Message::seal`]）
+    verify_seals: bool,
 }
 
 impl MessageManager {
@@ -27,9 +87,75 @@ impl MessageManager {
             message_rx: Some(rx),
             message_tx: tx,
             message_task_handle: None,
+            drain_tx: None,
+            aliases: Arc::new(RwLock::new(HashMap::new())),
+            interceptor: Arc::new(RwLock::new(None)),
+            verify_seals: false,
         }
     }
 
+    /// 设置每个插件允许的活跃订阅数上限
+    pub fn with_subscription_limit(self, limit: usize) -> Self {
+        self.distribution_center.set_subscription_limit(limit);
+        self
+    }
+
+    /// 设置消息 payload 的字节数上限（按序列化后的 JSON 计算），默认不限制
+    ///
+    /// 超限的消息在分发前被拒绝：放入死信队列，并广播 `system.message.rejected`；
+    /// 通过 `MessageContext::send` / `try_send` 发送的超限消息会直接返回错误。
+    /// 设置上限后每条消息都要序列化一次 payload 来计算大小
+    pub fn with_max_payload_size(self, limit: usize) -> Self {
+        self.distribution_center.set_max_payload_size(limit);
+        self
+    }
+
+    /// 分发前校验已封印消息（[`Message::seal`]）的完整性，发现被修改时记录错误日志
+    ///
+    /// 仅用于调试，默认关闭；未封印的消息不受影响。需要在 `start_message_loop` 之前设置
+    pub fn with_seal_verification(mut self, enabled: bool) -> Self {
+        self.verify_seals = enabled;
+        self
+    }
+
+    /// 批量设置消息类型别名
+    pub fn with_aliases(self, aliases: HashMap<String, String>) -> Self {
+        self.aliases.write().unwrap().extend(aliases);
+        self
+    }
+
+    /// 添加消息类型别名：以 `old_type` 发送的消息会被改写为 `new_type`
+    ///
+    /// 用于重命名主题时保持旧客户端可用，别名不会链式解析
+    pub fn add_alias(&self, old_type: impl Into<String>, new_type: impl Into<String>) {
+        let (old_type, new_type) = (old_type.into(), new_type.into());
+        tracing::info!("[消息管理器] 注册消息类型别名: {} -> {}", old_type, new_type);
+        self.aliases.write().unwrap().insert(old_type, new_type);
+    }
+
+    /// 移除消息类型别名
+    pub fn remove_alias(&self, old_type: &str) -> Option<String> {
+        self.aliases.write().unwrap().remove(old_type)
+    }
+
+    /// 获取当前的别名表
+    pub fn aliases(&self) -> HashMap<String, String> {
+        self.aliases.read().unwrap().clone()
+    }
+
+    /// 设置消息拦截器（替换已有的拦截器）
+    ///
+    /// 拦截器在别名改写之后、路由之前对每条消息调用，可用于打标记、补充上下文、脱敏等；
+    /// 返回 `None` 时消息被丢弃。可在消息循环运行时设置
+    pub fn set_interceptor(&self, interceptor: MessageInterceptor) {
+        *self.interceptor.write().unwrap() = Some(interceptor);
+    }
+
+    /// 移除消息拦截器
+    pub fn clear_interceptor(&self) {
+        *self.interceptor.write().unwrap() = None;
+    }
+
     /// 获取分发中心的引用
     pub fn distribution_center(&self) -> &Arc<DistributionCenter> {
         &self.distribution_center
@@ -44,34 +170,164 @@ impl MessageManager {
     pub fn start_message_loop(&mut self) {
         let distribution_center: Arc<DistributionCenter> = Arc::clone(&self.distribution_center);
         let mut message_rx = self.message_rx.take().expect("消息接收器已被使用");
+        let aliases = Arc::clone(&self.aliases);
+        let interceptor = Arc::clone(&self.interceptor);
+        let verify_seals = self.verify_seals;
+
+        let (drain_tx, mut drain_rx) = tokio::sync::oneshot::channel::<()>();
 
         let handle = tokio::spawn(async move {
-            while let Some(message) = message_rx.recv().await {
-                // 检查是否为定向消息
-                if let Some(recipient) = &message.recipient {
-                    // 定向消息：发送给指定插件
-                    if let Err(e) = distribution_center.send_direct(recipient, message.clone()).await {
-                        tracing::warn!("[消息管理器] 发送定向消息失败 (目标: {}): {}", recipient, e);
+            let mut deliveries = DeliveryTracker::default();
+            loop {
+                tokio::select! {
+                    message = message_rx.recv() => match message {
+                        Some(message) => {
+                            if verify_seals {
+                                check_seal(&message);
+                            }
+                            route_message(&distribution_center, &aliases, &interceptor, &mut deliveries, message).await;
+                        }
+                        None => break,
+                    },
+                    _ = &mut drain_rx => {
+                        // 不再接收新消息，但处理完已在队列中的消息
+                        message_rx.close();
+                        while let Some(message) = message_rx.recv().await {
+                            if verify_seals {
+                                check_seal(&message);
+                            }
+                            route_message(&distribution_center, &aliases, &interceptor, &mut deliveries, message).await;
+                        }
+                        break;
                     }
-                } else {
-                    // 广播消息：分发给所有订阅者
-                    distribution_center.distribute(&message).await;
                 }
             }
         });
 
         self.message_task_handle = Some(handle);
+        self.drain_tx = Some(drain_tx);
     }
 
     /// 停止消息处理任务
+    ///
+    /// 先关闭消息入口并分发队列中剩余的消息，超过 `DRAIN_TIMEOUT` 仍未完成则强制终止
     pub async fn stop_message_loop(&mut self) {
-        if let Some(handle) = self.message_task_handle.take() {
-            handle.abort(); 
-            let _ = handle.await;
+        if let Some(drain_tx) = self.drain_tx.take() {
+            let _ = drain_tx.send(());
+        }
+        if let Some(mut handle) = self.message_task_handle.take() {
+            if tokio::time::timeout(DRAIN_TIMEOUT, &mut handle).await.is_err() {
+                tracing::warn!("[消息管理器] 排空消息队列超时，强制停止");
+                handle.abort();
+                let _ = handle.await;
+            }
+        }
+    }
+
+    /// 关闭消息系统：排空消息循环后关闭分发中心
+    ///
+    /// 应在所有插件 `stop` 之后调用，保证插件在停止时发出的消息仍能送达
+    pub async fn shutdown(&mut self) {
+        self.stop_message_loop().await;
+        self.distribution_center.shutdown().await;
+    }
+}
+
+/// 处理一条进入消息循环的消息：记录投递次数、应用别名和拦截器并路由
+async fn route_message(
+    distribution_center: &DistributionCenter,
+    aliases: &RwLock<HashMap<String, String>>,
+    interceptor: &RwLock<Option<MessageInterceptor>>,
+    deliveries: &mut DeliveryTracker,
+    mut message: Message,
+) {
+    // 超限的消息在记录投递和改写之前直接拒绝，避免被克隆给所有订阅者；
+    // 经 MessageContext 发送的消息已在发送时检查过，不再重复序列化
+    if !std::mem::take(&mut message.payload_checked) {
+        if let Some(size) = distribution_center.oversized_payload(&message) {
+            reject_message(distribution_center, message, size).await;
+            return;
+        }
+    }
+
+    message.delivery_attempt = deliveries.record(&message);
+    apply_alias(aliases, &mut message);
+
+    let message = match interceptor.read().unwrap().as_ref() {
+        Some(intercept) => {
+            let message_type = message.message_type.clone();
+            match intercept(message) {
+                Some(message) => message,
+                None => {
+                    tracing::debug!("[消息管理器] 消息被拦截器丢弃 (类型: {})", message_type.as_str());
+                    return;
+                }
+            }
+        }
+        None => message,
+    };
+
+    // 检查是否为定向消息
+    if let Some(recipient) = &message.recipient {
+        // 定向消息：发送给指定插件
+        if let Err(e) = distribution_center.send_direct(recipient, message.clone()).await {
+            tracing::warn!("[消息管理器] 发送定向消息失败 (目标: {}): {}", recipient, e);
         }
+    } else {
+        // 广播消息：分发给所有订阅者
+        distribution_center.distribute(&message).await;
     }
 }
 
+/// 校验封印，消息在封印后被修改时记录错误（消息照常分发）
+fn check_seal(message: &Message) {
+    if message.verify_seal() == Some(false) {
+        tracing::error!(
+            "[消息管理器] 消息封印校验失败，消息在封印后被修改 (类型: {}, ID: {:?}, 来源: {:?})",
+            message.message_type.as_str(),
+            message.message_id,
+            message.source
+        );
+    }
+}
+
+/// 拒绝消息：放入死信队列（不含原 payload），并广播拒绝事件通知发送方
+async fn reject_message(distribution_center: &DistributionCenter, mut message: Message, size: usize) {
+    let limit = distribution_center.max_payload_size();
+    let reason = oversized_reason(&message, size, limit);
+    let notice = Message::new(
+        MESSAGE_REJECTED_TYPE,
+        serde_json::json!({
+            "message_type": message.message_type.as_str(),
+            "message_id": message.message_id,
+            "source": message.source,
+            "size": size,
+            "limit": limit,
+            "reason": reason,
+        }),
+    );
+
+    message.payload = serde_json::Value::Null;
+    distribution_center.push_dead_letter(message, reason).await;
+    distribution_center.distribute(&notice).await;
+}
+
+/// 按别名表改写消息类型，并在元数据中保留原始类型
+fn apply_alias(aliases: &RwLock<HashMap<String, String>>, message: &mut Message) {
+    let Some(new_type) = aliases.read().unwrap().get(message.message_type.as_str()).cloned() else {
+        return;
+    };
+    let old_type = std::mem::replace(&mut message.message_type, MessageType::new(new_type));
+    tracing::info!(
+        "[消息管理器] 使用别名改写消息类型: {} -> {}",
+        old_type.as_str(),
+        message.message_type.as_str()
+    );
+    message
+        .metadata
+        .insert(ORIGINAL_TYPE_METADATA_KEY.to_string(), old_type.0);
+}
+
 impl Default for MessageManager {
     fn default() -> Self {
         Self::new()
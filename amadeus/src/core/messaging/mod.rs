@@ -2,9 +2,14 @@ pub mod distribution_center;
 pub mod message;
 pub mod message_context;
 pub mod message_manager;
+pub mod redaction;
 
-pub use distribution_center::DistributionCenter;
-pub use message::{Message, MessageHandleResult, MessagePriority, MessageSource, MessageType};
-pub use message_context::MessageContext;
+pub use distribution_center::{DeadLetter, DistributionCenter, OverflowPolicy, Partition};
+pub use message::{
+    set_message_type_canonicalization, Message, MessageHandleResult, MessagePriority, MessageSource, MessageType,
+    PARTITION_KEY_METADATA_KEY, SEAL_METADATA_KEY,
+};
+pub use message_context::{FilteredReceiver, MessageContext, SelfAddressPolicy, TrySendError};
 pub use message_manager::MessageManager;
+pub use redaction::{register_sensitive_fields, Redacted};
 
@@ -0,0 +1,144 @@
+use super::message::Message;
+use serde_json::Value;
+use std::fmt;
+use std::sync::{LazyLock, RwLock};
+
+/// 替换敏感字段后的占位符
+pub const REDACTED: &str = "***";
+
+/// 元数据键：发送方标记的敏感字段，逗号分隔的 JSON 路径（如 `"content,user.email"`），`*` 表示整个 payload
+pub const SENSITIVE_METADATA_KEY: &str = "sensitive";
+
+/// 表示整个 payload 的路径
+const WHOLE_PAYLOAD: &str = "*";
+
+/// (消息类型前缀, 敏感字段路径)
+type SensitiveRule = (String, Vec<String>);
+
+/// 按消息类型前缀配置的敏感字段
+///
+/// 默认：`system.user.*` 整体视为敏感，备忘录和通知消息隐藏提醒内容
+static SENSITIVE_FIELDS: LazyLock<RwLock<Vec<SensitiveRule>>> = LazyLock::new(|| {
+    RwLock::new(vec![
+        ("system.user.".to_string(), vec![WHOLE_PAYLOAD.to_string()]),
+        ("system.memo.".to_string(), vec!["content".to_string(), "overrides.content".to_string()]),
+        ("system.notify.".to_string(), vec!["content".to_string(), "message".to_string()]),
+    ])
+});
+
+/// 为某一类消息（按类型前缀匹配）追加敏感字段，路径语法同 [`SENSITIVE_METADATA_KEY`]
+pub fn register_sensitive_fields(type_prefix: impl Into<String>, paths: &[&str]) {
+    let type_prefix = type_prefix.into();
+    let mut rules = SENSITIVE_FIELDS.write().unwrap();
+    let paths = paths.iter().map(|p| p.to_string());
+    match rules.iter_mut().find(|(prefix, _)| *prefix == type_prefix) {
+        Some((_, existing)) => existing.extend(paths),
+        None => rules.push((type_prefix, paths.collect())),
+    }
+}
+
+/// 计算消息需要隐藏的字段路径
+fn sensitive_paths(message: &Message) -> Vec<String> {
+    let message_type = message.message_type.as_str();
+    let mut paths: Vec<String> = SENSITIVE_FIELDS
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|(prefix, _)| message_type.starts_with(prefix.as_str()))
+        .flat_map(|(_, paths)| paths.iter().cloned())
+        .collect();
+
+    if let Some(flagged) = message.metadata.get(SENSITIVE_METADATA_KEY) {
+        paths.extend(flagged.split(',').map(str::trim).filter(|p| !p.is_empty()).map(String::from));
+    }
+    paths
+}
+
+/// 将值中所有标量替换为占位符，保留对象和数组的结构便于排查
+fn redact_all(value: &mut Value) {
+    match value {
+        Value::Object(map) => map.values_mut().for_each(redact_all),
+        Value::Array(items) => items.iter_mut().for_each(redact_all),
+        Value::Null => {}
+        other => *other = Value::String(REDACTED.to_string()),
+    }
+}
+
+fn redact_path(value: &mut Value, path: &[&str]) {
+    let Some((first, rest)) = path.split_first() else {
+        redact_all(value);
+        return;
+    };
+    match value {
+        Value::Object(map) => {
+            if let Some(child) = map.get_mut(*first) {
+                redact_path(child, rest);
+            }
+        }
+        // 数组中的每个元素都应用同一路径
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_path(item, path)),
+        _ => {}
+    }
+}
+
+/// 返回隐藏了敏感字段的 payload 副本
+pub fn redact_payload(message: &Message) -> Value {
+    let mut payload = message.payload.clone();
+    for path in sensitive_paths(message) {
+        if path == WHOLE_PAYLOAD {
+            redact_all(&mut payload);
+            break;
+        }
+        let segments: Vec<&str> = path.split('.').collect();
+        redact_path(&mut payload, &segments);
+    }
+    payload
+}
+
+/// `{:?}` 输出的消息同样隐藏敏感字段，避免日志或断言失败信息泄露内容
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = f.debug_struct("Message");
+        out.field("message_type", &self.message_type.as_str())
+            .field("source", &self.source)
+            .field("message_id", &self.message_id)
+            .field("recipient", &self.recipient)
+            .field("priority", &self.priority)
+            .field("timestamp", &self.timestamp)
+            .field("user", &self.user_context.as_ref().map(|u| u.user.id.0.as_str()))
+            .field("metadata", &self.metadata)
+            .field("delivery_attempt", &self.delivery_attempt);
+        if self.is_json() {
+            out.field("payload", &redact_payload(self));
+        } else {
+            out.field("payload", &format_args!("<{}, {} bytes>", self.content_type(), self.payload_size()));
+        }
+        out.finish_non_exhaustive()
+    }
+}
+
+/// 消息的日志展示形式，敏感字段已替换为 [`REDACTED`]
+///
+/// 不输出用户上下文，只输出用户 ID
+pub struct Redacted<'a>(pub(super) &'a Message);
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.0;
+        write!(f, "[{}] source={:?}", message.message_type.as_str(), message.source)?;
+        if let Some(id) = &message.message_id {
+            write!(f, " id={}", id)?;
+        }
+        if let Some(recipient) = &message.recipient {
+            write!(f, " recipient={}", recipient)?;
+        }
+        if let Some(user) = &message.user_context {
+            write!(f, " user={}", user.user.id.0)?;
+        }
+        if !message.is_json() {
+            // 二进制/文本 payload 只输出类型和大小
+            return write!(f, " payload=<{}, {} bytes>", message.content_type(), message.payload_size());
+        }
+        write!(f, " payload={}", redact_payload(message))
+    }
+}
@@ -39,22 +39,29 @@ impl Permission {
     /// 检查权限是否匹配 (支持通配符 *)
     /// "memo:*" 匹配 "memo:create", "memo:read"
     pub fn matches(&self, required: &str) -> bool {
-        if self.0 == "*" {
+        wildcard_matches(&self.0, required, ':')
+    }
+}
+
+/// 按分隔符逐段比较模式与目标，段 `*` 匹配其后的所有内容
+///
+/// 权限使用 `:` 分隔（"memo:*"），消息类型使用 `.` 分隔（"system.user.*"）
+pub fn wildcard_matches(pattern: &str, value: &str, separator: char) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    let pattern_parts: Vec<&str> = pattern.split(separator).collect();
+    let value_parts: Vec<&str> = value.split(separator).collect();
+
+    for (i, part) in pattern_parts.iter().enumerate() {
+        if *part == "*" {
             return true;
         }
-        let self_parts: Vec<&str> = self.0.split(':').collect();
-        let req_parts: Vec<&str> = required.split(':').collect();
-
-        for (i, part) in self_parts.iter().enumerate() {
-            if *part == "*" {
-                return true;
-            }
-            if i >= req_parts.len() || *part != req_parts[i] {
-                return false;
-            }
+        if i >= value_parts.len() || *part != value_parts[i] {
+            return false;
         }
-        true
     }
+    true
 }
 
 /// 用户基本信息
@@ -80,6 +87,9 @@ pub struct UserContext {
     pub roles: Vec<String>,
     /// 用户拥有的具体权限集合
     pub permissions: HashSet<Permission>,
+    /// 显式拒绝的权限集合，优先于所有授权
+    #[serde(default)]
+    pub denied_permissions: HashSet<Permission>,
     /// 会话/Token过期时间 (Unix Timestamp, Optional)
     pub expires_at: Option<u64>,
 }
@@ -90,6 +100,7 @@ impl UserContext {
             user,
             roles: Vec::new(),
             permissions: HashSet::new(),
+            denied_permissions: HashSet::new(),
             expires_at: None,
         }
     }
@@ -99,13 +110,26 @@ impl UserContext {
         self
     }
 
+    /// 显式拒绝某个权限（支持通配符，如 `memo:*`）
+    pub fn with_denied_permission(mut self, perm: impl Into<String>) -> Self {
+        self.denied_permissions.insert(Permission::new(perm));
+        self
+    }
+
     pub fn with_role(mut self, role: impl Into<String>) -> Self {
         self.roles.push(role.into());
         self
     }
 
     /// 检查是否拥有指定权限
+    ///
+    /// 显式拒绝是绝对的：命中拒绝规则时，任何授权（包括 admin/root 角色）都无效
     pub fn has_permission(&self, required_perm: &str) -> bool {
+        // 0. 拒绝优先
+        if self.is_denied(required_perm) {
+            return false;
+        }
+
         // 1. 检查 Admin 角色 (硬编码超级管理员)
         if self.roles.contains(&"admin".to_string()) || self.roles.contains(&"root".to_string()) {
             return true;
@@ -114,5 +138,10 @@ impl UserContext {
         // 2. 检查具体权限
         self.permissions.iter().any(|p| p.matches(required_perm))
     }
+
+    /// 检查指定权限是否被显式拒绝
+    pub fn is_denied(&self, required_perm: &str) -> bool {
+        self.denied_permissions.iter().any(|p| p.matches(required_perm))
+    }
 }
 
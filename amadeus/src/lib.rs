@@ -4,6 +4,7 @@ pub mod app;
 pub mod core;
 pub mod plugin;
 pub mod plugins;
+pub mod util;
 
 // 重新导出常用类型
 pub use app::App;
@@ -14,3 +15,6 @@ pub use core::messaging::{
     MessageManager
 };
 pub use plugin::{Plugin, PluginMetadata, PluginRegistry};
+pub use plugins::AppConfig;
+#[cfg(feature = "wasm")]
+pub use plugins::wasm_plugin as wasm;
@@ -2,12 +2,37 @@ use crate::core::messaging::distribution_center::DistributionCenter;
 use crate::core::messaging::message::Message;
 use crate::core::messaging::message_context::MessageContext;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
 use std::pin::Pin;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// 宿主提供的插件 API 版本
+///
+/// 插件通过 `Plugin::required_api_version` 声明所需版本（semver 要求，如 "0.1" 或 ">=0.1, <0.3"），
+/// 不兼容的插件在注册时被拒绝
+pub const AMADEUS_API_VERSION: &str = "0.1.0";
+
+/// [`PluginRegistry::shutdown_async`] 默认给每个插件的停止时间
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// [`Plugin::stop_async`] 返回的 Future
+pub type StopFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>;
+
+/// 元数据属性键：插件声明的所需 API 版本（WASM 插件可在元数据中声明）
+pub const REQUIRED_API_VERSION_PROPERTY: &str = "required_api_version";
+
+/// 判断插件要求的 API 版本是否与宿主版本兼容（semver 规则）
+pub fn is_api_compatible(required: &str) -> anyhow::Result<bool> {
+    let host = semver::Version::parse(AMADEUS_API_VERSION)?;
+    let req = semver::VersionReq::parse(required)
+        .map_err(|e| anyhow::anyhow!("无效的 API 版本要求 '{}': {}", required, e))?;
+    Ok(req.matches(&host))
+}
+
 /// 插件元数据 - 可以被序列化到 JSON 配置文件
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginMetadata {
@@ -35,7 +60,12 @@ fn uuid_v4_string() -> String {
 
 impl PluginMetadata {
     /// 创建一个新的插件元数据
+    ///
+    /// `version` 应为 semver 版本号（如 "0.1.0"），否则记录警告，[`semver`](Self::semver) 将返回错误
     pub fn new(name: &str, description: &str, version: &str) -> Self {
+        if let Err(e) = semver::Version::parse(version) {
+            tracing::warn!("插件 {} 的版本 '{}' 不是有效的 semver: {}", name, version, e);
+        }
         Self {
             name: name.to_string(),
             description: description.to_string(),
@@ -59,6 +89,12 @@ impl PluginMetadata {
         self
     }
 
+    /// 将 `version` 解析为 semver 版本，用于版本比较和兼容性检查
+    pub fn semver(&self) -> anyhow::Result<semver::Version> {
+        semver::Version::parse(&self.version)
+            .map_err(|e| anyhow::anyhow!("插件 {} 的版本 '{}' 无效: {}", self.name, self.version, e))
+    }
+
     /// 添加自定义属性
     pub fn with_property(mut self, key: &str, value: &str) -> Self {
         self.properties.insert(key.to_string(), value.to_string());
@@ -87,6 +123,17 @@ pub trait Plugin: Send + Sync {
         &self.metadata().uid
     }
 
+    /// 获取插件所需的宿主 API 版本
+    ///
+    /// 默认读取元数据属性 `required_api_version`，未声明时与当前版本一致
+    fn required_api_version(&self) -> &str {
+        self.metadata()
+            .properties
+            .get(REQUIRED_API_VERSION_PROPERTY)
+            .map(String::as_str)
+            .unwrap_or(AMADEUS_API_VERSION)
+    }
+
     /// 获取插件类型
     fn plugin_type(&self) -> PluginType {
         PluginType::Normal
@@ -104,6 +151,7 @@ pub trait Plugin: Send + Sync {
     /// 设置消息订阅
     /// 
     /// 插件可以在这里订阅感兴趣的消息类型
+    /// 注册表保证调用顺序为 `init` -> `setup_messaging` -> `start`，此时 `init` 已经完成
     /// 返回一个 Future，因为订阅可能涉及异步操作
     fn setup_messaging(
         &mut self,
@@ -120,12 +168,27 @@ pub trait Plugin: Send + Sync {
         Ok(())
     }
 
+    /// 运行插件 - 在所有插件启动后调用一次
+    ///
+    /// 实现不应阻塞：长期运行的工作应在 `start` 或消息订阅中派生任务完成
+    fn run(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// 停止插件 - 在插件停止时调用，用于清理资源
     fn stop(&mut self) -> anyhow::Result<()> {
         tracing::info!("[{}] 插件停止", self.metadata().name);
         Ok(())
     }
 
+    /// 异步停止插件，由 [`PluginRegistry::stop_all_async`] 在超时限制下调用
+    ///
+    /// 默认在 Future 中调用 `stop`；注册表在阻塞线程池上驱动该 Future，
+    /// 因此同步阻塞的 `stop` 同样受超时限制。需要等待线程或任务退出的插件可覆盖此方法
+    fn stop_async(&mut self) -> StopFuture<'_> {
+        Box::pin(async move { self.stop() })
+    }
+
     /// 获取插件是否启用
     fn is_enabled(&self) -> bool {
         self.metadata().enabled_by_default
@@ -133,8 +196,15 @@ pub trait Plugin: Send + Sync {
 }
 
 /// 插件注册表 - 管理所有插件
+///
+/// 生命周期顺序固定为 `init` -> `setup_messaging` -> `start` -> `run` -> `stop`：
+/// `setup_messaging` 和 `start_all` 会在需要时先执行 `init_all`，
+/// 已初始化的插件不会被重复初始化，直到 `stop_all` 之后
 pub struct PluginRegistry {
     plugins: Vec<Box<dyn Plugin>>,
+    initialized: bool,
+    /// 运行时由运维设置的启用/禁用状态（按插件名），优先于 `enabled_by_default`
+    state: HashMap<String, bool>,
 }
 
 impl PluginRegistry {
@@ -142,6 +212,8 @@ impl PluginRegistry {
     pub fn new() -> Self {
         Self {
             plugins: Vec::new(),
+            initialized: false,
+            state: HashMap::new(),
         }
     }
 
@@ -173,30 +245,124 @@ impl PluginRegistry {
         Ok(())
     }
 
+    /// 将每个插件当前的启用状态写入文件（`{ "插件名": true/false }`）
+    ///
+    /// 包括已注册的插件和运行时被禁用（因而已移除）的插件
+    pub fn save_state(&self, path: &str) -> anyhow::Result<()> {
+        let mut state: std::collections::BTreeMap<&str, bool> = self
+            .plugins
+            .iter()
+            .map(|p| (p.metadata().name.as_str(), p.is_enabled()))
+            .collect();
+        state.extend(self.state.iter().map(|(name, enabled)| (name.as_str(), *enabled)));
+        std::fs::write(path, serde_json::to_string_pretty(&state)?)?;
+        Ok(())
+    }
+
+    /// 读取 [`save_state`](Self::save_state) 保存的启用状态，优先于插件的 `enabled_by_default`
+    ///
+    /// 应在注册插件之前调用；已注册但被记录为禁用的插件会被移除。文件不存在时不做任何事
+    pub fn apply_state(&mut self, path: &str) -> anyhow::Result<&mut Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(self),
+            Err(e) => return Err(e.into()),
+        };
+        let state: HashMap<String, bool> = serde_json::from_str(&content)?;
+        for (name, enabled) in state {
+            self.set_enabled(&name, enabled);
+        }
+        Ok(self)
+    }
+
+    /// 运行时启用或禁用插件
+    ///
+    /// 禁用会移除已注册的插件（已初始化时先调用其 `stop`）；启用在下次通过
+    /// [`register_enabled`](Self::register_enabled) 注册时生效
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        self.state.insert(name.to_string(), enabled);
+        if !enabled {
+            if let Some(mut plugin) = self.unregister(name) {
+                if self.initialized {
+                    if let Err(e) = plugin.stop() {
+                        tracing::error!("停止插件 {} 失败: {}", name, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 插件是否启用：运行时设置的状态优先，其次是已注册插件自身的设置，未知插件视为禁用
+    pub fn is_enabled(&self, name: &str) -> bool {
+        if let Some(enabled) = self.state.get(name) {
+            return *enabled;
+        }
+        self.plugins
+            .iter()
+            .find(|p| p.metadata().name == name)
+            .is_some_and(|p| p.is_enabled())
+    }
+
     /// 注册一个插件并排序
+    ///
+    /// API 版本不兼容的插件会被拒绝并记录错误
     pub fn register<P: Plugin + 'static>(&mut self, plugin: P) {
+        if let Err(e) = self.try_register(plugin) {
+            tracing::error!("{}", e);
+        }
+    }
+
+    /// 注册一个插件并排序，API 版本不兼容时返回错误
+    pub fn try_register<P: Plugin + 'static>(&mut self, plugin: P) -> anyhow::Result<()> {
+        Self::check_api_version(&plugin)?;
         tracing::info!("注册插件: {} [{:?}]", plugin.metadata().name, plugin.plugin_type());
         self.plugins.push(Box::new(plugin));
         self.sort_plugins();
+        Ok(())
     }
 
     /// 批量注册插件列表并排序
     pub fn register_all(&mut self, plugins: Vec<Box<dyn Plugin>>) {
         for plugin in plugins {
+            if let Err(e) = Self::check_api_version(plugin.as_ref()) {
+                tracing::error!("{}", e);
+                continue;
+            }
             tracing::info!("注册插件: {} [{:?}]", plugin.metadata().name, plugin.plugin_type());
             self.plugins.push(plugin);
         }
         self.sort_plugins();
     }
 
+    /// 检查插件所需的 API 版本是否与宿主兼容
+    fn check_api_version(plugin: &dyn Plugin) -> anyhow::Result<()> {
+        let required = plugin.required_api_version();
+        if is_api_compatible(required)? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "拒绝插件 {}: 需要 API 版本 {}，宿主版本为 {}",
+                plugin.metadata().name,
+                required,
+                AMADEUS_API_VERSION
+            ))
+        }
+    }
+
     /// 根据配置有选择地注册插件并排序
+    ///
+    /// 通过 [`apply_state`](Self::apply_state) 或 [`set_enabled`](Self::set_enabled) 设置的状态优先于 `enabled_by_default`
     pub fn register_enabled(&mut self, plugins: Vec<Box<dyn Plugin>>) {
         for plugin in plugins {
-            let enabled = plugin.metadata().enabled_by_default;
             let name = plugin.metadata().name.clone();
+            let enabled = self.state.get(&name).copied().unwrap_or(plugin.metadata().enabled_by_default);
             let p_type = plugin.plugin_type();
             
             if enabled {
+                if let Err(e) = Self::check_api_version(plugin.as_ref()) {
+                    tracing::error!("{}", e);
+                    continue;
+                }
                 tracing::info!("✓ 注册插件: {} [{:?}] [启用]", name, p_type);
                 self.plugins.push(plugin);
             } else {
@@ -211,6 +377,10 @@ impl PluginRegistry {
         for plugin in plugins {
             let name = &plugin.metadata().name;
             if names.contains(&name.as_str()) {
+                if let Err(e) = Self::check_api_version(plugin.as_ref()) {
+                    tracing::error!("{}", e);
+                    continue;
+                }
                 tracing::info!("✓ 注册插件: {}", name);
                 self.plugins.push(plugin);
             }
@@ -226,6 +396,10 @@ impl PluginRegistry {
         for plugin in plugins {
             let meta = plugin.metadata();
             if filter(meta) {
+                if let Err(e) = Self::check_api_version(plugin.as_ref()) {
+                    tracing::error!("{}", e);
+                    continue;
+                }
                 tracing::info!("✓ 注册插件: {}", meta.name);
                 self.plugins.push(plugin);
             }
@@ -233,6 +407,13 @@ impl PluginRegistry {
         self.sort_plugins();
     }
 
+    /// 按名称移除已注册的插件，返回被移除的插件
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn Plugin>> {
+        let index = self.plugins.iter().position(|p| p.metadata().name == name)?;
+        tracing::info!("移除插件: {}", name);
+        Some(self.plugins.remove(index))
+    }
+
     /// 对插件进行排序：特权插件优先
     fn sort_plugins(&mut self) {
         self.plugins.sort_by(|a, b| a.plugin_type().cmp(&b.plugin_type()));
@@ -249,17 +430,29 @@ impl PluginRegistry {
     }
 
     /// 初始化所有插件
+    ///
+    /// 已经初始化过（且尚未 `stop_all`）时直接返回
     pub fn init_all(&mut self) -> anyhow::Result<&mut Self> {
+        if self.initialized {
+            return Ok(self);
+        }
         tracing::info!("=== 初始化所有插件 ===");
         // 此时插件已经排序，Privileged 在前
         for plugin in self.plugins.iter_mut() {
             plugin.init()?;
         }
+        self.initialized = true;
         Ok(self)
     }
 
-    /// 启动所有插件
+    /// 插件是否已经初始化
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// 启动所有插件（尚未初始化时先执行 `init_all`）
     pub fn start_all(&mut self) -> anyhow::Result<&mut Self> {
+        self.init_all()?;
         tracing::info!("=== 启动所有插件 ===");
         for plugin in self.plugins.iter_mut() {
             plugin.start()?;
@@ -267,29 +460,112 @@ impl PluginRegistry {
         Ok(self)
     }
 
+    /// 运行所有插件
+    ///
+    /// 依次调用每个插件的 `run`，不会阻塞等待停止信号；
+    /// 需要一直运行直到收到信号时使用 `App::run_async` / `App::run_until`
+    pub fn run_all(&mut self) -> anyhow::Result<&mut Self> {
+        tracing::info!("=== 运行所有插件 ===");
+        for plugin in self.plugins.iter_mut() {
+            plugin.run()?;
+        }
+        Ok(self)
+    }
+
     /// 停止所有插件（按相反顺序）
+    ///
+    /// 某个插件停止失败时记录错误并继续停止其余插件，最后返回汇总的错误
     pub fn stop_all(&mut self) -> anyhow::Result<&mut Self> {
         tracing::info!("=== 停止所有插件 ===");
+        let mut failures = Vec::new();
         // 停止时，Normal 先停，Privileged 后停
         for plugin in self.plugins.iter_mut().rev() {
-            plugin.stop()?;
+            if let Err(e) = plugin.stop() {
+                tracing::error!("插件 {} 停止失败: {}", plugin.metadata().name, e);
+                failures.push(format!("{}: {}", plugin.metadata().name, e));
+            }
         }
+        // 停止后再次启动需要重新初始化
+        self.initialized = false;
+        stop_failures(failures)?;
+        Ok(self)
+    }
+
+    /// 异步停止所有插件（按相反顺序），每个插件最多等待 `timeout`
+    ///
+    /// 每个插件的 `stop_async` 在阻塞线程池上执行，同步阻塞的 `stop` 也不会拖住整个流程。
+    /// 超时的插件记录错误后跳过；仍卡在同步 `stop` 中的插件留在后台线程上，从注册表中移除。
+    /// 停止失败的插件同样不影响其余插件，所有插件处理完后返回汇总的错误
+    pub async fn stop_all_async(&mut self, timeout: Duration) -> anyhow::Result<&mut Self> {
+        tracing::info!("=== 停止所有插件 ===");
+        let runtime = tokio::runtime::Handle::current();
+        let mut stopped = Vec::with_capacity(self.plugins.len());
+        let mut failures = Vec::new();
+        for mut plugin in std::mem::take(&mut self.plugins).into_iter().rev() {
+            let name = plugin.metadata().name.clone();
+            let runtime = runtime.clone();
+            let task = tokio::task::spawn_blocking(move || {
+                let result = runtime.block_on(tokio::time::timeout(timeout, plugin.stop_async()));
+                (plugin, result)
+            });
+            match tokio::time::timeout(timeout, task).await {
+                Ok(Ok((plugin, result))) => {
+                    stopped.push(plugin);
+                    match result {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => {
+                            tracing::error!("插件 {} 停止失败: {}", name, e);
+                            failures.push(format!("{}: {}", name, e));
+                        }
+                        Err(_) => tracing::error!("插件 {} 未能在 {:?} 内停止，已跳过", name, timeout),
+                    }
+                }
+                Ok(Err(e)) => {
+                    tracing::error!("插件 {} 停止时崩溃: {}", name, e);
+                    failures.push(format!("{}: {}", name, e));
+                }
+                Err(_) => tracing::error!("插件 {} 未能在 {:?} 内停止，已跳过", name, timeout),
+            }
+        }
+        stopped.reverse();
+        self.plugins = stopped;
+        self.initialized = false;
+        stop_failures(failures)?;
         Ok(self)
     }
 
     /// 执行插件启动流程 (init -> start)
+    ///
+    /// 在 `setup_messaging` 之后调用时插件已经初始化，只会执行 start
     pub fn startup(&mut self) -> anyhow::Result<()> {
         self.init_all()?
             .start_all()?;
         Ok(())
     }
 
+    /// 执行完整的插件生命周期 (init -> start -> run -> stop)
+    ///
+    /// 与 `run_all` 一样不会阻塞，适用于一次性任务和示例
+    pub fn run_lifecycle(&mut self) -> anyhow::Result<()> {
+        self.init_all()?
+            .start_all()?
+            .run_all()?
+            .stop_all()?;
+        Ok(())
+    }
+
     /// 执行插件停止流程 (stop)
     pub fn shutdown(&mut self) -> anyhow::Result<()> {
         self.stop_all()?;
         Ok(())
     }
 
+    /// 执行异步插件停止流程，每个插件的停止时间不超过 `timeout`
+    pub async fn shutdown_async(&mut self, timeout: Duration) -> anyhow::Result<()> {
+        self.stop_all_async(timeout).await?;
+        Ok(())
+    }
+
     /// 导出所有插件的元数据为 JSON
     pub fn export_metadata(&self) -> anyhow::Result<String> {
         let metadata: Vec<&PluginMetadata> = self
@@ -323,10 +599,13 @@ impl PluginRegistry {
     }
 
     /// 设置插件的消息订阅
+    ///
+    /// 尚未初始化时先执行 `init_all`，保证插件在 `setup_messaging` 中能看到 `init` 的结果
     pub async fn setup_messaging(
         &mut self,
         message_manager: &crate::core::messaging::message_manager::MessageManager,
     ) -> anyhow::Result<()> {
+        self.init_all()?;
         tracing::info!("=== 设置插件消息订阅 ===");
         
         let dc = message_manager.distribution_center();
@@ -360,6 +639,14 @@ impl PluginRegistry {
     }
 }
 
+/// 汇总停止失败的插件（`插件名: 错误`），没有失败时返回 `Ok`
+fn stop_failures(failures: Vec<String>) -> anyhow::Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!("{} 个插件停止失败: {}", failures.len(), failures.join("; "))
+}
+
 impl Default for PluginRegistry {
     fn default() -> Self {
         Self::new()
@@ -1,12 +1,32 @@
+use crate::core::messaging::distribution_center::DistributionCenter;
+use crate::core::messaging::message::Message;
+use crate::core::messaging::message_context::MessageContext;
 use crate::plugin::{Plugin, PluginMetadata};
 use anyhow::Result;
-use tracing::info;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// 正在运行的扫描任务（scan_id -> 任务句柄）
+type ScanTasks = Arc<Mutex<HashMap<String, JoinHandle<()>>>>;
 
 /// Code4rena 插件结构体
+///
+/// 消息接口：
+/// - `security.scan.request` `{target, scan_id?}` -> `security.scan.started` `{scan_id, target}`，
+///   完成后发送 `security.scan.report`
+/// - `security.scan.cancel` `{scan_id}` -> 取消扫描，发送 status 为 `cancelled` 的 `security.scan.report`
 pub struct Code4renaPlugin {
     metadata: PluginMetadata,
     // 可以添加插件特定的状态字段
     is_running: bool,
+    /// 模拟扫描耗时
+    scan_delay: Duration,
+    scans: ScanTasks,
 }
 
 impl Code4renaPlugin {
@@ -25,8 +45,16 @@ impl Code4renaPlugin {
         Self {
             metadata,
             is_running: false,
+            scan_delay: Duration::from_millis(100),
+            scans: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// 设置单次扫描的（模拟）耗时
+    pub fn with_scan_delay(mut self, delay: Duration) -> Self {
+        self.scan_delay = delay;
+        self
+    }
 }
 
 impl Default for Code4renaPlugin {
@@ -51,31 +79,162 @@ impl Plugin for Code4renaPlugin {
         Ok(())
     }
 
+    fn setup_messaging(
+        &mut self,
+        distribution_center: &DistributionCenter,
+        message_tx: mpsc::Sender<Message>,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<Option<Arc<MessageContext>>>> + Send>> {
+        let plugin_name = self.metadata.name.clone();
+        let plugin_uid = self.metadata.uid.clone();
+        let dc = Arc::new(distribution_center.clone());
+        let scan_delay = self.scan_delay;
+        let scans = self.scans.clone();
+
+        Box::pin(async move {
+            let ctx = Arc::new(MessageContext::new(dc, plugin_name, plugin_uid, message_tx));
+
+            let mut rx_request = ctx.subscribe("security.scan.request").await?;
+            let mut rx_cancel = ctx.subscribe("security.scan.cancel").await?;
+
+            let ctx_clone = ctx.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        Ok(msg) = rx_request.recv() => {
+                            start_scan(&msg, &ctx_clone, &scans, scan_delay).await;
+                        }
+                        Ok(msg) = rx_cancel.recv() => {
+                            cancel_scan(&msg, &ctx_clone, &scans).await;
+                        }
+                        else => break,
+                    }
+                }
+            });
+
+            Ok(Some(ctx))
+        })
+    }
+
     fn start(&mut self) -> Result<()> {
         info!("[Code4rena] 正在启动插件...");
-        info!("[Code4rena] 连接到 Code4rena API...");
         self.is_running = true;
-        
-        // 模拟执行任务
-        tokio::spawn(async {
-            info!("[Code4rena] 执行主要逻辑...");
-            info!("[Code4rena] 扫描合约代码...");
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            info!("[Code4rena] 分析潜在漏洞...");
-            info!("[Code4rena] 生成报告...");
-            info!("[Code4rena] 运行完成!");
-        });
-
-        info!("[Code4rena] 插件已启动!");
+        info!("[Code4rena] 插件已启动，等待 security.scan.request 请求");
         Ok(())
     }
 
     fn stop(&mut self) -> Result<()> {
         info!("[Code4rena] 正在停止插件...");
-        info!("[Code4rena] 保存状态...");
-        info!("[Code4rena] 清理资源...");
+        // 取消所有未完成的扫描
+        for (scan_id, handle) in self.scans.lock().unwrap().drain() {
+            info!("[Code4rena] 取消扫描 {}", scan_id);
+            handle.abort();
+        }
         self.is_running = false;
         info!("[Code4rena] 插件已停止!");
         Ok(())
     }
 }
+
+/// 处理扫描请求：登记任务并在后台执行扫描
+async fn start_scan(msg: &Message, ctx: &Arc<MessageContext>, scans: &ScanTasks, scan_delay: Duration) {
+    let Some(target) = msg.payload.get("target").and_then(|v| v.as_str()).map(str::to_string) else {
+        warn!("[Code4rena] 扫描请求缺少 target");
+        return;
+    };
+    let scan_id = msg
+        .payload
+        .get("scan_id")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| msg.message_id.clone())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    info!("[Code4rena] 开始扫描 {} (scan_id: {})", target, scan_id);
+
+    let task_ctx = ctx.clone();
+    let task_scans = scans.clone();
+    let task_scan_id = scan_id.clone();
+    let task_target = target.clone();
+    // 任务在句柄登记之后才开始执行，否则很快完成的扫描会先移除登记、再被插入一个失效的句柄
+    let (registered_tx, registered_rx) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        if registered_rx.await.is_err() {
+            return;
+        }
+        tokio::time::sleep(scan_delay).await;
+        let findings = analyze(&task_target);
+        {
+            // 只移除自己的登记，同一 scan_id 可能已被新的请求替换
+            let mut scans = task_scans.lock().unwrap();
+            if scans.get(&task_scan_id).is_some_and(|h| h.id() == tokio::task::id()) {
+                scans.remove(&task_scan_id);
+            }
+        }
+
+        let report = Message::new(
+            "security.scan.report",
+            serde_json::json!({
+                "scan_id": task_scan_id,
+                "target": task_target,
+                "status": "completed",
+                "summary": { "total": findings.len() },
+                "findings": findings,
+            }),
+        );
+        let _ = task_ctx.send(report).await;
+        info!("[Code4rena] 扫描完成 (scan_id: {})", task_scan_id);
+    });
+
+    // 同一 scan_id 的旧任务被新请求替换
+    let previous = scans.lock().unwrap().insert(scan_id.clone(), handle);
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+    let _ = registered_tx.send(());
+
+    let started = Message::new(
+        "security.scan.started",
+        serde_json::json!({ "scan_id": scan_id, "target": target }),
+    );
+    let _ = ctx.send(started).await;
+}
+
+/// 处理取消请求
+async fn cancel_scan(msg: &Message, ctx: &Arc<MessageContext>, scans: &ScanTasks) {
+    let Some(scan_id) = msg.payload.get("scan_id").and_then(|v| v.as_str()) else {
+        warn!("[Code4rena] 取消请求缺少 scan_id");
+        return;
+    };
+
+    let handle = scans.lock().unwrap().remove(scan_id);
+    match handle {
+        Some(handle) => {
+            handle.abort();
+            info!("[Code4rena] 已取消扫描 {}", scan_id);
+            let report = Message::new(
+                "security.scan.report",
+                serde_json::json!({
+                    "scan_id": scan_id,
+                    "status": "cancelled",
+                    "summary": { "total": 0 },
+                    "findings": [],
+                }),
+            );
+            let _ = ctx.send(report).await;
+        }
+        None => warn!("[Code4rena] 未找到正在运行的扫描 {}", scan_id),
+    }
+}
+
+/// 扫描目标并生成发现列表
+///
+/// 目前尚未接入真实的分析器，只返回一条说明性的发现，格式与真实结果一致
+fn analyze(target: &str) -> Vec<serde_json::Value> {
+    vec![serde_json::json!({
+        "id": "C4-INFO-001",
+        "severity": "info",
+        "title": "No analyzers configured",
+        "description": format!("Scanned {} without any vulnerability analyzers enabled", target),
+        "location": target,
+    })]
+}
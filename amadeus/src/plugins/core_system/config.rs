@@ -1,17 +1,290 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use super::scheduler::Scheduler;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// 缺失的字段逐个回退到默认值，见 [`CoreSystemConfig::from_json_with_defaults`]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
 pub struct CoreSystemConfig {
     pub memos: MemoConfig,
+    pub scheduler: SchedulerConfig,
+    pub notifications: NotificationConfig,
+}
+
+impl CoreSystemConfig {
+    /// 解析配置文件内容，缺失的字段逐个使用默认值，而不是整份配置回退为默认
+    ///
+    /// 映射类型的字段（`memos.priorities`、`memos.tag_schedules`）整体取值：
+    /// 文件中给出时按原样使用，缺失时才使用默认映射
+    ///
+    /// 返回配置以及使用了默认值的字段路径（如 `memos.expiration_days`）
+    pub fn from_json_with_defaults(content: &str) -> anyhow::Result<(Self, Vec<String>)> {
+        let mut value: serde_json::Value = serde_json::from_str(content)?;
+        let mut defaulted = Vec::new();
+        merge_defaults(&mut value, serde_json::to_value(Self::default())?, "", &mut defaulted);
+        Ok((serde_json::from_value(value)?, defaulted))
+    }
+
+    /// 检查所有 cron 表达式能否解析，以及数值字段是否在合理范围内
+    ///
+    /// 返回发现的全部问题，而不是遇到第一个就停止
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let errors = self.clone().repair();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 与 [`validate`](Self::validate) 相同的检查，但就地修正每个无效字段：
+    /// 无法解析的标签提醒被移除，越界的数值和未知的默认渠道恢复为默认值，其余字段保持不变
+    ///
+    /// 返回被修正的问题
+    pub fn repair(&mut self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let defaults = Self::default();
+
+        let mut tags: Vec<_> = self.memos.tag_schedules.iter().collect();
+        tags.sort();
+        let mut invalid_tags = Vec::new();
+        for (tag, cron) in tags {
+            if let Err(e) = Scheduler::validate_cron(cron) {
+                errors.push(ConfigError::new(
+                    format!("memos.tag_schedules.{}", tag),
+                    format!("invalid cron expression '{}': {}", cron, e),
+                ));
+                invalid_tags.push(tag.clone());
+            }
+        }
+        for tag in invalid_tags {
+            self.memos.tag_schedules.remove(&tag);
+        }
+
+        if self.memos.expiration_days == 0 {
+            errors.push(ConfigError::new(
+                "memos.expiration_days",
+                "must be at least 1 (0 would recycle expired memos immediately)",
+            ));
+            self.memos.expiration_days = defaults.memos.expiration_days;
+        }
+        if self.memos.query_cache_ttl_secs == Some(0) {
+            errors.push(ConfigError::new("memos.query_cache_ttl_secs", "must be at least 1 when set"));
+            self.memos.query_cache_ttl_secs = defaults.memos.query_cache_ttl_secs;
+        }
+        if self.memos.query_cache_ttl_secs.is_some() && self.memos.query_cache_capacity == 0 {
+            errors.push(ConfigError::new("memos.query_cache_capacity", "must be at least 1 when the query cache is enabled"));
+            self.memos.query_cache_capacity = defaults.memos.query_cache_capacity;
+        }
+        if self.scheduler.max_concurrent_jobs == 0 {
+            errors.push(ConfigError::new("scheduler.max_concurrent_jobs", "must be at least 1"));
+            self.scheduler.max_concurrent_jobs = defaults.scheduler.max_concurrent_jobs;
+        }
+        if self.scheduler.reload_page_size == 0 {
+            errors.push(ConfigError::new("scheduler.reload_page_size", "must be at least 1"));
+            self.scheduler.reload_page_size = defaults.scheduler.reload_page_size;
+        }
+        if let Some(channel) = &self.notifications.default_channel {
+            if !self.notifications.is_known(channel) {
+                errors.push(ConfigError::new(
+                    "notifications.default_channel",
+                    format!("unknown adapter '{}' (known: {})", channel, self.notifications.adapters.join(", ")),
+                ));
+                self.notifications.default_channel = None;
+            }
+        }
+
+        errors
+    }
+}
+
+/// [`CoreSystemConfig::validate`] 发现的单个问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 出问题的字段路径（如 `memos.tag_schedules.stage_goal`）
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// 值为映射（而不是结构体）的字段：作为整体取值，文件中给出时不与默认条目合并，
+/// 否则运营者无法删除默认的条目（如 `stage_goal` 标签提醒）
+const MAP_FIELDS: &[&str] = &["memos.priorities", "memos.tag_schedules"];
+
+/// 将 `defaults` 中 `value` 缺少的结构体字段补齐，记录补齐的路径
+fn merge_defaults(value: &mut serde_json::Value, defaults: serde_json::Value, path: &str, defaulted: &mut Vec<String>) {
+    if MAP_FIELDS.contains(&path) {
+        return;
+    }
+    let (serde_json::Value::Object(map), serde_json::Value::Object(default_map)) = (value, defaults) else {
+        return;
+    };
+    for (key, default) in default_map {
+        let field_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+        match map.get_mut(&key) {
+            Some(existing) => merge_defaults(existing, default, &field_path, defaulted),
+            None => {
+                map.insert(key, default);
+                defaulted.push(field_path);
+            }
+        }
+    }
+}
+
+/// 提醒通知的路由配置
+///
+/// 触发的 `system.memo.remind` 会转发到 `system.notify.<adapter>`，由对应的适配器插件投递
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// 已知的通知适配器，备忘录的 `notify_channel` 必须是其中之一
+    pub adapters: Vec<String>,
+    /// 备忘录未指定渠道、所有者也没有设置 `notification_platform` 偏好时使用的适配器
+    #[serde(default)]
+    pub default_channel: Option<String>,
+    /// 摘要模式（用户偏好 `reminder_mode` 为 `"digest"`）下合并提醒的时间窗口（毫秒）
+    #[serde(default = "default_digest_window_ms")]
+    pub digest_window_ms: u64,
+}
+
+fn default_digest_window_ms() -> u64 {
+    5_000
+}
+
+impl NotificationConfig {
+    /// 判断适配器是否已知
+    pub fn is_known(&self, adapter: &str) -> bool {
+        self.adapters.iter().any(|a| a == adapter)
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            adapters: ["cli", "discord", "qq", "email", "sms"].iter().map(|a| a.to_string()).collect(),
+            default_channel: None,
+            digest_window_ms: default_digest_window_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// 同时执行的任务体数量上限，同一时刻触发的其余任务排队等待
+    pub max_concurrent_jobs: usize,
+    /// 启动时每批重新载入的活跃提醒数量
+    #[serde(default = "default_reload_page_size")]
+    pub reload_page_size: usize,
+    /// 停止时等待正在执行的任务完成的最长时间（毫秒）
+    #[serde(default = "default_shutdown_timeout_ms")]
+    pub shutdown_timeout_ms: u64,
+}
+
+fn default_reload_page_size() -> usize {
+    500
+}
+
+fn default_shutdown_timeout_ms() -> u64 {
+    3000
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_jobs: 16,
+            reload_page_size: default_reload_page_size(),
+            shutdown_timeout_ms: default_shutdown_timeout_ms(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct MemoConfig {
     /// 不同优先级的配置
     pub priorities: HashMap<i32, PriorityConfig>,
     /// 默认过期策略 (单位: 天) - 备忘录过期多久后自动回收/删除
     pub expiration_days: u64,
+    /// 删除父备忘录时如何处理其子项
+    #[serde(default)]
+    pub on_parent_delete: ParentDeletePolicy,
+    /// 启动时对已错过的一次性提醒（remind_at 已过）的处理方式
+    #[serde(default)]
+    pub missed_reminder_policy: MissedReminderPolicy,
+    /// 写入时规范化标签（去空白、小写、去重），关闭后按原样存储
+    #[serde(default = "default_normalize_tags")]
+    pub normalize_tags: bool,
+    /// 标签提醒：带有该标签的备忘录额外注册一个 cron 提醒（标签 -> cron 表达式）
+    #[serde(default = "default_tag_schedules")]
+    pub tag_schedules: HashMap<String, String>,
+    /// 列表查询结果的缓存时间（秒），未设置时不缓存
+    #[serde(default)]
+    pub query_cache_ttl_secs: Option<u64>,
+    /// 最多缓存的不同查询数量
+    #[serde(default = "default_query_cache_capacity")]
+    pub query_cache_capacity: usize,
+}
+
+fn default_normalize_tags() -> bool {
+    true
+}
+
+fn default_tag_schedules() -> HashMap<String, String> {
+    HashMap::from([("stage_goal".to_string(), "0 0 10 * * *".to_string())]) // 10:00 AM daily
+}
+
+fn default_query_cache_capacity() -> usize {
+    256
+}
+
+/// 启动重载时已错过的一次性提醒的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedReminderPolicy {
+    /// 立即补发（带 `late: true` 标记）
+    Fire,
+    /// 丢弃错过的提醒
+    #[default]
+    Skip,
+    /// 仅当错过的时间不超过给定秒数时补发
+    FireIfWithin(u64),
+}
+
+impl MissedReminderPolicy {
+    /// 判断错过 `overdue_secs` 秒的提醒是否需要补发
+    pub fn should_fire(&self, overdue_secs: u64) -> bool {
+        match self {
+            Self::Fire => true,
+            Self::Skip => false,
+            Self::FireIfWithin(grace) => overdue_secs <= *grace,
+        }
+    }
+}
+
+/// 删除父备忘录时子项的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParentDeletePolicy {
+    /// 子项保留，解除与父项的关联
+    #[default]
+    Detach,
+    /// 子项（及其后代）一并删除
+    Cascade,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,7 +294,7 @@ pub struct PriorityConfig {
     pub default_reminder_message: String,
 }
 
-impl Default for CoreSystemConfig {
+impl Default for MemoConfig {
     fn default() -> Self {
         let mut priorities = HashMap::new();
         priorities.insert(0, PriorityConfig {
@@ -46,10 +319,14 @@ impl Default for CoreSystemConfig {
         });
 
         Self {
-            memos: MemoConfig {
-                priorities,
-                expiration_days: 30, // Default retain for 30 days after expiration
-            },
+            priorities,
+            expiration_days: 30, // Default retain for 30 days after expiration
+            on_parent_delete: ParentDeletePolicy::default(),
+            missed_reminder_policy: MissedReminderPolicy::default(),
+            normalize_tags: default_normalize_tags(),
+            tag_schedules: default_tag_schedules(),
+            query_cache_ttl_secs: None,
+            query_cache_capacity: default_query_cache_capacity(),
         }
     }
 }
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 用户偏好 `reminder_mode` 的取值：逐条提醒（默认）
+pub const REMINDER_MODE_INDIVIDUAL: &str = "individual";
+/// 用户偏好 `reminder_mode` 的取值：窗口内的提醒合并为一条摘要
+pub const REMINDER_MODE_DIGEST: &str = "digest";
+
+/// 摘要模式下按 (用户, 通知渠道) 暂存的提醒
+///
+/// 窗口内的第一条提醒开启窗口，窗口结束时由调用方 [`take`](Self::take) 取出并发送摘要
+#[derive(Debug, Default)]
+pub struct ReminderDigests {
+    pending: Mutex<HashMap<(String, String), Vec<serde_json::Value>>>,
+}
+
+impl ReminderDigests {
+    /// 暂存一条提醒的 payload，返回 `true` 表示它开启了新的窗口
+    pub fn push(&self, user_id: &str, channel: &str, item: serde_json::Value) -> bool {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let items = pending.entry((user_id.to_string(), channel.to_string())).or_default();
+        items.push(item);
+        items.len() == 1
+    }
+
+    /// 取出并清空窗口内暂存的提醒
+    pub fn take(&self, user_id: &str, channel: &str) -> Vec<serde_json::Value> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.remove(&(user_id.to_string(), channel.to_string())).unwrap_or_default()
+    }
+}
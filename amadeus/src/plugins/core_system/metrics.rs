@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 延迟直方图的桶上界（微秒），超出最后一个桶的记录计入溢出桶
+pub const LATENCY_BUCKETS_US: [u64; 8] = [100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 500_000];
+
+/// 单个消息类型的处理统计
+///
+/// 所有字段均为原子计数器，记录时无需加锁
+#[derive(Debug, Default)]
+pub struct HandlerMetrics {
+    count: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    /// 每个桶对应 `LATENCY_BUCKETS_US` 中的一个上界，最后一个为溢出桶
+    buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+}
+
+impl HandlerMetrics {
+    /// 记录一次处理耗时
+    pub fn record(&self, elapsed: Duration) {
+        let us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+
+        let idx = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 已处理的消息数
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// 导出为 JSON 快照
+    pub fn snapshot(&self) -> serde_json::Value {
+        let count = self.count();
+        let total_us = self.total_us.load(Ordering::Relaxed);
+        let histogram: Vec<serde_json::Value> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, bucket)| {
+                serde_json::json!({
+                    // 溢出桶没有上界
+                    "le_us": LATENCY_BUCKETS_US.get(i),
+                    "count": bucket.load(Ordering::Relaxed),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "count": count,
+            "total_us": total_us,
+            "avg_us": total_us.checked_div(count).unwrap_or(0),
+            "max_us": self.max_us.load(Ordering::Relaxed),
+            "histogram": histogram,
+        })
+    }
+}
+
+/// CoreSystem 备忘录处理器的统计信息（按消息类型分组）
+///
+/// 每个消息类型的统计项在首次出现时创建，之后的记录只需读锁和原子操作
+#[derive(Debug, Default)]
+pub struct MemoMetrics {
+    by_type: RwLock<HashMap<String, Arc<HandlerMetrics>>>,
+}
+
+impl MemoMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次指定消息类型的处理
+    pub fn record(&self, message_type: &str, elapsed: Duration) {
+        self.handler(message_type).record(elapsed);
+    }
+
+    /// 获取指定消息类型的处理次数
+    pub fn count(&self, message_type: &str) -> u64 {
+        self.by_type
+            .read()
+            .unwrap()
+            .get(message_type)
+            .map(|m| m.count())
+            .unwrap_or(0)
+    }
+
+    /// 导出所有消息类型的统计快照
+    pub fn snapshot(&self) -> serde_json::Value {
+        let by_type = self.by_type.read().unwrap();
+        let map: serde_json::Map<String, serde_json::Value> = by_type
+            .iter()
+            .map(|(ty, m)| (ty.clone(), m.snapshot()))
+            .collect();
+        serde_json::Value::Object(map)
+    }
+
+    fn handler(&self, message_type: &str) -> Arc<HandlerMetrics> {
+        if let Some(m) = self.by_type.read().unwrap().get(message_type) {
+            return m.clone();
+        }
+        self.by_type
+            .write()
+            .unwrap()
+            .entry(message_type.to_string())
+            .or_default()
+            .clone()
+    }
+}
@@ -1,52 +1,120 @@
 pub mod storage;
 pub mod scheduler;
 pub mod config;
+pub mod metrics;
+pub mod preferences;
+pub mod digest;
 
-use crate::plugin::{Plugin, PluginMetadata};
+use crate::plugin::{Plugin, PluginMetadata, StopFuture};
+use crate::util::{RandomIdGenerator, SharedIdGenerator};
 use self::storage::Storage;
-use self::storage::types::{MemoQueryParams, MemoRecord};
-use self::scheduler::Scheduler;
-use self::config::CoreSystemConfig;
+use self::storage::types::{normalize_tags, ActiveReminder, FieldUpdate, MemoQueryParams, MemoRecord};
+use self::scheduler::{FireHook, Scheduler, JOB_UUID_METADATA_KEY};
+use self::config::{CoreSystemConfig, ParentDeletePolicy};
+use self::metrics::MemoMetrics;
+use self::digest::{ReminderDigests, REMINDER_MODE_DIGEST};
 use crate::core::messaging::{
     Message,
+    MessagePriority,
     DistributionCenter,
     MessageContext
 };
+use crate::core::messaging::message::deserialize_priority_level;
 use anyhow::Result;
 use std::sync::Arc;
 use std::pin::Pin;
 use tokio::sync::mpsc;
-use tracing::{info, error, warn};
+use tracing::{info, error, warn, Instrument};
 use serde::{Deserialize, Serialize};
 
+/// 元数据键：`system.memo.remind` 所属的备忘录 ID，与调度器写入的 `job_uuid` 一起用于关联提醒历史
+pub const CAUSED_BY_MEMO_METADATA_KEY: &str = "caused_by_memo";
+
 pub struct CoreSystemPlugin {
     metadata: PluginMetadata,
     db_url: String,
     config: CoreSystemConfig,
+    metrics: Arc<MemoMetrics>,
+    /// setup_messaging 中创建的调度器，停止时用于等待正在执行的任务
+    scheduler: Arc<std::sync::Mutex<Option<Arc<Scheduler>>>>,
+    /// 用户 ID 与调度任务 UUID 的来源
+    ids: SharedIdGenerator,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct MemoCreateRequest {
     content: String,
     cron: Option<String>,
-    remind_at: Option<i64>,
+    remind_at: Option<i64>, // 一次性提醒 (Unix 秒)，可与 cron 同时设置
     tags: Option<Vec<String>>,
     todo_date: Option<i64>,
-    priority: Option<i32>, // 0=Low, 1=Normal, 2=High, 3=Critical
+    #[serde(default, deserialize_with = "deserialize_priority_level")]
+    priority: Option<i32>, // 0=Low, 1=Normal, 2=High, 3=Critical，也接受 "low"/"normal"/"high"/"critical"
+    parent_id: Option<i64>, // 父备忘录ID（用于子任务/项目分组）
+    notify_channel: Option<String>, // 提醒投递的适配器（如 "sms"），覆盖所有者的默认渠道
 }
 
-use std::path::PathBuf;
+impl From<&MemoRecord> for MemoCreateRequest {
+    fn from(memo: &MemoRecord) -> Self {
+        Self {
+            content: memo.content.clone(),
+            cron: memo.cron_pattern.clone(),
+            remind_at: memo.remind_at,
+            tags: Some(memo.tags.clone()),
+            todo_date: memo.todo_date,
+            priority: Some(memo.priority),
+            parent_id: memo.parent_id,
+            notify_channel: memo.notify_channel.clone(),
+        }
+    }
+}
+
+use std::path::Path;
 use std::fs;
 
-#[derive(Debug, Deserialize)]
+/// `todo_date`、`remind_at`、`cron` 显式传 `null` 表示清空，省略表示不修改
+#[derive(Debug, Serialize, Deserialize)]
 struct MemoUpdateRequest {
     id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tags: Option<Vec<String>>,
-    todo_date: Option<i64>,
+    #[serde(default, skip_serializing_if = "FieldUpdate::is_unchanged")]
+    todo_date: FieldUpdate<i64>,
+    #[serde(default, deserialize_with = "deserialize_priority_level", skip_serializing_if = "Option::is_none")]
     priority: Option<i32>,
-    remind_at: Option<i64>,
-    cron: Option<String>,
+    #[serde(default, skip_serializing_if = "FieldUpdate::is_unchanged")]
+    remind_at: FieldUpdate<i64>,
+    #[serde(default, skip_serializing_if = "FieldUpdate::is_unchanged")]
+    cron: FieldUpdate<String>,
+    /// 提醒投递的适配器，`null` 恢复为所有者的默认渠道
+    #[serde(default, skip_serializing_if = "FieldUpdate::is_unchanged")]
+    notify_channel: FieldUpdate<String>,
+}
+
+impl MemoUpdateRequest {
+    /// 请求中实际设置或清空的字段（不含 id），清空的字段值为 `null`
+    fn changed_fields(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut fields = match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        fields.remove("id");
+        fields
+    }
+
+    /// 是否修改了影响提醒任务的字段
+    fn affects_schedule(&self) -> bool {
+        self.content.is_some() || self.tags.is_some() || self.priority.is_some()
+            || !self.remind_at.is_unchanged() || !self.cron.is_unchanged() || !self.notify_channel.is_unchanged()
+    }
+
+    /// 是否修改了 `memos` 表中除 `notify_channel` 以外的列
+    fn updates_columns(&self) -> bool {
+        self.content.is_some() || self.tags.is_some() || self.priority.is_some()
+            || !self.todo_date.is_unchanged() || !self.remind_at.is_unchanged() || !self.cron.is_unchanged()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -61,22 +129,74 @@ struct MemoActionRequest {
     id: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize)]
+struct MemoReorderRequest {
+    id: i64,
+    /// 放在该备忘录之后，缺省或为 null 时放到最前
+    #[serde(default)]
+    after_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoSnoozeUntilRequest {
+    id: i64,
+    /// Unix 时间戳（秒）或 ISO 8601 时间；不带时区的时间按用户偏好的时区解析（默认 UTC）
+    at: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoDuplicateRequest {
+    id: i64,
+    /// 覆盖源备忘录的字段（与 create 请求的字段相同，如 todo_date、tags）
+    #[serde(default)]
+    overrides: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct MemoMetadata {
     job_uuid: Option<String>,
     extra_cron_jobs: Option<Vec<String>>,
+    /// `remind_at` 对应的一次性提醒任务
+    #[serde(default)]
+    one_shot_job_uuid: Option<String>,
+    /// 创建该备忘录的请求 message_id（用于重复投递去重）
+    #[serde(default)]
+    source_message_id: Option<String>,
+    /// 已补发过迟到提醒的 remind_at（避免每次重启重复补发）
+    #[serde(default)]
+    late_reminder_for: Option<i64>,
+    /// 暂停（snooze）后的提醒时间，及其一次性任务；新的暂停会取消旧的
+    #[serde(default)]
+    snoozed_until: Option<i64>,
+    #[serde(default)]
+    snooze_job_uuid: Option<String>,
 }
 
 impl CoreSystemPlugin {
     pub fn new(db_url: &str) -> Self {
-        // Load config from file or use default
-        let config_path = PathBuf::from("core_system_config.json");
-        let config = if config_path.exists() {
-             match fs::read_to_string(&config_path) {
-                 Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
-                     error!("Failed to parse config: {}, using default", e);
-                     CoreSystemConfig::default()
-                 }),
+        Self::from_config_file(db_url, "core_system_config.json")
+    }
+
+    /// 从指定的配置文件加载配置；文件不存在时写入默认配置
+    ///
+    /// 缺失的字段使用默认值；无效的字段（如无法解析的标签 cron）被单独移除或恢复为默认值，
+    /// 其余配置保持不变
+    pub fn from_config_file(db_url: &str, config_path: impl AsRef<Path>) -> Self {
+        let config_path = config_path.as_ref();
+        let mut config = if config_path.exists() {
+             match fs::read_to_string(config_path) {
+                 Ok(content) => match CoreSystemConfig::from_json_with_defaults(&content) {
+                     Ok((config, defaulted)) => {
+                         if !defaulted.is_empty() {
+                             warn!("Config is missing fields, using defaults for: {}", defaulted.join(", "));
+                         }
+                         config
+                     }
+                     Err(e) => {
+                         error!("Failed to parse config: {}, using default", e);
+                         CoreSystemConfig::default()
+                     }
+                 },
                  Err(e) => {
                      error!("Failed to read config: {}, using default", e);
                      CoreSystemConfig::default()
@@ -86,10 +206,13 @@ impl CoreSystemPlugin {
             let default_config = CoreSystemConfig::default();
             // Try to write default config
             if let Ok(content) = serde_json::to_string_pretty(&default_config) {
-                let _ = fs::write(&config_path, content);
+                let _ = fs::write(config_path, content);
             }
             default_config
         };
+        for e in config.repair() {
+            error!("Invalid config, ignoring {}", e);
+        }
         
         Self {
             metadata: PluginMetadata::new(
@@ -99,8 +222,33 @@ impl CoreSystemPlugin {
             ),
             db_url: db_url.to_string(),
             config,
+            metrics: Arc::new(MemoMetrics::new()),
+            scheduler: Arc::new(std::sync::Mutex::new(None)),
+            ids: Arc::new(RandomIdGenerator),
         }
     }
+
+    /// 使用指定配置替换从文件加载的配置
+    pub fn with_config(mut self, config: CoreSystemConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 使用指定的 ID 生成器创建用户 ID 和调度任务 UUID（测试中可用于精确断言）
+    pub fn with_id_generator(mut self, ids: SharedIdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// 当前使用的配置
+    pub fn config(&self) -> &CoreSystemConfig {
+        &self.config
+    }
+
+    /// 获取备忘录处理器的统计信息
+    pub fn metrics(&self) -> Arc<MemoMetrics> {
+        self.metrics.clone()
+    }
 }
 
 impl Plugin for CoreSystemPlugin {
@@ -124,86 +272,77 @@ impl Plugin for CoreSystemPlugin {
     ) -> Pin<Box<dyn std::future::Future<Output = Result<Option<Arc<MessageContext>>>> + Send>> {
         let db_url = self.db_url.clone();
         let config = self.config.clone(); // Clone config to move into closure
+        let metrics = self.metrics.clone();
         let dc = Arc::new(distribution_center.clone());
         let plugin_name = self.metadata.name.clone();
         let plugin_uid = self.metadata.uid.clone();
         let tx = message_tx.clone();
+        let scheduler_slot = self.scheduler.clone();
+        let ids = self.ids.clone();
 
         Box::pin(async move {
             info!("Setting up CoreSystem messaging...");
             
             // Initialize Storage
-            let storage = Arc::new(Storage::new(&db_url).await?);
+            let mut storage = Storage::new(&db_url).await?
+                .with_tag_normalization(config.memos.normalize_tags)
+                .with_id_generator(ids.clone());
+            if let Some(ttl) = config.memos.query_cache_ttl_secs {
+                storage = storage.with_query_cache(std::time::Duration::from_secs(ttl), config.memos.query_cache_capacity);
+            }
+            let storage = Arc::new(storage);
             info!("Storage initialized at {}", db_url);
             
-            // Initialize Scheduler
-            let scheduler = Arc::new(Scheduler::new(tx.clone()).await?);
+            // Initialize Scheduler (every reminder fire is written to the reminder log)
+            let log_storage = storage.clone();
+            let reminder_log_hook: FireHook = Arc::new(move |uuid, msg| {
+                let storage = log_storage.clone();
+                let memo_id = (msg.message_type.as_str() == "system.memo.remind")
+                    .then(|| msg.payload.get("id").and_then(|v| v.as_i64()))
+                    .flatten();
+                Box::pin(async move {
+                    if let Some(memo_id) = memo_id {
+                        if let Err(e) = storage.log_reminder(memo_id, Some(&uuid.to_string())).await {
+                            error!("Failed to log reminder for item {}: {}", memo_id, e);
+                        }
+                    }
+                })
+            });
+            let scheduler = Arc::new(Scheduler::new(tx.clone()).await?
+                .with_max_concurrent_jobs(config.scheduler.max_concurrent_jobs)
+                .with_fire_hook(reminder_log_hook)
+                .with_id_generator(ids));
             scheduler.start().await?;
+            *scheduler_slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(scheduler.clone());
             info!("Scheduler started");
 
             // Reload active reminders from Storage
             info!("Reloading active reminders...");
-            match storage.get_active_reminders().await {
-                Ok(reminders) => {
-                    for (id, content, _remind_at, cron_pattern, metadata_str, tags_str) in reminders {
-                        let mut meta = metadata_str.and_then(|m| serde_json::from_str::<MemoMetadata>(&m).ok()).unwrap_or(MemoMetadata { job_uuid: None, extra_cron_jobs: None });
-                        let mut meta_updated = false;
-
-                        // 1. Handle Main Cron
-                        if let Some(cron) = cron_pattern {
-                            let trigger_msg = Message::new(
-                                "system.memo.remind",
-                                serde_json::json!({ "id": id, "content": content, "type": "primary" })
-                            );
-                            match scheduler.add_cron_job(&cron, trigger_msg).await {
-                                Ok(uuid) => {
-                                    info!("Reloaded cron job for item {}: {}", id, uuid);
-                                    meta.job_uuid = Some(uuid.to_string());
-                                    meta_updated = true;
-                                },
-                                Err(e) => error!("Failed to reload cron job for item {}: {}", id, e),
-                            }
-                        }
-
-                        // 2. Handle Tag Reminders (Simplified reload logic: always recreate)
-                        // Note: In a real system, we might want to check if jobs are already running or stored in meta differently.
-                        // Here we just re-register based on tags.
-                        if let Some(tags_json) = tags_str {
-                            if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
-                                if tags.contains(&"stage_goal".to_string()) {
-                                    let daily_cron = "0 0 10 * * *"; 
-                                    let trigger_msg = Message::new(
-                                        "system.memo.remind",
-                                        serde_json::json!({ 
-                                            "id": id, 
-                                            "content": content,
-                                            "type": "tag_reminder",
-                                            "tag": "stage_goal"
-                                        })
-                                    );
-                                    match scheduler.add_cron_job(daily_cron, trigger_msg).await {
-                                        Ok(uuid) => {
-                                            info!("Reloaded tag reminder for item {}: {}", id, uuid);
-                                            let mut jobs = meta.extra_cron_jobs.unwrap_or_default();
-                                            jobs.push(uuid.to_string());
-                                            meta.extra_cron_jobs = Some(jobs);
-                                            meta_updated = true;
-                                        },
-                                        Err(e) => error!("Failed to reload tag reminder: {}", e),
-                                    }
-                                }
-                            }
-                        }
-
-                        if meta_updated {
-                             if let Ok(json) = serde_json::to_string(&meta) {
-                                 let _ = storage.update_memo_metadata(id, &json).await;
-                             }
-                        }
+            // 分页载入，启动时的内存占用不随备忘录数量增长
+            let page_size = config.scheduler.reload_page_size.max(1) as i64;
+            let now = chrono::Utc::now().timestamp();
+            // 按 id 键集分页：处理过程中行是否仍属于活跃提醒都不影响下一页的起点
+            let mut after_id = 0;
+            let mut reloaded = 0;
+            loop {
+                let page = match storage.active_reminders_page(page_size, after_id).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        error!("Failed to load active reminders: {}", e);
+                        break;
                     }
+                };
+                let fetched = page.len() as i64;
+                for reminder in page {
+                    after_id = reminder.id;
+                    reload_reminder(reminder, &storage, &scheduler, &tx, &config, now).await;
+                    reloaded += 1;
+                }
+                if fetched < page_size {
+                    break;
                 }
-                Err(e) => error!("Failed to load active reminders: {}", e),
             }
+            info!("Reloaded {} active reminders", reloaded);
             
             let ctx = Arc::new(MessageContext::new(
                 dc,
@@ -213,43 +352,84 @@ impl Plugin for CoreSystemPlugin {
             ));
 
             // Subscribe to relevant messages
-            let mut rx_create = ctx.subscribe("system.memo.create").await;
-            let mut rx_update = ctx.subscribe("system.memo.update").await;
-            let mut rx_complete = ctx.subscribe("system.memo.complete").await;
-            let mut rx_delete = ctx.subscribe("system.memo.delete").await;
-            let mut rx_list = ctx.subscribe("system.memo.list").await;
-            let mut rx_sched = ctx.subscribe("system.schedule.add").await;
+            let mut rx_create = ctx.subscribe("system.memo.create").await?;
+            let mut rx_update = ctx.subscribe("system.memo.update").await?;
+            let mut rx_complete = ctx.subscribe("system.memo.complete").await?;
+            let mut rx_delete = ctx.subscribe("system.memo.delete").await?;
+            let mut rx_list = ctx.subscribe("system.memo.list").await?;
+            let mut rx_today = ctx.subscribe("system.memo.today").await?;
+            let mut rx_reorder = ctx.subscribe("system.memo.reorder").await?;
+            let mut rx_duplicate = ctx.subscribe("system.memo.duplicate").await?;
+            let mut rx_snooze = ctx.subscribe("system.memo.snooze_until").await?;
+            let mut rx_remind_now = ctx.subscribe("system.memo.remind_now").await?;
+            let mut rx_metrics = ctx.subscribe("system.memo.metrics").await?;
+            let mut rx_sched = ctx.subscribe("system.schedule.add").await?;
+            let mut rx_backup = ctx.subscribe("system.backup.create").await?;
+            let mut rx_remind = ctx.subscribe("system.memo.remind").await?;
             
             // Subscribe to user messages separately because wildcard is not supported yet
-            let mut rx_user_resolve = ctx.subscribe("system.user.resolve").await;
-            let mut rx_user_grant = ctx.subscribe("system.user.grant_role").await;
+            let mut rx_user_resolve = ctx.subscribe("system.user.resolve").await?;
+            let mut rx_user_grant = ctx.subscribe("system.user.grant_role").await?;
+            let mut rx_user_by_role = ctx.subscribe("system.user.by_role").await?;
+            let mut rx_platform_stats = ctx.subscribe("system.user.platform_stats").await?;
+            let mut rx_prefs_get = ctx.subscribe("system.user.prefs.get").await?;
+            let mut rx_prefs_set = ctx.subscribe("system.user.prefs.set").await?;
+
+            // 定向请求（如 MessageContext::request_direct 发来的 system.user.resolve）
+            let mut rx_direct = ctx.enable_direct_messaging().await;
 
             let storage_clone = storage.clone();
             let scheduler_clone = scheduler.clone();
             let ctx_clone = ctx.clone();
             let config_clone = config.clone();
+            let metrics_clone = metrics.clone();
+            let digests = Arc::new(ReminderDigests::default());
 
             // Spawn message handler
             tokio::spawn(async move {
                 loop {
                     tokio::select! {
                         Ok(msg) = rx_create.recv() => {
-                            handle_memo_message(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone).await;
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
                         }
                         Ok(msg) = rx_update.recv() => {
-                            handle_memo_message(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone).await;
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
                         }
                         Ok(msg) = rx_complete.recv() => {
-                            handle_memo_message(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone).await;
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
                         }
                         Ok(msg) = rx_delete.recv() => {
-                            handle_memo_message(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone).await;
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
                         }
                         Ok(msg) = rx_list.recv() => {
-                            handle_memo_message(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone).await;
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_today.recv() => {
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_reorder.recv() => {
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_duplicate.recv() => {
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_snooze.recv() => {
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_remind_now.recv() => {
+                            handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                        }
+                        Ok(msg) = rx_metrics.recv() => {
+                            handle_metrics_message(&msg, &metrics_clone, &ctx_clone).await;
                         }
                         Ok(msg) = rx_sched.recv() => {
-                            handle_schedule_message(&msg, &scheduler_clone, &ctx_clone).await;
+                            handle_schedule_message(&msg, &storage_clone, &scheduler_clone, &ctx_clone, &config_clone).await;
+                        }
+                        Ok(msg) = rx_backup.recv() => {
+                            handle_backup_message(&msg, &storage_clone, &ctx_clone).await;
+                        }
+                        Ok(msg) = rx_remind.recv() => {
+                            route_reminder(&msg, &storage_clone, &ctx_clone, &config_clone, &digests).await;
                         }
                         Ok(msg) = rx_user_resolve.recv() => {
                             handle_user_message(&msg, &storage_clone, &ctx_clone).await;
@@ -257,6 +437,27 @@ impl Plugin for CoreSystemPlugin {
                         Ok(msg) = rx_user_grant.recv() => {
                             handle_user_message(&msg, &storage_clone, &ctx_clone).await;
                         }
+                        Ok(msg) = rx_user_by_role.recv() => {
+                            handle_user_message(&msg, &storage_clone, &ctx_clone).await;
+                        }
+                        Ok(msg) = rx_platform_stats.recv() => {
+                            handle_user_message(&msg, &storage_clone, &ctx_clone).await;
+                        }
+                        Ok(msg) = rx_prefs_get.recv() => {
+                            handle_user_message(&msg, &storage_clone, &ctx_clone).await;
+                        }
+                        Ok(msg) = rx_prefs_set.recv() => {
+                            handle_user_message(&msg, &storage_clone, &ctx_clone).await;
+                        }
+                        Some(msg) = rx_direct.recv() => {
+                            if msg.message_type.as_str().starts_with("system.user.") {
+                                handle_user_message(&msg, &storage_clone, &ctx_clone).await;
+                            } else if msg.message_type.as_str().starts_with("system.memo.") {
+                                handle_memo_message_timed(&msg, &storage_clone, &ctx_clone, &scheduler_clone, &config_clone, &metrics_clone).await;
+                            } else {
+                                warn!("Ignoring unsupported direct message: {}", msg.message_type.as_str());
+                            }
+                        }
                         else => {
                             tracing::info!("All message channels closed, stopping handler");
                             break;
@@ -268,6 +469,7 @@ impl Plugin for CoreSystemPlugin {
             // Spawn expiration checker
             let storage_expire = storage.clone();
             let config_expire = config.clone();
+            let ctx_expire = ctx.clone();
             
             tokio::spawn(async move {
                 let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600)); // Check hourly
@@ -275,9 +477,12 @@ impl Plugin for CoreSystemPlugin {
                     interval.tick().await;
                     // Mark expired
                     match storage_expire.mark_expired_memos().await {
-                        Ok(count) => {
-                            if count > 0 {
-                                info!("Marked {} memos as expired", count);
+                        Ok(ids) => {
+                            if !ids.is_empty() {
+                                info!("Marked {} memos as expired", ids.len());
+                            }
+                            for id in ids {
+                                notify_memo_changed(&ctx_expire, id, "expired", serde_json::json!({ "status": "expired" })).await;
                             }
                         },
                         Err(e) => error!("Failed to check expired memos: {}", e),
@@ -300,6 +505,285 @@ impl Plugin for CoreSystemPlugin {
             Ok(Some(ctx))
         })
     }
+
+    fn stop_async(&mut self) -> StopFuture<'_> {
+        let scheduler = self.scheduler.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let timeout = std::time::Duration::from_millis(self.config.scheduler.shutdown_timeout_ms);
+        Box::pin(async move {
+            info!("Stopping CoreSystemPlugin");
+            if let Some(scheduler) = scheduler {
+                // 超时只记录警告，不阻止其余插件停止
+                if let Err(e) = scheduler.shutdown(timeout).await {
+                    warn!("Scheduler did not shut down cleanly: {}", e);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 重新注册一条活跃提醒的调度任务
+async fn reload_reminder(
+    reminder: ActiveReminder,
+    storage: &Storage,
+    scheduler: &Scheduler,
+    tx: &mpsc::Sender<Message>,
+    config: &CoreSystemConfig,
+    now: i64,
+) {
+    let ActiveReminder { id, content, remind_at, cron_pattern, metadata: metadata_str, tags: tags_str, notify_channel } = reminder;
+    let mut meta = metadata_str.and_then(|m| serde_json::from_str::<MemoMetadata>(&m).ok()).unwrap_or_default();
+    let mut meta_updated = false;
+
+    // 1. Handle Main Cron
+    if let Some(cron) = &cron_pattern {
+        let trigger_msg = remind_message(
+            id,
+            serde_json::json!({ "id": id, "content": content, "type": "primary", "notify_channel": notify_channel })
+        );
+        match scheduler.add_cron_job(cron, trigger_msg).await {
+            Ok(uuid) => {
+                info!("Reloaded cron job for item {}: {}", id, uuid);
+                meta.job_uuid = Some(uuid.to_string());
+                meta_updated = true;
+            },
+            Err(e) => error!("Failed to reload cron job for item {}: {}", id, e),
+        }
+    }
+
+    // 1b. Handle One-shot Reminder (past-due ones follow missed_reminder_policy)
+    if let Some(at) = remind_at {
+        if at > now {
+            let trigger_msg = remind_message(
+                id,
+                serde_json::json!({ "id": id, "content": content, "type": "one_shot", "remind_at": at, "notify_channel": notify_channel })
+            );
+            match scheduler.add_one_shot_job(at, trigger_msg).await {
+                Ok(uuid) => {
+                    info!("Reloaded one-shot reminder for item {}: {}", id, uuid);
+                    meta.one_shot_job_uuid = Some(uuid.to_string());
+                    meta_updated = true;
+                },
+                Err(e) => error!("Failed to reload one-shot reminder for item {}: {}", id, e),
+            }
+        } else {
+            let overdue = (now - at) as u64;
+            let already_sent = meta.late_reminder_for == Some(at);
+            if !already_sent && config.memos.missed_reminder_policy.should_fire(overdue) {
+                info!("Firing missed one-shot reminder for item {} ({}s late)", id, overdue);
+                let late_msg = remind_message(
+                    id,
+                    serde_json::json!({
+                        "id": id,
+                        "content": content,
+                        "type": "one_shot",
+                        "remind_at": at,
+                        "late": true,
+                        "notify_channel": notify_channel
+                    })
+                );
+                match tx.send(late_msg).await {
+                    Ok(_) => {
+                        if let Err(e) = storage.log_reminder(id, None).await {
+                            error!("Failed to log missed reminder for item {}: {}", id, e);
+                        }
+                        meta.late_reminder_for = Some(at);
+                        meta_updated = true;
+                    },
+                    Err(e) => error!("Failed to send missed reminder for item {}: {}", id, e),
+                }
+            } else {
+                info!("Skipping past-due one-shot reminder for item {} (remind_at {})", id, at);
+            }
+        }
+    }
+
+    // 1c. Handle pending snooze
+    if let Some(until) = meta.snoozed_until {
+        meta.snooze_job_uuid = None;
+        if until > now {
+            match schedule_snooze(scheduler, id, &content, notify_channel.as_deref(), until).await {
+                Ok(uuid) => {
+                    info!("Reloaded snooze for item {}: {}", id, uuid);
+                    meta.snooze_job_uuid = Some(uuid.to_string());
+                },
+                Err(e) => error!("Failed to reload snooze for item {}: {}", id, e),
+            }
+        } else {
+            meta.snoozed_until = None;
+        }
+        meta_updated = true;
+    }
+
+    // 2. Handle Tag Reminders (Simplified reload logic: always recreate)
+    // Note: In a real system, we might want to check if jobs are already running or stored in meta differently.
+    // Here we just re-register based on tags and `memos.tag_schedules`.
+    if let Some(tags_json) = tags_str {
+        if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
+            for tag in &schedule_tags(&tags, config) {
+                let Some(tag_cron) = config.memos.tag_schedules.get(tag) else {
+                    continue;
+                };
+                let trigger_msg = remind_message(
+                    id,
+                    serde_json::json!({ 
+                        "id": id, 
+                        "content": content,
+                        "type": "tag_reminder",
+                        "tag": tag,
+                        "notify_channel": notify_channel
+                    })
+                );
+                match scheduler.add_cron_job(tag_cron, trigger_msg).await {
+                    Ok(uuid) => {
+                        info!("Reloaded tag reminder for item {}: {}", id, uuid);
+                        let mut jobs = meta.extra_cron_jobs.unwrap_or_default();
+                        jobs.push(uuid.to_string());
+                        meta.extra_cron_jobs = Some(jobs);
+                        meta_updated = true;
+                    },
+                    Err(e) => error!("Failed to reload tag reminder: {}", e),
+                }
+            }
+        }
+    }
+
+    if meta_updated {
+        if let Ok(json) = serde_json::to_string(&meta) {
+            let _ = storage.update_memo_metadata(id, &json).await;
+        }
+    }
+}
+
+/// 执行备忘录消息处理并记录耗时统计
+async fn handle_memo_message_timed(
+    msg: &Message,
+    storage: &Storage,
+    ctx: &MessageContext,
+    scheduler: &Scheduler,
+    config: &CoreSystemConfig,
+    metrics: &MemoMetrics,
+) {
+    let started = std::time::Instant::now();
+    handle_memo_message(msg, storage, ctx, scheduler, config).await;
+    metrics.record(msg.message_type.as_str(), started.elapsed());
+}
+
+async fn handle_metrics_message(msg: &Message, metrics: &MemoMetrics, ctx: &MessageContext) {
+    if msg.message_type.as_str() == "system.memo.metrics" {
+        let reply = Message::new(
+            "system.memo.metrics.reply",
+            serde_json::json!({ "metrics": metrics.snapshot() })
+        );
+        if let Err(e) = ctx.send(reply).await {
+            error!("Failed to send metrics reply: {}", e);
+        }
+    }
+}
+
+/// 处理触发的提醒：在 `system.memo.remind` span（带 `caused_by_memo`、`job_uuid`）中记录日志并转发给通知适配器
+async fn route_reminder(msg: &Message, storage: &Storage, ctx: &MessageContext, config: &CoreSystemConfig, digests: &Arc<ReminderDigests>) {
+    let span = tracing::info_span!(
+        "system.memo.remind",
+        caused_by_memo = msg.metadata.get(CAUSED_BY_MEMO_METADATA_KEY).map(String::as_str),
+        job_uuid = msg.metadata.get(JOB_UUID_METADATA_KEY).map(String::as_str),
+        kind = msg.payload.get("type").and_then(|v| v.as_str()),
+    );
+    async {
+        info!("Reminder fired");
+        route_reminder_to_adapter(msg, storage, ctx, config, digests).await;
+    }
+    .instrument(span)
+    .await
+}
+
+/// 将触发的提醒转发给通知适配器 `system.notify.<adapter>`，保留关联元数据
+///
+/// 渠道优先级：备忘录的 `notify_channel` > 所有者的 `notification_platform` 偏好 > 配置的默认渠道。
+/// 所有者的 `reminder_mode` 偏好为 `"digest"` 时，提醒先暂存，窗口结束后合并为一条摘要发送
+async fn route_reminder_to_adapter(
+    msg: &Message,
+    storage: &Storage,
+    ctx: &MessageContext,
+    config: &CoreSystemConfig,
+    digests: &Arc<ReminderDigests>,
+) {
+    let owner = reminder_owner_prefs(msg, storage).await;
+    let channel = match msg.payload.get("notify_channel").and_then(|v| v.as_str()) {
+        Some(channel) => Some(channel.to_string()),
+        None => owner.as_ref()
+            .and_then(|(_, prefs)| prefs.get("notification_platform")?.as_str().map(String::from))
+            .or_else(|| config.notifications.default_channel.clone()),
+    };
+    let Some(channel) = channel else {
+        return;
+    };
+    if !config.notifications.is_known(&channel) {
+        warn!("Dropping reminder for unknown notification adapter: {}", channel);
+        return;
+    }
+
+    if let Some((user_id, prefs)) = &owner {
+        if prefs.get("reminder_mode").and_then(|v| v.as_str()) == Some(REMINDER_MODE_DIGEST) {
+            if digests.push(user_id, &channel, msg.payload.clone()) {
+                let window = std::time::Duration::from_millis(config.notifications.digest_window_ms);
+                tokio::spawn(flush_digest(ctx.clone(), digests.clone(), user_id.clone(), channel, window));
+            }
+            return;
+        }
+    }
+
+    let mut routed = Message::new(format!("system.notify.{}", channel), msg.payload.clone());
+    for key in [CAUSED_BY_MEMO_METADATA_KEY, JOB_UUID_METADATA_KEY] {
+        if let Some(value) = msg.metadata.get(key) {
+            routed.metadata.insert(key.to_string(), value.clone());
+        }
+    }
+    if let Err(e) = ctx.send(routed).await {
+        error!("Failed to route reminder to {}: {}", channel, e);
+    }
+}
+
+/// 等待摘要窗口结束，发送 `system.memo.remind.digest` 并转发给通知适配器
+async fn flush_digest(
+    ctx: MessageContext,
+    digests: Arc<ReminderDigests>,
+    user_id: String,
+    channel: String,
+    window: std::time::Duration,
+) {
+    tokio::time::sleep(window).await;
+    let items = digests.take(&user_id, &channel);
+    if items.is_empty() {
+        return;
+    }
+    info!("Sending digest of {} reminders to {} via {}", items.len(), user_id, channel);
+
+    let payload = serde_json::json!({
+        "type": "digest",
+        "user_id": user_id,
+        "notify_channel": channel,
+        "count": items.len(),
+        "items": items,
+    });
+    for message_type in ["system.memo.remind.digest".to_string(), format!("system.notify.{}", channel)] {
+        if let Err(e) = ctx.send(Message::new(message_type, payload.clone())).await {
+            error!("Failed to send reminder digest: {}", e);
+        }
+    }
+}
+
+/// 构造备忘录的提醒消息，元数据中记录所属备忘录以便追踪
+fn remind_message(id: i64, payload: serde_json::Value) -> Message {
+    Message::new("system.memo.remind", payload).with_metadata(CAUSED_BY_MEMO_METADATA_KEY, id.to_string())
+}
+
+/// 提醒所属备忘录的所有者及其偏好
+async fn reminder_owner_prefs(msg: &Message, storage: &Storage) -> Option<(String, std::collections::HashMap<String, serde_json::Value>)> {
+    let id = msg.payload.get("id")?.as_i64()?;
+    let user_id = storage.get_memo(id).await.ok()??.user_id?;
+    let prefs = storage.get_prefs(&user_id).await.ok()?;
+    Some((user_id, prefs))
 }
 
 async fn handle_memo_message(
@@ -313,125 +797,340 @@ async fn handle_memo_message(
 
     match msg_type {
         "system.memo.create" => {
-            if let Ok(req) = serde_json::from_value::<MemoCreateRequest>(msg.payload.clone()) {
-                info!("Creating item: {} (tags: {:?})", req.content, req.tags);
+            if let Ok(req) = msg.payload_as::<MemoCreateRequest>() {
+                // 重新投递的创建请求：如果已经创建过，直接回复已有的备忘录
+                if msg.is_redelivery() {
+                    if let Some(message_id) = &msg.message_id {
+                        if let Ok(Some((id, content))) = storage.find_memo_by_source_message(message_id).await {
+                            info!("Ignoring redelivered create {} (attempt {}), item {} already exists", message_id, msg.delivery_attempt, id);
+                            let reply = Message::new(
+                                "system.memo.created",
+                                serde_json::json!({ "id": id, "content": content, "duplicate": true })
+                            );
+                            let _ = ctx.send(reply).await;
+                            return;
+                        }
+                    }
+                }
+
+                info!("Creating item: {}", msg.redacted());
                 
                 // Get User ID from context if available
                 let user_id = msg.user_context.as_ref().map(|u| u.user.id.0.as_str());
-                
-                // Serialize tags to JSON string if present
-                let tags_json = req.tags.as_ref().and_then(|t| serde_json::to_string(t).ok());
-                
-                match storage.add_memo(
-                    &req.content, 
-                    req.remind_at, 
-                    req.cron.as_deref(), 
-                    tags_json.as_deref(), 
-                    req.todo_date,
-                    req.priority,
-                    user_id
-                ).await {
-                    Ok(id) => {
-                        let mut metadata = MemoMetadata {
-                            job_uuid: None,
-                            extra_cron_jobs: None,
-                        };
-
-                        // 1. Handle Main Cron (if provided)
-                        if let Some(cron) = &req.cron {
-                             let priority_cfg = config.memos.priorities.get(&req.priority.unwrap_or(1));
-                             let reminder_text = if let Some(cfg) = priority_cfg {
-                                 cfg.default_reminder_message.replace("{content}", &req.content)
-                             } else {
-                                 req.content.clone()
-                             };
-                             
-                             let trigger_msg = Message::new(
-                                 "system.memo.remind",
-                                 serde_json::json!({ 
-                                     "id": id, 
-                                     "content": req.content, 
-                                     "type": "primary",
-                                     "message": reminder_text,
-                                     "priority": req.priority
-                                 })
-                             );
-                             match scheduler.add_cron_job(cron, trigger_msg).await {
-                                 Ok(uuid) => {
-                                     info!("Scheduled reminder for item {}: {}", id, uuid);
-                                     metadata.job_uuid = Some(uuid.to_string());
-                                 },
-                                 Err(e) => error!("Failed to schedule reminder for item {}: {}", id, e),
-                             }
-                        }
-
-                        // 2. Handle Tag-based Scheduling (Simple Hardcoded Example)
-                        // In real world, this should be configurable
-                        if let Some(tags) = &req.tags {
-                            if tags.contains(&"stage_goal".to_string()) {
-                                let daily_cron = "0 0 10 * * * *"; // 10:00 AM daily
-                                let trigger_msg = Message::new(
-                                    "system.memo.remind",
-                                    serde_json::json!({ 
-                                        "id": id, 
-                                        "content": req.content,
-                                        "type": "tag_reminder",
-                                        "tag": "stage_goal"
-                                    })
-                                );
-                                match scheduler.add_cron_job(daily_cron, trigger_msg).await {
-                                    Ok(uuid) => {
-                                        info!("Scheduled tag reminder for item {}: {}", id, uuid);
-                                        let mut jobs = metadata.extra_cron_jobs.unwrap_or_default();
-                                        jobs.push(uuid.to_string());
-                                        metadata.extra_cron_jobs = Some(jobs);
-                                    },
-                                    Err(e) => error!("Failed to schedule tag reminder: {}", e),
-                                }
-                            }
-                        }
-
-                        // Update metadata
-                        if let Ok(json) = serde_json::to_string(&metadata) {
-                            let _ = storage.update_memo_metadata(id, &json).await;
-                        }
 
-                        let reply = Message::new(
-                            "system.memo.created",
-                            serde_json::json!({ "id": id, "content": req.content })
-                        );
+                if let Err(e) = check_notify_channel(req.notify_channel.as_deref(), config) {
+                    return send_memo_error(ctx, msg_type, None, &e).await;
+                }
+                match create_memo(&req, user_id, msg.message_id.clone(), storage, ctx, scheduler, config).await {
+                    Ok(payload) => {
+                        let reply = Message::new("system.memo.created", payload);
                         let _ = ctx.send(reply).await;
                     },
-                    Err(e) => error!("Failed to create item: {}", e),
+                    Err(e) => {
+                        error!("Failed to create item: {}", e);
+                        send_memo_error(ctx, msg_type, None, "failed to create item").await;
+                    }
                 }
             } else {
                 warn!("Invalid payload for system.memo.create");
             }
         },
+        "system.memo.update" => {
+            let Ok(req) = msg.payload_as::<MemoUpdateRequest>() else {
+                warn!("Invalid payload for system.memo.update");
+                return;
+            };
+            let fields = req.changed_fields();
+            if fields.is_empty() {
+                return send_memo_error(ctx, msg_type, req.id, "no fields to update").await;
+            }
+
+            let memo = match storage.get_memo(req.id).await {
+                Ok(Some(memo)) if memo.status != "deleted" => memo,
+                Ok(_) => return send_memo_error(ctx, msg_type, req.id, "item not found").await,
+                Err(e) => {
+                    error!("Failed to load item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to load item").await;
+                }
+            };
+            // 只能修改自己的备忘录（管理员除外）
+            if let Some(user_ctx) = &msg.user_context {
+                if !user_ctx.has_permission("system:admin") && memo.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+                    warn!("User {} may not update item {}", user_ctx.user.id.0, req.id);
+                    return send_memo_error(ctx, msg_type, req.id, "permission denied").await;
+                }
+            }
+
+            if let FieldUpdate::Set(channel) = &req.notify_channel {
+                if let Err(e) = check_notify_channel(Some(channel), config) {
+                    return send_memo_error(ctx, msg_type, req.id, &e).await;
+                }
+            }
+
+            let tags_json = req.tags.as_ref().and_then(|t| serde_json::to_string(t).ok());
+            if req.updates_columns() {
+                if let Err(e) = storage.update_memo(
+                    req.id,
+                    req.content.as_deref(),
+                    req.remind_at,
+                    req.cron.as_ref().map(String::as_str),
+                    tags_json.as_deref(),
+                    req.todo_date,
+                    req.priority
+                ).await {
+                    error!("Failed to update item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to update item").await;
+                }
+            }
+            if !req.notify_channel.is_unchanged() {
+                let channel = match &req.notify_channel {
+                    FieldUpdate::Set(channel) => Some(channel.as_str()),
+                    _ => None,
+                };
+                if let Err(e) = storage.set_memo_notify_channel(req.id, channel).await {
+                    error!("Failed to update notify_channel of item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to update item").await;
+                }
+            }
+
+            // 提醒内容或时间变化时重建该备忘录的调度任务
+            if req.affects_schedule() {
+                if let Err(e) = reschedule_memo(req.id, storage, scheduler, config).await {
+                    error!("Failed to reschedule item {}: {}", req.id, e);
+                }
+            }
+
+            info!("Item {} updated: {:?}", req.id, fields.keys().collect::<Vec<_>>());
+            let fields = serde_json::Value::Object(fields);
+            let reply = Message::new(
+                "system.memo.update.success",
+                serde_json::json!({ "id": req.id, "fields": fields })
+            );
+            let _ = ctx.send(reply).await;
+            notify_memo_changed(ctx, req.id, "updated", fields).await;
+        },
+        "system.memo.duplicate" => {
+            let Ok(req) = msg.payload_as::<MemoDuplicateRequest>() else {
+                warn!("Invalid payload for system.memo.duplicate");
+                return;
+            };
+
+            let source = match storage.get_memo(req.id).await {
+                Ok(Some(memo)) if memo.status != "deleted" => memo,
+                Ok(_) => return send_memo_error(ctx, msg_type, req.id, "item not found").await,
+                Err(e) => {
+                    error!("Failed to load item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to load item").await;
+                }
+            };
+
+            // 只能复制自己的备忘录（管理员除外）
+            let requester = msg.user_context.as_ref();
+            if let Some(user_ctx) = requester {
+                if !user_ctx.has_permission("system:admin") && source.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+                    warn!("User {} may not duplicate item {}", user_ctx.user.id.0, req.id);
+                    return send_memo_error(ctx, msg_type, req.id, "permission denied").await;
+                }
+            }
+
+            // 已过去的一次性提醒不复制，调度从新备忘录重新开始
+            let now = chrono::Utc::now().timestamp();
+            let mut base = MemoCreateRequest::from(&source);
+            base.remind_at = base.remind_at.filter(|at| *at > now);
+            let mut fields = serde_json::to_value(&base).unwrap_or_default();
+            for (key, value) in req.overrides {
+                fields[key] = value;
+            }
+            let create_req = match serde_json::from_value::<MemoCreateRequest>(fields) {
+                Ok(create_req) => create_req,
+                Err(e) => return send_memo_error(ctx, msg_type, req.id, &format!("invalid overrides: {}", e)).await,
+            };
+
+            let user_id = requester.map(|u| u.user.id.0.as_str()).or(source.user_id.as_deref());
+            match create_memo(&create_req, user_id, msg.message_id.clone(), storage, ctx, scheduler, config).await {
+                Ok(mut payload) => {
+                    info!("Item {} duplicated from {}", payload["id"], req.id);
+                    payload["source_id"] = serde_json::json!(req.id);
+                    let _ = ctx.send(Message::new("system.memo.duplicated", payload)).await;
+                },
+                Err(e) => {
+                    error!("Failed to duplicate item {}: {}", req.id, e);
+                    send_memo_error(ctx, msg_type, req.id, "failed to create duplicate").await;
+                }
+            }
+        },
+        "system.memo.remind_now" => {
+            let Ok(req) = msg.payload_as::<MemoActionRequest>() else {
+                warn!("Invalid payload for system.memo.remind_now");
+                return;
+            };
+
+            let memo = match storage.get_memo(req.id).await {
+                Ok(Some(memo)) if memo.status != "deleted" => memo,
+                Ok(_) => return send_memo_error(ctx, msg_type, req.id, "item not found").await,
+                Err(e) => {
+                    error!("Failed to load item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to load item").await;
+                }
+            };
+            if let Some(user_ctx) = &msg.user_context {
+                if !user_ctx.has_permission("system:admin") && memo.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+                    return send_memo_error(ctx, msg_type, req.id, "permission denied").await;
+                }
+            }
+
+            // 与调度触发的提醒相同的内容，`type` 为 manual 以便区分
+            let priority = Some(memo.priority);
+            let remind = remind_message(
+                memo.id,
+                serde_json::json!({
+                    "id": memo.id,
+                    "content": memo.content,
+                    "type": "manual",
+                    "message": reminder_text(&memo.content, priority, config),
+                    "priority": priority,
+                    "priority_label": priority_label(priority),
+                    "notify_channel": memo.notify_channel
+                })
+            );
+            info!("Firing reminder for item {} on demand", memo.id);
+            let _ = ctx.send(remind).await;
+        },
+        "system.memo.reorder" => {
+            let Ok(req) = msg.payload_as::<MemoReorderRequest>() else {
+                warn!("Invalid payload for system.memo.reorder");
+                return;
+            };
+
+            let memo = match storage.get_memo(req.id).await {
+                Ok(Some(memo)) if memo.status != "deleted" => memo,
+                Ok(_) => return send_memo_error(ctx, msg_type, req.id, "item not found").await,
+                Err(e) => {
+                    error!("Failed to load item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to load item").await;
+                }
+            };
+            if let Some(user_ctx) = &msg.user_context {
+                if !user_ctx.has_permission("system:admin") && memo.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+                    return send_memo_error(ctx, msg_type, req.id, "permission denied").await;
+                }
+            }
+
+            // 邻居必须属于同一所有者，由 reorder_memo 校验
+            match storage.reorder_memo(req.id, req.after_id).await {
+                Ok(sort_order) => {
+                    info!("Item {} reordered after {:?}", req.id, req.after_id);
+                    let reply = Message::new(
+                        "system.memo.reordered",
+                        serde_json::json!({ "id": req.id, "after_id": req.after_id, "sort_order": sort_order })
+                    );
+                    let _ = ctx.send(reply).await;
+                    notify_memo_changed(ctx, req.id, "updated", serde_json::json!({ "sort_order": sort_order })).await;
+                },
+                Err(e) => send_memo_error(ctx, msg_type, req.id, &e.to_string()).await,
+            }
+        },
+        "system.memo.snooze_until" => {
+            let Ok(req) = msg.payload_as::<MemoSnoozeUntilRequest>() else {
+                warn!("Invalid payload for system.memo.snooze_until");
+                return;
+            };
+
+            let memo = match storage.get_memo(req.id).await {
+                Ok(Some(memo)) if memo.status == "pending" => memo,
+                Ok(_) => return send_memo_error(ctx, msg_type, req.id, "item not found or not pending").await,
+                Err(e) => {
+                    error!("Failed to load item {}: {}", req.id, e);
+                    return send_memo_error(ctx, msg_type, req.id, "failed to load item").await;
+                }
+            };
+            if let Some(user_ctx) = &msg.user_context {
+                if !user_ctx.has_permission("system:admin") && memo.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+                    return send_memo_error(ctx, msg_type, req.id, "permission denied").await;
+                }
+            }
+
+            // 不带时区的时间按请求用户的时区偏好解析
+            let timezone = match &msg.user_context {
+                Some(user_ctx) => user_timezone(storage, &user_ctx.user.id.0).await,
+                None => None,
+            };
+            let until = match parse_snooze_time(&req.at, timezone) {
+                Ok(until) => until,
+                Err(e) => return send_memo_error(ctx, msg_type, req.id, &e.to_string()).await,
+            };
+            if until <= chrono::Utc::now().timestamp() {
+                return send_memo_error(ctx, msg_type, req.id, "snooze time is in the past").await;
+            }
+
+            let mut metadata = storage
+                .get_memo_metadata(req.id)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|m| serde_json::from_str::<MemoMetadata>(&m).ok())
+                .unwrap_or_default();
+
+            // 取消之前的暂停
+            if let Some(uuid) = metadata.snooze_job_uuid.take().and_then(|u| uuid::Uuid::parse_str(&u).ok()) {
+                info!("Cancelling previous snooze {} for item {}", uuid, req.id);
+                let _ = scheduler.remove_job(uuid).await;
+            }
+
+            match schedule_snooze(scheduler, req.id, &memo.content, memo.notify_channel.as_deref(), until).await {
+                Ok(uuid) => {
+                    info!("Item {} snoozed until {}", req.id, until);
+                    metadata.snoozed_until = Some(until);
+                    metadata.snooze_job_uuid = Some(uuid.to_string());
+                    if let Ok(json) = serde_json::to_string(&metadata) {
+                        let _ = storage.update_memo_metadata(req.id, &json).await;
+                    }
+                    let reply = Message::new(
+                        "system.memo.snoozed",
+                        serde_json::json!({ "id": req.id, "until": until })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => {
+                    error!("Failed to snooze item {}: {}", req.id, e);
+                    send_memo_error(ctx, msg_type, req.id, "failed to schedule snooze").await;
+                }
+            }
+        },
         "system.memo.complete" | "system.memo.delete" => {
-            if let Ok(req) = serde_json::from_value::<MemoActionRequest>(msg.payload.clone()) {
+            if let Ok(req) = msg.payload_as::<MemoActionRequest>() {
                 let new_status = if msg_type == "system.memo.complete" { "completed" } else { "deleted" };
                 
-                // 1. Get Metadata to find ALL Job UUIDs
-                if let Ok(Some(meta_str)) = storage.get_memo_metadata(req.id).await {
-                     if let Ok(meta) = serde_json::from_str::<MemoMetadata>(&meta_str) {
-                         // Remove main job
-                         if let Some(uuid_str) = meta.job_uuid {
-                             if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
-                                 info!("Removing main job {} for item {}", uuid, req.id);
-                                 let _ = scheduler.remove_job(uuid).await;
-                             }
-                         }
-                         // Remove extra jobs (tag reminders)
-                         if let Some(jobs) = meta.extra_cron_jobs {
-                             for uuid_str in jobs {
-                                 if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
-                                     info!("Removing extra job {} for item {}", uuid, req.id);
-                                     let _ = scheduler.remove_job(uuid).await;
-                                 }
-                             }
-                         }
-                     }
+                // 1. Remove ALL scheduled jobs of this item
+                remove_memo_jobs(storage, scheduler, req.id).await;
+
+                // 1b. Handle children when the parent is deleted
+                if msg_type == "system.memo.delete" {
+                    match config.memos.on_parent_delete {
+                        ParentDeletePolicy::Detach => match storage.detach_children(req.id).await {
+                            Ok(children) => {
+                                if !children.is_empty() {
+                                    info!("Detached {} children of item {}", children.len(), req.id);
+                                }
+                                for child in children {
+                                    notify_memo_changed(ctx, child, "updated", serde_json::json!({ "parent_id": null })).await;
+                                }
+                            },
+                            Err(e) => error!("Failed to detach children of item {}: {}", req.id, e),
+                        },
+                        ParentDeletePolicy::Cascade => match storage.descendant_ids(req.id).await {
+                            Ok(children) => {
+                                for child in children {
+                                    remove_memo_jobs(storage, scheduler, child).await;
+                                    match storage.update_memo_status(child, "deleted").await {
+                                        Ok(_) => notify_memo_changed(ctx, child, "deleted", serde_json::json!({ "status": "deleted" })).await,
+                                        Err(e) => error!("Failed to delete child {} of item {}: {}", child, req.id, e),
+                                    }
+                                }
+                            },
+                            Err(e) => error!("Failed to load children of item {}: {}", req.id, e),
+                        },
+                    }
                 }
 
                 // 2. Update Status
@@ -443,6 +1142,7 @@ async fn handle_memo_message(
                             serde_json::json!({ "id": req.id, "status": new_status })
                         );
                         let _ = ctx.send(reply).await;
+                        notify_memo_changed(ctx, req.id, new_status, serde_json::json!({ "status": new_status })).await;
                     },
                     Err(e) => error!("Failed to update item {}: {}", req.id, e),
                 }
@@ -450,17 +1150,10 @@ async fn handle_memo_message(
         },
         "system.memo.list" => {
              // 尝试解析高级查询参数
-             let mut params = if let Ok(req) = serde_json::from_value::<MemoListRequest>(msg.payload.clone()) {
-                 req.query.unwrap_or(MemoQueryParams {
-                     user_id: None, status: None, tags: None, min_priority: None,
-                     from_date: None, to_date: None, keyword: None, limit: None, offset: None
-                 })
-             } else {
-                 MemoQueryParams {
-                     user_id: None, status: None, tags: None, min_priority: None,
-                     from_date: None, to_date: None, keyword: None, limit: None, offset: None
-                 }
-             };
+             let mut params = msg.payload_as::<MemoListRequest>()
+                 .ok()
+                 .and_then(|req| req.query)
+                 .unwrap_or_default();
 
              // 自动填充当前用户ID（如果请求未指定且上下文存在）
              if params.user_id.is_none() {
@@ -472,45 +1165,522 @@ async fn handle_memo_message(
                  }
              }
 
-             match storage.query_memos(params).await {
-                 Ok(memos) => {
-                     let reply = Message::new(
-                         "system.memo.list.reply",
-                         serde_json::json!({ "memos": memos })
-                     );
+             // total 为满足过滤条件的总数，不受分页影响
+             let total = match storage.count_memos(&params).await {
+                 Ok(total) => total,
+                 Err(e) => {
+                     error!("Failed to count items: {}", e);
+                     return;
+                 }
+             };
+
+             // 带 cursor 的请求走键集分页，回复中附带 next_cursor（空字符串表示第一页）
+             let result = if params.cursor.is_some() {
+                 storage.query_memos_page(params).await
+                     .map(|page| serde_json::json!({ "memos": page.memos, "next_cursor": page.next_cursor, "total": total }))
+             } else {
+                 storage.query_memos(params).await
+                     .map(|memos| serde_json::json!({ "memos": memos, "total": total }))
+             };
+
+             match result {
+                 Ok(payload) => {
+                     let reply = Message::new("system.memo.list.reply", payload);
                      let _ = ctx.send(reply).await;
                  },
                  Err(e) => error!("Failed to list items: {}", e),
              }
         },
+        "system.memo.today" => {
+            // "今天"按请求用户的时区偏好计算，未设置时使用 UTC
+            let user_id = msg.user_context.as_ref().map(|u| u.user.id.0.clone());
+            let timezone = match &user_id {
+                Some(id) => user_timezone(storage, id).await,
+                None => None,
+            }
+            .unwrap_or(chrono_tz::UTC);
+
+            let today = chrono::Utc::now().with_timezone(&timezone).date_naive();
+            let Some((from, to)) = local_day_range(timezone, today) else {
+                error!("Cannot determine the bounds of {} in {}", today, timezone);
+                return;
+            };
+
+            let params = MemoQueryParams {
+                user_id,
+                status: msg.payload.get("status").and_then(|v| v.as_str()).map(String::from),
+                from_date: Some(from),
+                to_date: Some(to),
+                ..Default::default()
+            };
+            match storage.query_memos(params).await {
+                Ok(memos) => {
+                    let reply = Message::new(
+                        "system.memo.today.reply",
+                        serde_json::json!({
+                            "memos": memos,
+                            "date": today.to_string(),
+                            "timezone": timezone.name(),
+                            "from": from,
+                            "to": to,
+                        })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => error!("Failed to list today's items: {}", e),
+            }
+        },
         _ => {}
     }
 }
 
-async fn handle_schedule_message(msg: &Message, scheduler: &Scheduler, ctx: &MessageContext) {
-    if msg.message_type.as_str() == "system.schedule.add" {
-        if let Some(cron) = msg.payload.get("cron").and_then(|v| v.as_str()) {
-            info!("Scheduling job: {}", cron);
-            // The payload should contain the message to be sent
-            if let Some(trigger_msg_val) = msg.payload.get("message") {
-                 if let Ok(trigger_msg) = serde_json::from_value::<Message>(trigger_msg_val.clone()) {
-                     match scheduler.add_cron_job(cron, trigger_msg).await {
-                         Ok(uuid) => {
-                             info!("Job scheduled: {}", uuid);
-                             let reply = Message::new(
-                                 "system.schedule.added",
-                                 serde_json::json!({ "uuid": uuid.to_string(), "cron": cron })
-                             );
-                             if let Err(e) = ctx.send(reply).await {
-                                 error!("Failed to send reply: {}", e);
-                             }
-                         },
-                         Err(e) => error!("Failed to schedule job: {}", e),
+/// 检查备忘录指定的提醒渠道是否为已知的适配器，返回面向请求方的错误说明
+fn check_notify_channel(channel: Option<&str>, config: &CoreSystemConfig) -> std::result::Result<(), String> {
+    match channel {
+        Some(channel) if !config.notifications.is_known(channel) => Err(format!("unknown notify_channel: {}", channel)),
+        _ => Ok(()),
+    }
+}
+
+/// 创建备忘录并注册其提醒任务，返回 `system.memo.created` 的回复内容
+async fn create_memo(
+    req: &MemoCreateRequest,
+    user_id: Option<&str>,
+    source_message_id: Option<String>,
+    storage: &Storage,
+    ctx: &MessageContext,
+    scheduler: &Scheduler,
+    config: &CoreSystemConfig,
+) -> Result<serde_json::Value> {
+    check_notify_channel(req.notify_channel.as_deref(), config).map_err(anyhow::Error::msg)?;
+
+    // Serialize tags to JSON string if present
+    let tags_json = req.tags.as_ref().and_then(|t| serde_json::to_string(t).ok());
+
+    let id = storage.add_memo(
+        &req.content, 
+        req.remind_at, 
+        req.cron.as_deref(), 
+        tags_json.as_deref(), 
+        req.todo_date,
+        req.priority,
+        user_id,
+        req.parent_id
+    ).await?;
+    if req.notify_channel.is_some() {
+        storage.set_memo_notify_channel(id, req.notify_channel.as_deref()).await?;
+    }
+
+    let mut metadata = MemoMetadata {
+        source_message_id,
+        ..Default::default()
+    };
+    schedule_memo_jobs(id, req, &mut metadata, scheduler, config).await;
+
+    // Update metadata
+    if let Ok(json) = serde_json::to_string(&metadata) {
+        let _ = storage.update_memo_metadata(id, &json).await;
+    }
+
+    // 下一次提醒时间：cron 的下一次触发与 remind_at 中较早的一个
+    let mut next_fire_time = req.remind_at;
+    if let Some(uuid) = metadata.job_uuid.as_deref().and_then(|u| uuid::Uuid::parse_str(u).ok()) {
+        if let Ok(Some(next)) = scheduler.next_fire_time(uuid).await {
+            next_fire_time = Some(next_fire_time.map_or(next, |at| at.min(next)));
+        }
+    }
+
+    notify_memo_changed(ctx, id, "created", serde_json::to_value(req).unwrap_or_default()).await;
+
+    let mut payload = serde_json::json!({ "id": id, "content": req.content });
+    if let Some(next) = next_fire_time {
+        payload["next_fire_time"] = serde_json::json!(next);
+    }
+    let warnings = config_warnings(req, config);
+    if !warnings.is_empty() {
+        payload["warnings"] = serde_json::json!(warnings);
+    }
+    Ok(payload)
+}
+
+/// 重新加载备忘录并重建其所有提醒任务（保留元数据中与调度无关的字段）
+async fn reschedule_memo(id: i64, storage: &Storage, scheduler: &Scheduler, config: &CoreSystemConfig) -> Result<()> {
+    let memo = storage.get_memo(id).await?.ok_or_else(|| anyhow::anyhow!("item {} not found", id))?;
+    remove_memo_jobs(storage, scheduler, id).await;
+
+    let mut metadata = storage
+        .get_memo_metadata(id)
+        .await?
+        .and_then(|m| serde_json::from_str::<MemoMetadata>(&m).ok())
+        .unwrap_or_default();
+    metadata.job_uuid = None;
+    metadata.one_shot_job_uuid = None;
+    metadata.extra_cron_jobs = None;
+    metadata.late_reminder_for = None;
+    // 提醒时间变化后，之前的暂停不再有意义
+    metadata.snoozed_until = None;
+    metadata.snooze_job_uuid = None;
+
+    schedule_memo_jobs(id, &MemoCreateRequest::from(&memo), &mut metadata, scheduler, config).await;
+    storage.update_memo_metadata(id, &serde_json::to_string(&metadata)?).await
+}
+
+/// 为备忘录注册提醒任务（cron、一次性提醒、标签提醒），任务 UUID 记录到 `metadata`
+async fn schedule_memo_jobs(
+    id: i64,
+    req: &MemoCreateRequest,
+    metadata: &mut MemoMetadata,
+    scheduler: &Scheduler,
+    config: &CoreSystemConfig,
+) {
+    // `remind_at` 与 `cron` 可以同时设置：
+    // remind_at 注册一次性提醒，cron 注册周期提醒，两者独立触发
+
+    // 1. Handle Main Cron (if provided)
+    if let Some(cron) = &req.cron {
+         let trigger_msg = templated_reminder(id, req, "primary", config);
+         match scheduler.add_cron_job(cron, trigger_msg).await {
+             Ok(uuid) => {
+                 info!("Scheduled reminder for item {}: {}", id, uuid);
+                 metadata.job_uuid = Some(uuid.to_string());
+             },
+             Err(e) => error!("Failed to schedule reminder for item {}: {}", id, e),
+         }
+    }
+
+    // 1b. Handle One-shot Reminder (if provided)
+    if let Some(at) = req.remind_at {
+        let mut trigger_msg = templated_reminder(id, req, "one_shot", config);
+        trigger_msg.payload["remind_at"] = serde_json::json!(at);
+        match scheduler.add_one_shot_job(at, trigger_msg).await {
+            Ok(uuid) => {
+                info!("Scheduled one-shot reminder for item {}: {}", id, uuid);
+                metadata.one_shot_job_uuid = Some(uuid.to_string());
+            },
+            Err(e) => error!("Failed to schedule one-shot reminder for item {}: {}", id, e),
+        }
+    }
+
+    // 2. Handle Tag-based Scheduling (configured in `memos.tag_schedules`)
+    for tag in &schedule_tags(req.tags.as_deref().unwrap_or_default(), config) {
+        let Some(tag_cron) = config.memos.tag_schedules.get(tag) else {
+            continue;
+        };
+        let trigger_msg = remind_message(
+            id,
+            serde_json::json!({ 
+                "id": id, 
+                "content": req.content,
+                "type": "tag_reminder",
+                "tag": tag,
+                "notify_channel": req.notify_channel
+            })
+        );
+        match scheduler.add_cron_job(tag_cron, trigger_msg).await {
+            Ok(uuid) => {
+                info!("Scheduled tag reminder for item {}: {}", id, uuid);
+                metadata.extra_cron_jobs.get_or_insert_with(Vec::new).push(uuid.to_string());
+            },
+            Err(e) => error!("Failed to schedule tag reminder: {}", e),
+        }
+    }
+}
+
+/// 用于匹配 `memos.tag_schedules` 的标签：开启 `memos.normalize_tags` 时与存储中的标签一样规范化
+fn schedule_tags(tags: &[String], config: &CoreSystemConfig) -> Vec<String> {
+    if config.memos.normalize_tags {
+        normalize_tags(tags)
+    } else {
+        tags.to_vec()
+    }
+}
+
+/// 按优先级模板构造的提醒消息（`type` 为 `kind`），备忘录的主提醒和 `system.schedule.add` 共用
+fn templated_reminder(id: i64, req: &MemoCreateRequest, kind: &str, config: &CoreSystemConfig) -> Message {
+    remind_message(
+        id,
+        serde_json::json!({
+            "id": id,
+            "content": req.content,
+            "type": kind,
+            "message": reminder_text(&req.content, req.priority, config),
+            "priority": req.priority,
+            "priority_label": priority_label(req.priority),
+            "notify_channel": req.notify_channel
+        })
+    )
+}
+
+/// 检查创建请求引用的配置（标签提醒的 cron、优先级模板），返回给用户的警告
+///
+/// 这些问题不会阻止创建，只是对应的提醒不会按预期生效
+fn config_warnings(req: &MemoCreateRequest, config: &CoreSystemConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for tag in &schedule_tags(req.tags.as_deref().unwrap_or_default(), config) {
+        if let Some(tag_cron) = config.memos.tag_schedules.get(tag) {
+            if let Err(e) = Scheduler::validate_cron(tag_cron) {
+                warnings.push(format!("tag reminder for '{}' not scheduled: invalid cron '{}' ({})", tag, tag_cron, e));
+            }
+        }
+    }
+    if let Some(priority) = req.priority {
+        if !config.memos.priorities.contains_key(&priority) {
+            warnings.push(format!("priority {} is not configured, default reminder text is used", priority));
+        }
+    }
+    warnings
+}
+
+/// 按优先级模板生成提醒文本，未配置该优先级时使用原内容
+fn reminder_text(content: &str, priority: Option<i32>, config: &CoreSystemConfig) -> String {
+    match config.memos.priorities.get(&priority.unwrap_or(1)) {
+        Some(cfg) => cfg.default_reminder_message.replace("{content}", content),
+        None => content.to_string(),
+    }
+}
+
+/// 优先级的名称（如 `"high"`），随数字等级一起放入回复和提醒中
+fn priority_label(priority: Option<i32>) -> Option<&'static str> {
+    priority.and_then(|p| MessagePriority::from_level(p.into())).map(MessagePriority::label)
+}
+
+/// 注册暂停后的一次性提醒
+async fn schedule_snooze(
+    scheduler: &Scheduler,
+    id: i64,
+    content: &str,
+    notify_channel: Option<&str>,
+    until: i64,
+) -> Result<uuid::Uuid> {
+    let trigger_msg = remind_message(
+        id,
+        serde_json::json!({ "id": id, "content": content, "type": "snooze", "remind_at": until, "notify_channel": notify_channel })
+    );
+    scheduler.add_one_shot_job(until, trigger_msg).await
+}
+
+/// 用户的时区偏好，未设置或无法解析时返回 `None`
+async fn user_timezone(storage: &Storage, user_id: &str) -> Option<chrono_tz::Tz> {
+    let prefs = storage.get_prefs(user_id).await.ok()?;
+    prefs.get("timezone")?.as_str()?.parse().ok()
+}
+
+/// `date` 在 `timezone` 中对应的时间范围（Unix 秒，闭区间），用于按 `todo_date` 过滤
+fn local_day_range(timezone: chrono_tz::Tz, date: chrono::NaiveDate) -> Option<(i64, i64)> {
+    let start = local_midnight(timezone, date)?;
+    let end = local_midnight(timezone, date.succ_opt()?)?;
+    Some((start, end - 1))
+}
+
+/// 当地零点；夏令时切换导致零点不存在时取当天最早的有效时刻
+fn local_midnight(timezone: chrono_tz::Tz, date: chrono::NaiveDate) -> Option<i64> {
+    use chrono::TimeZone;
+
+    let midnight = date.and_time(chrono::NaiveTime::MIN);
+    (0..=4)
+        .find_map(|step| timezone.from_local_datetime(&(midnight + chrono::Duration::minutes(30 * step))).earliest())
+        .map(|dt| dt.timestamp())
+}
+
+/// 解析暂停时间：Unix 时间戳（秒）、RFC 3339 时间，或按 `timezone`（默认 UTC）解释的本地时间
+fn parse_snooze_time(at: &serde_json::Value, timezone: Option<chrono_tz::Tz>) -> Result<i64> {
+    use chrono::TimeZone;
+
+    if let Some(ts) = at.as_i64() {
+        return Ok(ts);
+    }
+    let Some(text) = at.as_str() else {
+        anyhow::bail!("at must be a unix timestamp or an ISO 8601 string");
+    };
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Ok(dt.timestamp());
+    }
+    let naive = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M"))
+        .map_err(|_| anyhow::anyhow!("invalid time: {}", text))?;
+    let tz = timezone.unwrap_or(chrono_tz::UTC);
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| anyhow::anyhow!("time does not exist in {}: {}", tz, text))
+}
+
+/// 广播备忘录变更事件 `system.memo.changed`，供客户端维护实时视图
+async fn notify_memo_changed(ctx: &MessageContext, id: i64, change_type: &str, fields: serde_json::Value) {
+    let event = Message::new(
+        "system.memo.changed",
+        serde_json::json!({ "id": id, "change_type": change_type, "fields": fields })
+    );
+    if let Err(e) = ctx.send(event).await {
+        error!("Failed to send change event for item {}: {}", id, e);
+    }
+}
+
+/// 移除备忘录关联的所有调度任务（主 cron、一次性提醒、标签提醒）
+async fn remove_memo_jobs(storage: &Storage, scheduler: &Scheduler, id: i64) {
+    if let Ok(Some(meta_str)) = storage.get_memo_metadata(id).await {
+         if let Ok(meta) = serde_json::from_str::<MemoMetadata>(&meta_str) {
+             // Remove main job
+             if let Some(uuid_str) = meta.job_uuid {
+                 if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
+                     info!("Removing main job {} for item {}", uuid, id);
+                     let _ = scheduler.remove_job(uuid).await;
+                 }
+             }
+             // Remove one-shot job
+             if let Some(uuid_str) = meta.one_shot_job_uuid {
+                 if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
+                     info!("Removing one-shot job {} for item {}", uuid, id);
+                     let _ = scheduler.remove_job(uuid).await;
+                 }
+             }
+             // Remove pending snooze
+             if let Some(uuid_str) = meta.snooze_job_uuid {
+                 if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
+                     info!("Removing snooze job {} for item {}", uuid, id);
+                     let _ = scheduler.remove_job(uuid).await;
+                 }
+             }
+             // Remove extra jobs (tag reminders)
+             if let Some(jobs) = meta.extra_cron_jobs {
+                 for uuid_str in jobs {
+                     if let Ok(uuid) = uuid::Uuid::parse_str(&uuid_str) {
+                         info!("Removing extra job {} for item {}", uuid, id);
+                         let _ = scheduler.remove_job(uuid).await;
                      }
                  }
-            }
+             }
+         }
+    }
+}
+
+/// 在线备份整个数据库（仅管理员）
+///
+/// Payload: `{ "path": "/var/backups/amadeus.db" }`，目标文件不能已存在；
+/// 成功回复 `system.backup.created`，失败回复 `system.backup.error`
+async fn handle_backup_message(msg: &Message, storage: &Storage, ctx: &MessageContext) {
+    let send_error = |error: String| async move {
+        let reply = Message::new("system.backup.error", serde_json::json!({ "error": error }));
+        let _ = ctx.send(reply).await;
+    };
+
+    let is_admin = msg.user_context.as_ref().is_some_and(|u| u.has_permission("system:admin"));
+    if !is_admin {
+        warn!("Rejected system.backup.create from non-admin");
+        return send_error("permission denied: system:admin required".to_string()).await;
+    }
+    let Some(path) = msg.payload.get("path").and_then(|v| v.as_str()) else {
+        return send_error("missing field: path".to_string()).await;
+    };
+
+    match storage.backup_to(path).await {
+        Ok(size) => {
+            let reply = Message::new(
+                "system.backup.created",
+                serde_json::json!({ "path": path, "size": size })
+            );
+            let _ = ctx.send(reply).await;
+        },
+        Err(e) => {
+            error!("Failed to back up database to {}: {}", path, e);
+            send_error(e.to_string()).await;
+        }
+    }
+}
+
+/// `system.schedule.add`：注册 cron 任务
+///
+/// - `{ "cron": ..., "message": {...} }`：到期时原样发送内嵌的消息
+/// - `{ "cron": ..., "memo_id": 1 }`：按备忘录的优先级模板构造 `system.memo.remind`（`type` 为 `"scheduled"`），
+///   并附带所有者的用户上下文；任务记入备忘录元数据，随备忘录删除、完成或修改提醒设置而取消。
+///   仅所有者或管理员可用，失败时回复 `system.schedule.error`
+async fn handle_schedule_message(
+    msg: &Message,
+    storage: &Storage,
+    scheduler: &Scheduler,
+    ctx: &MessageContext,
+    config: &CoreSystemConfig,
+) {
+    if msg.message_type.as_str() != "system.schedule.add" {
+        return;
+    }
+    let Some(cron) = msg.payload.get("cron").and_then(|v| v.as_str()) else {
+        return;
+    };
+    info!("Scheduling job: {}", cron);
+
+    if let Some(memo_id) = msg.payload.get("memo_id").and_then(|v| v.as_i64()) {
+        if let Err(e) = schedule_for_memo(msg, memo_id, cron, storage, scheduler, ctx, config).await {
+            error!("Failed to schedule job for item {}: {}", memo_id, e);
+            let reply = Message::new(
+                "system.schedule.error",
+                serde_json::json!({ "memo_id": memo_id, "cron": cron, "error": e.to_string() })
+            );
+            let _ = ctx.send(reply).await;
+        }
+        return;
+    }
+
+    // The payload should contain the message to be sent
+    if let Some(trigger_msg_val) = msg.payload.get("message") {
+         if let Ok(trigger_msg) = <Message as serde::Deserialize>::deserialize(trigger_msg_val) {
+             match scheduler.add_cron_job(cron, trigger_msg).await {
+                 Ok(uuid) => {
+                     info!("Job scheduled: {}", uuid);
+                     let reply = Message::new(
+                         "system.schedule.added",
+                         serde_json::json!({ "uuid": uuid.to_string(), "cron": cron })
+                     );
+                     if let Err(e) = ctx.send(reply).await {
+                         error!("Failed to send reply: {}", e);
+                     }
+                 },
+                 Err(e) => error!("Failed to schedule job: {}", e),
+             }
+         }
+    }
+}
+
+/// 为备忘录注册一个额外的 cron 提醒，提醒内容与备忘录自身的提醒一致
+async fn schedule_for_memo(
+    msg: &Message,
+    memo_id: i64,
+    cron: &str,
+    storage: &Storage,
+    scheduler: &Scheduler,
+    ctx: &MessageContext,
+    config: &CoreSystemConfig,
+) -> Result<()> {
+    let memo = storage.get_memo(memo_id).await?.ok_or_else(|| anyhow::anyhow!("item {} not found", memo_id))?;
+    if let Some(user_ctx) = &msg.user_context {
+        if !user_ctx.has_permission("system:admin") && memo.user_id.as_deref() != Some(user_ctx.user.id.0.as_str()) {
+            anyhow::bail!("permission denied");
         }
     }
+
+    let mut trigger_msg = templated_reminder(memo_id, &MemoCreateRequest::from(&memo), "scheduled", config);
+    if let Some(owner) = &memo.user_id {
+        if let Some(owner_ctx) = storage.get_user_context(owner).await? {
+            trigger_msg = trigger_msg.with_user(owner_ctx);
+        }
+    }
+
+    let uuid = scheduler.add_cron_job(cron, trigger_msg).await?;
+    info!("Scheduled job {} for item {}", uuid, memo_id);
+
+    let mut metadata = storage
+        .get_memo_metadata(memo_id)
+        .await?
+        .and_then(|m| serde_json::from_str::<MemoMetadata>(&m).ok())
+        .unwrap_or_default();
+    metadata.extra_cron_jobs.get_or_insert_with(Vec::new).push(uuid.to_string());
+    storage.update_memo_metadata(memo_id, &serde_json::to_string(&metadata)?).await?;
+
+    let reply = Message::new(
+        "system.schedule.added",
+        serde_json::json!({ "uuid": uuid.to_string(), "cron": cron, "memo_id": memo_id })
+    );
+    ctx.send(reply).await
 }
 
 async fn handle_user_message(msg: &Message, storage: &Storage, ctx: &MessageContext) {
@@ -528,9 +1698,9 @@ async fn handle_user_message(msg: &Message, storage: &Storage, ctx: &MessageCont
                             // 2. 如果找到，获取完整上下文
                             if let Ok(Some(user_ctx)) = storage.get_user_context(&user_info.id.0).await {
                                 // 3. 返回 UserContext
-                                if let crate::core::messaging::message::MessageSource::Plugin(sender_name) = &msg.source {
+                                if let Some(target) = msg.reply_target() {
                                      let reply = Message::new_direct(
-                                         sender_name,
+                                         target,
                                          "system.user.resolved",
                                          serde_json::to_value(&user_ctx).unwrap_or(serde_json::Value::Null)
                                      ).with_id(msg.message_id.clone().unwrap_or_default()); // 关联 ID
@@ -541,16 +1711,16 @@ async fn handle_user_message(msg: &Message, storage: &Storage, ctx: &MessageCont
                         },
                         Ok(None) => {
                             // 用户不存在，自动创建
-                            info!("Creating new user for {}/{}", platform, uid);
+                            info!("Creating new user: {}", msg.redacted());
                             match storage.create_user(name, platform, uid).await {
                                 Ok(new_user) => {
                                     // 赋予默认角色 'user'
                                     let _ = storage.add_role_to_user(&new_user.id.0, "user").await;
                                     
                                     if let Ok(Some(user_ctx)) = storage.get_user_context(&new_user.id.0).await {
-                                        if let crate::core::messaging::message::MessageSource::Plugin(sender_name) = &msg.source {
+                                        if let Some(target) = msg.reply_target() {
                                              let reply = Message::new_direct(
-                                                 sender_name,
+                                                 target,
                                                  "system.user.resolved",
                                                  serde_json::to_value(&user_ctx).unwrap_or(serde_json::Value::Null)
                                              ).with_id(msg.message_id.clone().unwrap_or_default());
@@ -588,6 +1758,147 @@ async fn handle_user_message(msg: &Message, storage: &Storage, ctx: &MessageCont
                  }
             }
         },
+        "system.user.by_role" => {
+            // Payload: { "role": "viewer" }  (仅管理员)
+            let is_admin = msg.user_context.as_ref().is_some_and(|u| u.has_permission("system:admin"));
+            if !is_admin {
+                warn!("Rejected system.user.by_role from non-admin");
+                send_user_error(ctx, "system.user.by_role", "permission denied: system:admin required").await;
+                return;
+            }
+
+            let Some(role) = msg.payload.get("role").and_then(|v| v.as_str()) else {
+                send_user_error(ctx, "system.user.by_role", "missing field: role").await;
+                return;
+            };
+
+            match storage.users_with_role(role).await {
+                Ok(users) => {
+                    let reply = Message::new(
+                        "system.user.by_role.reply",
+                        serde_json::json!({ "role": role, "users": users })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => error!("Failed to query users by role {}: {}", role, e),
+            }
+        },
+        "system.user.platform_stats" => {
+            // 各平台的用户数（仅管理员）
+            let is_admin = msg.user_context.as_ref().is_some_and(|u| u.has_permission("system:admin"));
+            if !is_admin {
+                warn!("Rejected system.user.platform_stats from non-admin");
+                send_user_error(ctx, "system.user.platform_stats", "permission denied: system:admin required").await;
+                return;
+            }
+
+            match storage.platform_stats().await {
+                Ok(stats) => {
+                    let platforms: Vec<_> = stats
+                        .into_iter()
+                        .map(|(platform, users)| serde_json::json!({ "platform": platform, "users": users }))
+                        .collect();
+                    let reply = Message::new(
+                        "system.user.platform_stats.reply",
+                        serde_json::json!({ "platforms": platforms })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => error!("Failed to query platform stats: {}", e),
+            }
+        },
+        "system.user.prefs.get" => {
+            // Payload: { "user_id": "..." }  (省略时为当前用户)
+            let request = "system.user.prefs.get";
+            let user_id = match prefs_target_user(msg) {
+                Ok(id) => id,
+                Err(e) => {
+                    send_user_error(ctx, request, e).await;
+                    return;
+                }
+            };
+
+            match storage.get_prefs(&user_id).await {
+                Ok(prefs) => {
+                    let reply = Message::new(
+                        "system.user.prefs.reply",
+                        serde_json::json!({ "user_id": user_id, "prefs": prefs })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => {
+                    error!("Failed to load preferences for {}: {}", user_id, e);
+                    send_user_error(ctx, request, &format!("storage error: {}", e)).await;
+                }
+            }
+        },
+        "system.user.prefs.set" => {
+            // Payload: { "user_id": "...", "key": "timezone", "value": "Asia/Shanghai" }
+            let request = "system.user.prefs.set";
+            let user_id = match prefs_target_user(msg) {
+                Ok(id) => id,
+                Err(e) => {
+                    send_user_error(ctx, request, e).await;
+                    return;
+                }
+            };
+
+            let Some(key) = msg.payload.get("key").and_then(|v| v.as_str()) else {
+                send_user_error(ctx, request, "missing field: key").await;
+                return;
+            };
+            let value = msg.payload.get("value").cloned().unwrap_or(serde_json::Value::Null);
+            if let Err(e) = preferences::validate_preference(key, &value) {
+                send_user_error(ctx, request, &e.to_string()).await;
+                return;
+            }
+
+            match storage.set_pref(&user_id, key, &value).await {
+                Ok(()) => {
+                    let reply = Message::new(
+                        "system.user.prefs.updated",
+                        serde_json::json!({ "user_id": user_id, "key": key, "value": value })
+                    );
+                    let _ = ctx.send(reply).await;
+                },
+                Err(e) => {
+                    error!("Failed to save preference {} for {}: {}", key, user_id, e);
+                    send_user_error(ctx, request, &format!("storage error: {}", e)).await;
+                }
+            }
+        },
         _ => {}
     }
 }
+
+/// 确定偏好读写的目标用户：默认为发送者本人，操作他人需要 `system:admin`
+fn prefs_target_user(msg: &Message) -> std::result::Result<String, &'static str> {
+    let Some(user) = msg.user_context.as_ref() else {
+        return Err("missing user context");
+    };
+    match msg.payload.get("user_id").and_then(|v| v.as_str()) {
+        Some(id) if id != user.user.id.0 && !user.has_permission("system:admin") => {
+            Err("permission denied: system:admin required")
+        }
+        Some(id) => Ok(id.to_string()),
+        None => Ok(user.user.id.0.clone()),
+    }
+}
+
+/// `id` 为 `None` 时（如创建失败）回复中的 `id` 为 `null`
+async fn send_memo_error(ctx: &MessageContext, request: &str, id: impl Into<Option<i64>>, error: &str) {
+    let id = id.into();
+    let reply = Message::new(
+        "system.memo.error",
+        serde_json::json!({ "request": request, "id": id, "error": error })
+    );
+    let _ = ctx.send(reply).await;
+}
+
+async fn send_user_error(ctx: &MessageContext, request: &str, error: &str) {
+    let reply = Message::new(
+        "system.user.error",
+        serde_json::json!({ "request": request, "error": error })
+    );
+    let _ = ctx.send(reply).await;
+}
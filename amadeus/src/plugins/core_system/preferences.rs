@@ -0,0 +1,47 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+use super::digest::{REMINDER_MODE_DIGEST, REMINDER_MODE_INDIVIDUAL};
+
+/// 已知的用户偏好键，未列出的键一律拒绝
+pub const KNOWN_PREFERENCES: &[&str] = &["timezone", "quiet_hours", "notification_platform", "reminder_mode"];
+
+/// 校验偏好键和值
+///
+/// - `timezone`: IANA 时区名，如 `"Asia/Shanghai"`
+/// - `quiet_hours`: `{ "start": "HH:MM", "end": "HH:MM" }`，允许跨午夜
+/// - `notification_platform`: 非空的平台标识，如 `"discord"`
+/// - `reminder_mode`: `"individual"`（逐条提醒）或 `"digest"`（短时间内的提醒合并为一条摘要）
+pub fn validate_preference(key: &str, value: &Value) -> Result<()> {
+    match key {
+        "timezone" => {
+            let Some(tz) = value.as_str() else {
+                bail!("timezone must be a string");
+            };
+            if tz.parse::<chrono_tz::Tz>().is_err() {
+                bail!("unknown timezone: {}", tz);
+            }
+        }
+        "quiet_hours" => {
+            for field in ["start", "end"] {
+                let Some(time) = value.get(field).and_then(|v| v.as_str()) else {
+                    bail!("quiet_hours.{} is required", field);
+                };
+                if chrono::NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                    bail!("quiet_hours.{} must be HH:MM, got {}", field, time);
+                }
+            }
+        }
+        "notification_platform" => {
+            if value.as_str().is_none_or(|p| p.trim().is_empty()) {
+                bail!("notification_platform must be a non-empty string");
+            }
+        }
+        "reminder_mode" => {
+            if !matches!(value.as_str(), Some(REMINDER_MODE_INDIVIDUAL | REMINDER_MODE_DIGEST)) {
+                bail!("reminder_mode must be \"{}\" or \"{}\"", REMINDER_MODE_INDIVIDUAL, REMINDER_MODE_DIGEST);
+            }
+        }
+        _ => bail!("unknown preference: {}", key),
+    }
+    Ok(())
+}
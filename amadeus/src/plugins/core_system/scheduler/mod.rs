@@ -2,17 +2,105 @@ use anyhow::Result;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tokio::sync::mpsc;
 use crate::core::messaging::message::Message;
+use crate::util::{RandomIdGenerator, SharedIdGenerator};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, Semaphore};
 use tracing::{info, error};
 
+/// Metadata key carrying the UUID of the job that produced a scheduled message
+pub const JOB_UUID_METADATA_KEY: &str = "job_uuid";
+
+/// Hook run with the job UUID and message each time a message job fires, before the message is sent
+pub type FireHook = Arc<dyn Fn(uuid::Uuid, &Message) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
 pub struct Scheduler {
     sched: JobScheduler,
     message_tx: mpsc::Sender<Message>,
+    /// Number of job fires whose body panicked
+    panic_count: Arc<AtomicU64>,
+    fire_hook: Option<FireHook>,
+    /// Limits how many job bodies run at once; `None` means unbounded
+    concurrency: Option<Arc<Semaphore>>,
+    /// Job fires that have started and not yet finished
+    in_flight: Arc<InFlight>,
+    /// Source of job UUIDs
+    ids: SharedIdGenerator,
+}
+
+/// Counts running job fires so shutdown can wait for them to drain
+#[derive(Default)]
+struct InFlight {
+    count: AtomicUsize,
+    idle: Notify,
+}
+
+impl InFlight {
+    fn enter(self: &Arc<Self>) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(self.clone())
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            // Register for the wakeup before checking, so a fire finishing in between is not missed
+            notified.as_mut().enable();
+            if self.count.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
 }
 
 impl Scheduler {
     pub async fn new(message_tx: mpsc::Sender<Message>) -> Result<Self> {
         let sched = JobScheduler::new().await?;
-        Ok(Self { sched, message_tx })
+        Ok(Self {
+            sched,
+            message_tx,
+            panic_count: Arc::new(AtomicU64::new(0)),
+            fire_hook: None,
+            concurrency: None,
+            in_flight: Arc::new(InFlight::default()),
+            ids: Arc::new(RandomIdGenerator),
+        })
+    }
+
+    /// Allow at most `max` job bodies to run at the same time (applies to jobs added afterwards).
+    ///
+    /// Fires beyond the limit wait for a free slot instead of all running at once, so many jobs
+    /// sharing the same schedule do not stampede the message channel or the database.
+    pub fn with_max_concurrent_jobs(mut self, max: usize) -> Self {
+        self.concurrency = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Assign job UUIDs from `ids` instead of random UUIDv4s (applies to jobs added afterwards)
+    pub fn with_id_generator(mut self, ids: SharedIdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Run `hook` on every fire of jobs added afterwards via `add_cron_job` / `add_one_shot_job`.
+    pub fn with_fire_hook(mut self, hook: FireHook) -> Self {
+        self.fire_hook = Some(hook);
+        self
     }
 
     pub async fn start(&self) -> Result<()> {
@@ -20,26 +108,119 @@ impl Scheduler {
         Ok(())
     }
 
-    /// Add a cron job that sends a message
+    /// Stop firing jobs and wait up to `timeout` for fires that are already running to finish.
+    ///
+    /// Returns an error if some fires are still running when the timeout expires;
+    /// those are left to complete (or be dropped) on their own.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<()> {
+        let mut sched = self.sched.clone();
+        sched.shutdown().await?;
+
+        if tokio::time::timeout(timeout, self.in_flight.wait_idle()).await.is_err() {
+            anyhow::bail!(
+                "{} scheduled job(s) still running after {:?}",
+                self.in_flight(),
+                timeout
+            );
+        }
+        info!("Scheduler shut down");
+        Ok(())
+    }
+
+    /// Number of job fires currently running (including those waiting for a concurrency slot)
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.count.load(Ordering::SeqCst)
+    }
+
+    /// Check that `schedule` parses as a cron expression, without registering a job
+    pub fn validate_cron(schedule: &str) -> Result<()> {
+        Job::new_async(schedule, |_uuid, _l| Box::pin(async {}))?;
+        Ok(())
+    }
+
+    /// Add a cron job that sends a message (tagged with the job UUID in `job_uuid` metadata)
     pub async fn add_cron_job(&self, schedule: &str, message: Message) -> Result<uuid::Uuid> {
         let tx = self.message_tx.clone();
-        let message_clone = message.clone();
         let schedule_str = schedule.to_string();
+        let hook = self.fire_hook.clone();
+
+        self.add_cron_task(schedule, move |uuid| {
+            let tx = tx.clone();
+            let msg = message.clone().with_metadata(JOB_UUID_METADATA_KEY, uuid.to_string());
+            let sched_str = schedule_str.clone();
+            let hook = hook.clone();
+            async move {
+                info!("Executing cron job {}: {}", uuid, sched_str);
+                if let Some(hook) = hook {
+                    hook(uuid, &msg).await;
+                }
+                if let Err(e) = tx.send(msg).await {
+                    error!("Failed to send scheduled message: {}", e);
+                }
+            }
+        })
+        .await
+    }
+
+    /// Add a cron job running an arbitrary async task on every fire.
+    ///
+    /// A panic inside the task is caught, logged with the job UUID and counted;
+    /// the job stays registered and keeps firing on schedule.
+    pub async fn add_cron_task<F, Fut>(&self, schedule: &str, task: F) -> Result<uuid::Uuid>
+    where
+        F: Fn(uuid::Uuid) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let panic_count = self.panic_count.clone();
+        let concurrency = self.concurrency.clone();
+        let in_flight = self.in_flight.clone();
 
         // Job::new_async requires a static future or similar, we need to be careful with closures.
         // cloning data into the closure.
         let job = Job::new_async(schedule, move |uuid, _l| {
+            let guard = in_flight.enter();
+            Box::pin(run_guarded(uuid, task(uuid), panic_count.clone(), concurrency.clone(), guard))
+        })?;
+
+        self.add_job(job).await
+    }
+
+    /// Add a one-shot job that sends a message once at the given Unix timestamp (seconds),
+    /// tagged with the job UUID in `job_uuid` metadata.
+    /// Timestamps in the past fire immediately.
+    pub async fn add_one_shot_job(&self, at: i64, message: Message) -> Result<uuid::Uuid> {
+        let now = chrono::Utc::now().timestamp();
+        let delay = std::time::Duration::from_secs(at.saturating_sub(now).max(0) as u64);
+        let tx = self.message_tx.clone();
+        let panic_count = self.panic_count.clone();
+        let concurrency = self.concurrency.clone();
+        let in_flight = self.in_flight.clone();
+        let hook = self.fire_hook.clone();
+
+        let job = Job::new_one_shot_async(delay, move |uuid, _l| {
+            let guard = in_flight.enter();
             let tx = tx.clone();
-            let msg = message_clone.clone();
-            let sched_str = schedule_str.clone();
-            Box::pin(async move {
-                info!("Executing cron job {}: {}", uuid, sched_str);
+            let msg = message.clone().with_metadata(JOB_UUID_METADATA_KEY, uuid.to_string());
+            let hook = hook.clone();
+            Box::pin(run_guarded(uuid, async move {
+                info!("Executing one-shot job {} (scheduled for {})", uuid, at);
+                if let Some(hook) = hook {
+                    hook(uuid, &msg).await;
+                }
                 if let Err(e) = tx.send(msg).await {
                     error!("Failed to send scheduled message: {}", e);
                 }
-            })
+            }, panic_count.clone(), concurrency.clone(), guard))
         })?;
 
+        self.add_job(job).await
+    }
+
+    /// Give `job` the next id from the generator and register it
+    async fn add_job(&self, mut job: Job) -> Result<uuid::Uuid> {
+        let mut data = job.job_data()?;
+        data.id = Some(self.ids.next_uuid().into());
+        job.set_job_data(data)?;
         let guid = self.sched.add(job).await?;
         Ok(guid)
     }
@@ -50,7 +231,51 @@ impl Scheduler {
         Ok(())
     }
 
-    // For one-off jobs, tokio-cron-scheduler might be overkill or less precise, 
-    // but we can use it if we format the time as a cron string or use its other features if available.
-    // For now, let's assume CRON support is the main requirement.
+    /// Next time (Unix seconds) the given job is scheduled to fire, if known
+    pub async fn next_fire_time(&self, uuid: uuid::Uuid) -> Result<Option<i64>> {
+        let mut sched = self.sched.clone();
+        let next = sched.next_tick_for_job(uuid).await?;
+        Ok(next.map(|t| t.timestamp()))
+    }
+
+    /// Number of job fires that panicked since the scheduler was created
+    pub fn panic_count(&self) -> u64 {
+        self.panic_count.load(Ordering::Relaxed)
+    }
+}
+
+/// Run a job body in its own task so that a panic is contained to that single fire.
+/// With a concurrency limit, the body only starts once a permit is available.
+/// The fire counts as in flight until `_guard` is dropped at the end.
+async fn run_guarded<Fut>(
+    uuid: uuid::Uuid,
+    body: Fut,
+    panic_count: Arc<AtomicU64>,
+    concurrency: Option<Arc<Semaphore>>,
+    _guard: InFlightGuard,
+)
+where
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    // The permit is held until the body finishes (or panics)
+    let _permit = match concurrency {
+        Some(semaphore) => match semaphore.acquire_owned().await {
+            Ok(permit) => Some(permit),
+            Err(_) => return,
+        },
+        None => None,
+    };
+
+    if let Err(e) = tokio::spawn(body).await {
+        if e.is_panic() {
+            panic_count.fetch_add(1, Ordering::Relaxed);
+            let payload = e.into_panic();
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            error!("Scheduled job {} panicked: {}", uuid, reason);
+        }
+    }
 }
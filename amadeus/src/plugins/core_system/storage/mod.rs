@@ -1,15 +1,42 @@
 use anyhow::Result;
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite, Row, QueryBuilder};
+use sqlx::{sqlite::{SqliteConnectOptions, SqlitePoolOptions}, Pool, Sqlite, Row, QueryBuilder};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 use crate::core::user::{UserId, PlatformId, PlatformUserId, UserInfo, UserContext};
-use std::collections::HashSet;
+use crate::util::{RandomIdGenerator, SharedIdGenerator};
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
 
 pub mod types;
-use self::types::{MemoQueryParams, MemoRecord};
+mod query_cache;
+use self::query_cache::QueryCache;
+use self::types::{normalize_tags, ActiveReminder, FieldUpdate, MemoCursor, MemoPage, MemoQueryParams, MemoRecord, MemoSortBy, ReminderLogEntry};
+
+/// Indexes every database is expected to have, as (name, CREATE statement)
+const EXPECTED_INDEXES: &[(&str, &str)] = &[
+    ("idx_memos_user_status", "CREATE INDEX IF NOT EXISTS idx_memos_user_status ON memos(user_id, status)"),
+    ("idx_memos_todo_date", "CREATE INDEX IF NOT EXISTS idx_memos_todo_date ON memos(todo_date)"),
+    ("idx_memos_parent", "CREATE INDEX IF NOT EXISTS idx_memos_parent ON memos(parent_id)"),
+    // Expression index matching find_memo_by_source_message, so redelivery dedup is not a table scan
+    ("idx_memos_source_message", "CREATE INDEX IF NOT EXISTS idx_memos_source_message ON memos(json_extract(metadata, '$.source_message_id'))"),
+    ("idx_users_platform", "CREATE INDEX IF NOT EXISTS idx_users_platform ON users(platform, platform_user_id)"),
+    ("idx_reminder_log_memo", "CREATE INDEX IF NOT EXISTS idx_reminder_log_memo ON reminder_log(memo_id, fired_at)"),
+];
 
 #[derive(Debug, Clone)]
 pub struct Storage {
+    /// Pool used for schema changes and all mutations
     pool: Pool<Sqlite>,
+    /// Pool used for list/get/stats queries; the same pool as `pool` unless a replica is configured
+    read_pool: Pool<Sqlite>,
+    /// Trim, lowercase and deduplicate tags on write (and in tag filters)
+    normalize_tags: bool,
+    /// Optional TTL cache for `query_memos`, shared between clones; `None` when disabled
+    query_cache: Option<Arc<QueryCache>>,
+    /// Source of new user ids
+    ids: SharedIdGenerator,
 }
 
 impl Storage {
@@ -34,12 +61,104 @@ impl Storage {
             .connect(database_url)
             .await?;
 
-        let storage = Self { pool };
+        let storage = Self { read_pool: pool.clone(), pool, normalize_tags: true, query_cache: None, ids: Arc::new(RandomIdGenerator) };
         storage.init_schema().await?;
         
         Ok(storage)
     }
 
+    /// Initialize storage with a separate read-only pool for read-heavy queries.
+    ///
+    /// `query_memos`, `get_memo` and `memo_stats` go through `read_url`; everything else uses
+    /// `write_url`. On SQLite, `read_url` may point at the same file to get a second,
+    /// read-only pool that doesn't contend with writers.
+    pub async fn new_with_replica(write_url: &str, read_url: &str) -> Result<Self> {
+        let mut storage = Self::new(write_url).await?;
+
+        let read_options = SqliteConnectOptions::from_str(read_url)?.read_only(true);
+        storage.read_pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(read_options)
+            .await?;
+
+        info!("Storage read replica connected: {}", read_url);
+        Ok(storage)
+    }
+
+    /// Enable or disable tag normalization (enabled by default).
+    ///
+    /// When enabled, `add_memo` / `update_memo` store tags trimmed, lowercased and deduplicated,
+    /// so `Work`, `work` and ` work ` are one tag; tag filters are normalized the same way.
+    pub fn with_tag_normalization(mut self, enabled: bool) -> Self {
+        self.normalize_tags = enabled;
+        self
+    }
+
+    /// Cache `query_memos` results for `ttl`, keeping at most `capacity` distinct queries.
+    ///
+    /// Any memo mutation drops the cached queries of the memo's owner (and unscoped queries);
+    /// bulk mutations such as expiry sweeps drop the whole cache.
+    pub fn with_query_cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.query_cache = Some(Arc::new(QueryCache::new(ttl, capacity)));
+        self
+    }
+
+    /// Mint user ids from `ids` instead of random UUIDv4s (e.g. a seeded generator in tests)
+    pub fn with_id_generator(mut self, ids: SharedIdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Number of `query_memos` calls served from the cache
+    pub fn query_cache_hits(&self) -> u64 {
+        self.query_cache.as_ref().map_or(0, |c| c.hits())
+    }
+
+    /// Number of cacheable `query_memos` calls that had to hit the database
+    pub fn query_cache_misses(&self) -> u64 {
+        self.query_cache.as_ref().map_or(0, |c| c.misses())
+    }
+
+    /// Drop cached queries that may include memos owned by `user_id`
+    fn invalidate_user_queries(&self, user_id: Option<&str>) {
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate_user(user_id);
+        }
+    }
+
+    /// Drop cached queries that may include memo `id`, looking up its owner
+    async fn invalidate_memo_queries(&self, id: i64) -> Result<()> {
+        if let Some(cache) = &self.query_cache {
+            let owner: Option<Option<String>> = sqlx::query_scalar("SELECT user_id FROM memos WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+            match owner {
+                Some(user_id) => cache.invalidate_user(user_id.as_deref()),
+                None => cache.clear(),
+            }
+        }
+        Ok(())
+    }
+
+    fn clear_query_cache(&self) {
+        if let Some(cache) = &self.query_cache {
+            cache.clear();
+        }
+    }
+
+    /// Apply tag normalization to a JSON tag array; non-array input is stored unchanged
+    fn prepare_tags(&self, tags: Option<&str>) -> Option<String> {
+        let tags = tags?;
+        if !self.normalize_tags {
+            return Some(tags.to_string());
+        }
+        match serde_json::from_str::<Vec<String>>(tags) {
+            Ok(list) => serde_json::to_string(&normalize_tags(&list)).ok(),
+            Err(_) => Some(tags.to_string()),
+        }
+    }
+
     async fn init_schema(&self) -> Result<()> {
         // 创建表 - 重构 Memos 表以支持更高级的查询
         // 注意：SQLite 的 ALTER TABLE 功能有限，对于复杂的结构变更，
@@ -59,7 +178,10 @@ impl Storage {
                 tags TEXT, -- JSON array of tags: ["tag1", "tag2"]
                 todo_date INTEGER, -- 截止日期/执行日期
                 priority INTEGER DEFAULT 1, -- 重要程度: 0=Low, 1=Normal, 2=High, 3=Critical
-                user_id TEXT -- 所有者ID
+                user_id TEXT, -- 所有者ID
+                parent_id INTEGER REFERENCES memos(id) ON DELETE SET NULL, -- 父备忘录ID
+                notify_channel TEXT, -- 提醒投递的适配器（为空时使用所有者的默认渠道）
+                sort_order REAL -- 手动排序位置（为空表示未手动排列）
             );
             "#
         )
@@ -72,10 +194,29 @@ impl Storage {
         let _ = sqlx::query("ALTER TABLE memos ADD COLUMN todo_date INTEGER").execute(&self.pool).await;
         let _ = sqlx::query("ALTER TABLE memos ADD COLUMN priority INTEGER DEFAULT 1").execute(&self.pool).await;
         let _ = sqlx::query("ALTER TABLE memos ADD COLUMN user_id TEXT").execute(&self.pool).await;
+        let _ = sqlx::query("ALTER TABLE memos ADD COLUMN parent_id INTEGER REFERENCES memos(id) ON DELETE SET NULL").execute(&self.pool).await;
+        let _ = sqlx::query("ALTER TABLE memos ADD COLUMN notify_channel TEXT").execute(&self.pool).await;
+        let _ = sqlx::query("ALTER TABLE memos ADD COLUMN sort_order REAL").execute(&self.pool).await;
 
-        // 创建索引以加速查询
-        let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_memos_user_status ON memos(user_id, status)").execute(&self.pool).await;
-        let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_memos_todo_date ON memos(todo_date)").execute(&self.pool).await;
+        // 创建索引以加速查询（失败的会在 verify_indexes 中修复）
+        for (_, create_sql) in EXPECTED_INDEXES.iter().filter(|(name, _)| name.starts_with("idx_memos_")) {
+            let _ = sqlx::query(create_sql).execute(&self.pool).await;
+        }
+
+        // 提醒触发日志：每次提醒任务触发写入一行，用于审计"是否真的提醒过"
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS reminder_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                memo_id INTEGER NOT NULL REFERENCES memos(id) ON DELETE CASCADE,
+                job_uuid TEXT, -- 触发的调度任务（启动时补发的迟到提醒为空）
+                fired_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_reminder_log_memo ON reminder_log(memo_id, fired_at);
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
 
         // --- 用户系统表 ---
         
@@ -115,19 +256,108 @@ impl Storage {
             CREATE TABLE IF NOT EXISTS role_permissions (
                 role TEXT NOT NULL,
                 permission TEXT NOT NULL,
+                deny INTEGER NOT NULL DEFAULT 0, -- 1 = 显式拒绝
                 PRIMARY KEY (role, permission)
             );
             "#
         )
         .execute(&self.pool)
         .await?;
+        let _ = sqlx::query("ALTER TABLE role_permissions ADD COLUMN deny INTEGER NOT NULL DEFAULT 0").execute(&self.pool).await;
+
+        // Role Inheritance Table: `role` 继承 `inherits` 的全部权限
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS role_inherits (
+                role TEXT NOT NULL,
+                inherits TEXT NOT NULL,
+                PRIMARY KEY (role, inherits)
+            );
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // User Preferences Table: value 为 JSON 文本
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS user_preferences (
+                user_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (user_id, key),
+                FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+            );
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // 旧库迁移时索引可能创建失败（例如当时缺少 user_id 列），在所有列补齐后检查并修复
+        self.verify_indexes().await?;
 
         Ok(())
     }
 
+    /// Check `sqlite_master` for the expected indexes and recreate any that are missing.
+    /// Returns the names of the indexes that were repaired.
+    pub async fn verify_indexes(&self) -> Result<Vec<String>> {
+        let existing: HashSet<String> = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'index'")
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| row.get("name"))
+            .collect();
+
+        let mut repaired = Vec::new();
+        for (name, create_sql) in EXPECTED_INDEXES {
+            if existing.contains(*name) {
+                continue;
+            }
+            match sqlx::query(create_sql).execute(&self.pool).await {
+                Ok(_) => {
+                    info!("Repaired missing index {}", name);
+                    repaired.push(name.to_string());
+                },
+                Err(e) => warn!("Failed to repair missing index {}: {}", name, e),
+            }
+        }
+
+        Ok(repaired)
+    }
+
+    /// Write a consistent snapshot of the live database to `path` with `VACUUM INTO`.
+    ///
+    /// Readers and writers keep running; the copy reflects a single point in time.
+    /// Fails if `path` already exists. Returns the size of the backup in bytes.
+    pub async fn backup_to(&self, path: impl AsRef<Path>) -> Result<u64> {
+        let path = path.as_ref();
+        if path.exists() {
+            anyhow::bail!("backup target already exists: {}", path.display());
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().into_owned())
+            .execute(&self.pool)
+            .await?;
+
+        let size = tokio::fs::metadata(path).await?.len();
+        info!("Backed up database to {} ({} bytes)", path.display(), size);
+        Ok(size)
+    }
+
     pub fn pool(&self) -> &Pool<Sqlite> {
         &self.pool
     }
+
+    /// Pool used for read-only queries (same as `pool()` without a replica)
+    pub fn read_pool(&self) -> &Pool<Sqlite> {
+        &self.read_pool
+    }
     
     // --- 备忘录核心功能 ---
 
@@ -139,18 +369,20 @@ impl Storage {
         tags: Option<&str>, 
         todo_date: Option<i64>,
         priority: Option<i32>,
-        user_id: Option<&str>
+        user_id: Option<&str>,
+        parent_id: Option<i64>
     ) -> Result<i64> {
         let created_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
             .as_secs() as i64;
         
         let priority_val = priority.unwrap_or(1); // Default Normal
+        let tags = self.prepare_tags(tags);
             
         let id = sqlx::query(
             r#"
-            INSERT INTO memos (content, created_at, remind_at, cron_pattern, status, tags, todo_date, priority, user_id)
-            VALUES (?, ?, ?, ?, 'pending', ?, ?, ?, ?)
+            INSERT INTO memos (content, created_at, remind_at, cron_pattern, status, tags, todo_date, priority, user_id, parent_id)
+            VALUES (?, ?, ?, ?, 'pending', ?, ?, ?, ?, ?)
             RETURNING id
             "#
         )
@@ -162,29 +394,136 @@ impl Storage {
         .bind(todo_date)
         .bind(priority_val)
         .bind(user_id)
+        .bind(parent_id)
         .fetch_one(&self.pool)
         .await?
         .get(0);
-        
+
+        self.invalidate_user_queries(user_id);
         Ok(id)
     }
 
     /// 高级查询接口
     /// 使用 sqlx::QueryBuilder 安全地构建动态 SQL，防止注入
+    ///
+    /// 指定 `cursor` 时切换为键集分页的排序，需要下一页游标请使用 [`Storage::query_memos_page`]
+    ///
+    /// 启用查询缓存（[`Storage::with_query_cache`]）时，相同参数的查询在缓存有效期内直接返回缓存结果
     pub async fn query_memos(&self, params: MemoQueryParams) -> Result<Vec<MemoRecord>> {
-        let mut qb = QueryBuilder::new("SELECT * FROM memos WHERE 1=1 ");
+        let keyset = params.cursor.is_some();
+        let cached = self.query_cache.as_ref().and_then(|cache| Some((cache, QueryCache::key(&params)?)));
+        let Some((cache, key)) = cached else {
+            return self.fetch_memos(params, keyset).await;
+        };
+
+        if let Some(records) = cache.get(&key) {
+            return Ok(records);
+        }
+        let user_id = params.user_id.clone();
+        let records = self.fetch_memos(params, keyset).await?;
+        cache.insert(key, user_id, records.clone());
+        Ok(records)
+    }
+
+    /// 键集分页查询：按 `(todo_date, id)` 排序，从 `cursor` 之后取 `limit` 条
+    ///
+    /// 翻页期间插入或删除数据不会导致重复或遗漏；不设置 `limit` 时一次返回全部
+    pub async fn query_memos_page(&self, mut params: MemoQueryParams) -> Result<MemoPage> {
+        let limit = params.limit;
+        // 多取一条用于判断是否还有下一页
+        params.limit = limit.map(|l| l.max(0) + 1);
+        params.offset = None;
+
+        let mut memos = self.fetch_memos(params, true).await?;
+        let next_cursor = match limit {
+            Some(l) if memos.len() > l.max(0) as usize => {
+                memos.truncate(l.max(0) as usize);
+                memos.last().map(|m| MemoCursor::from(m).encode())
+            }
+            _ => None,
+        };
+        Ok(MemoPage { memos, next_cursor })
+    }
+
+    async fn fetch_memos(&self, params: MemoQueryParams, keyset: bool) -> Result<Vec<MemoRecord>> {
+        let mut qb = QueryBuilder::new(
+            "SELECT memos.*, \
+             (SELECT COUNT(*) FROM memos c WHERE c.parent_id = memos.id AND c.status != 'deleted') AS children \
+             FROM memos WHERE 1=1 "
+        );
 
+        self.push_memo_filters(&mut qb, &params);
+
+        // Keyset Cursor: 严格位于上一页最后一条之后（SQLite 中 NULL 排在最前）
+        if let Some(cursor) = params.cursor.as_deref().map(MemoCursor::decode).transpose()?.flatten() {
+            match cursor.todo_date {
+                Some(todo_date) => {
+                    qb.push(" AND todo_date IS NOT NULL AND (todo_date, id) > (");
+                    qb.push_bind(todo_date);
+                    qb.push(", ");
+                    qb.push_bind(cursor.id);
+                    qb.push(") ");
+                }
+                None => {
+                    qb.push(" AND (todo_date IS NOT NULL OR id > ");
+                    qb.push_bind(cursor.id);
+                    qb.push(") ");
+                }
+            }
+        }
+
+        // Ordering
+        if keyset {
+            qb.push(" ORDER BY todo_date ASC, id ASC ");
+        } else if params.sort_by == MemoSortBy::Manual {
+            qb.push(" ORDER BY sort_order IS NULL, sort_order ASC, id ASC ");
+        } else {
+            qb.push(" ORDER BY todo_date ASC, priority DESC, created_at DESC ");
+        }
+
+        // Pagination
+        if let Some(limit) = params.limit {
+            qb.push(" LIMIT ");
+            qb.push_bind(limit);
+        }
+        if let Some(offset) = params.offset {
+            qb.push(" OFFSET ");
+            qb.push_bind(offset);
+        }
+
+        let query = qb.build();
+        let rows = query.fetch_all(&self.read_pool).await?;
+
+        let records = rows.into_iter().map(MemoRecord::from).collect();
+        Ok(records)
+    }
+
+    /// 统计满足过滤条件的备忘录数量（忽略 `cursor`、`limit`、`offset`），结果不经过查询缓存
+    ///
+    /// 常见的用户 + 状态过滤由 `idx_memos_user_status` 覆盖
+    pub async fn count_memos(&self, params: &MemoQueryParams) -> Result<i64> {
+        let mut qb = QueryBuilder::new("SELECT COUNT(*) FROM memos WHERE 1=1 ");
+        self.push_memo_filters(&mut qb, params);
+
+        let count = qb.build_query_scalar::<i64>().fetch_one(&self.read_pool).await?;
+        Ok(count)
+    }
+
+    /// 追加 `params` 中的过滤条件（用户、状态、优先级、日期、父项、存在性、关键词、标签）
+    ///
+    /// 不包含游标、排序和分页，供列表查询和计数共用
+    fn push_memo_filters<'a>(&self, qb: &mut QueryBuilder<'a, Sqlite>, params: &MemoQueryParams) {
         // User Filter
-        if let Some(uid) = params.user_id {
+        if let Some(uid) = &params.user_id {
             qb.push(" AND user_id = ");
-            qb.push_bind(uid);
+            qb.push_bind(uid.clone());
         }
 
         // Status Filter
-        if let Some(status) = params.status {
+        if let Some(status) = &params.status {
             if status != "all" {
                 qb.push(" AND status = ");
-                qb.push_bind(status);
+                qb.push_bind(status.clone());
             }
         } else {
             // Default to not showing deleted
@@ -207,8 +546,22 @@ impl Storage {
             qb.push_bind(to);
         }
 
+        // Parent Filter
+        if let Some(parent_id) = params.parent_id {
+            qb.push(" AND parent_id = ");
+            qb.push_bind(parent_id);
+        }
+
+        // Presence Filters (NULL / NOT NULL)
+        if let Some(has) = params.has_todo_date {
+            qb.push(if has { " AND todo_date IS NOT NULL " } else { " AND todo_date IS NULL " });
+        }
+        if let Some(has) = params.has_cron {
+            qb.push(if has { " AND cron_pattern IS NOT NULL " } else { " AND cron_pattern IS NULL " });
+        }
+
         // Keyword Search (Content)
-        if let Some(keyword) = params.keyword {
+        if let Some(keyword) = &params.keyword {
             qb.push(" AND content LIKE ");
             qb.push_bind(format!("%{}%", keyword));
         }
@@ -217,58 +570,71 @@ impl Storage {
         // For strict correctness, we should iterate tags.
         // Or if we normalized tags, we'd use JOIN.
         // Here we use OR logic: (tags LIKE '%"tag1"%' OR tags LIKE '%"tag2"%')
-        if let Some(tags) = params.tags {
+        if let Some(tags) = &params.tags {
             if !tags.is_empty() {
                 qb.push(" AND (");
                 let mut separated = qb.separated(" OR ");
                 for tag in tags {
+                    let tag = if self.normalize_tags { tag.trim().to_lowercase() } else { tag.clone() };
                     separated.push("tags LIKE ");
                     separated.push_bind_unseparated(format!("%\"{}\"%", tag));
                 }
                 qb.push(")");
             }
         }
+    }
 
-        // Ordering
-        qb.push(" ORDER BY todo_date ASC, priority DESC, created_at DESC ");
+    /// 按 ID 获取单条备忘录（包括已删除的）
+    pub async fn get_memo(&self, id: i64) -> Result<Option<MemoRecord>> {
+        let row = sqlx::query(
+            "SELECT memos.*, \
+             (SELECT COUNT(*) FROM memos c WHERE c.parent_id = memos.id AND c.status != 'deleted') AS children \
+             FROM memos WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await?;
 
-        // Pagination
-        if let Some(limit) = params.limit {
-            qb.push(" LIMIT ");
-            qb.push_bind(limit);
-        }
-        if let Some(offset) = params.offset {
-            qb.push(" OFFSET ");
-            qb.push_bind(offset);
-        }
+        Ok(row.map(MemoRecord::from))
+    }
 
-        let query = qb.build();
-        let rows = query.fetch_all(&self.pool).await?;
+    /// 按状态统计备忘录数量，可限定用户
+    pub async fn memo_stats(&self, user_id: Option<&str>) -> Result<HashMap<String, i64>> {
+        let mut qb = QueryBuilder::new("SELECT status, COUNT(*) AS count FROM memos WHERE 1=1 ");
+        if let Some(uid) = user_id {
+            qb.push(" AND user_id = ");
+            qb.push_bind(uid);
+        }
+        qb.push(" GROUP BY status");
 
-        let records = rows.into_iter().map(MemoRecord::from).collect();
-        Ok(records)
+        let rows = qb.build().fetch_all(&self.read_pool).await?;
+        Ok(rows.iter().map(|r| (r.get("status"), r.get("count"))).collect())
     }
 
-    /// 标记过期的备忘录（自动回收）
-    pub async fn mark_expired_memos(&self) -> Result<u64> {
+    /// 标记过期的备忘录（自动回收），返回被标记的备忘录 ID
+    pub async fn mark_expired_memos(&self) -> Result<Vec<i64>> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
             .as_secs() as i64;
 
-        let result = sqlx::query(
+        let rows = sqlx::query(
             r#"
             UPDATE memos 
             SET status = 'expired' 
             WHERE status = 'pending' 
               AND todo_date IS NOT NULL 
               AND todo_date < ?
+            RETURNING id
             "#
         )
         .bind(now)
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(result.rows_affected())
+        if !rows.is_empty() {
+            self.clear_query_cache();
+        }
+        Ok(rows.iter().map(|r| r.get("id")).collect())
     }
 
     /// 回收（删除）过期的备忘录
@@ -292,46 +658,48 @@ impl Storage {
         .execute(&self.pool)
         .await?;
 
+        if result.rows_affected() > 0 {
+            self.clear_query_cache();
+        }
         Ok(result.rows_affected())
     }
 
-    /// 获取所有活跃的提醒（状态为 pending 且有 remind_at 或 cron_pattern）
-    /// 这个主要用于系统启动时加载调度器，不需要复杂的过滤
-    pub async fn get_active_reminders(&self) -> Result<Vec<(i64, String, Option<i64>, Option<String>, Option<String>, Option<String>)>> {
+    /// 分页获取活跃的提醒（状态为 pending 且有 remind_at、cron_pattern 或未到期的暂停）
+    ///
+    /// 返回 id 大于 `after_id` 的最多 `limit` 条，按 id 排序；传入上一页最后一条的 id 取下一页。
+    /// 用于系统启动时分批重新注册调度任务，避免一次性载入所有备忘录
+    pub async fn active_reminders_page(&self, limit: i64, after_id: i64) -> Result<Vec<ActiveReminder>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, content, remind_at, cron_pattern, metadata, tags
+            SELECT id, content, remind_at, cron_pattern, metadata, tags, notify_channel
             FROM memos 
             WHERE status = 'pending' 
-              AND (remind_at IS NOT NULL OR cron_pattern IS NOT NULL)
+              AND (remind_at IS NOT NULL OR cron_pattern IS NOT NULL
+                   OR CASE WHEN json_valid(metadata) THEN json_extract(metadata, '$.snoozed_until') END IS NOT NULL)
+              AND id > ?
+            ORDER BY id
+            LIMIT ?
             "#
         )
+        .bind(after_id)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        let reminders = rows.iter().map(|row| {
-            (
-                row.get("id"),
-                row.get("content"),
-                row.get("remind_at"),
-                row.get("cron_pattern"),
-                row.get("metadata"),
-                row.get("tags"),
-            )
-        }).collect();
-
-        Ok(reminders)
+        Ok(rows.into_iter().map(ActiveReminder::from).collect())
     }
 
     /// 更新备忘录完整信息
+    ///
+    /// `remind_at`、`cron_pattern`、`todo_date` 可以通过 [`FieldUpdate::Clear`] 清空
     pub async fn update_memo(
         &self, 
         id: i64, 
         content: Option<&str>, 
-        remind_at: Option<i64>, 
-        cron_pattern: Option<&str>, 
+        remind_at: FieldUpdate<i64>, 
+        cron_pattern: FieldUpdate<&str>, 
         tags: Option<&str>, 
-        todo_date: Option<i64>,
+        todo_date: FieldUpdate<i64>,
         priority: Option<i32>
     ) -> Result<()> {
         let mut qb = QueryBuilder::new("UPDATE memos SET ");
@@ -342,24 +710,42 @@ impl Storage {
             separated.push_bind_unseparated(c);
         }
         
-        if let Some(r) = remind_at {
-            separated.push("remind_at = ");
-            separated.push_bind_unseparated(r);
+        match remind_at {
+            FieldUpdate::Set(r) => {
+                separated.push("remind_at = ");
+                separated.push_bind_unseparated(r);
+            }
+            FieldUpdate::Clear => {
+                separated.push("remind_at = NULL");
+            }
+            FieldUpdate::Unchanged => {}
         }
 
-        if let Some(c) = cron_pattern {
-            separated.push("cron_pattern = ");
-            separated.push_bind_unseparated(c);
+        match cron_pattern {
+            FieldUpdate::Set(c) => {
+                separated.push("cron_pattern = ");
+                separated.push_bind_unseparated(c);
+            }
+            FieldUpdate::Clear => {
+                separated.push("cron_pattern = NULL");
+            }
+            FieldUpdate::Unchanged => {}
         }
         
-        if let Some(t) = tags {
+        if let Some(t) = self.prepare_tags(tags) {
             separated.push("tags = ");
             separated.push_bind_unseparated(t);
         }
         
-        if let Some(td) = todo_date {
-            separated.push("todo_date = ");
-            separated.push_bind_unseparated(td);
+        match todo_date {
+            FieldUpdate::Set(td) => {
+                separated.push("todo_date = ");
+                separated.push_bind_unseparated(td);
+            }
+            FieldUpdate::Clear => {
+                separated.push("todo_date = NULL");
+            }
+            FieldUpdate::Unchanged => {}
         }
         
         if let Some(p) = priority {
@@ -371,7 +757,7 @@ impl Storage {
         qb.push_bind(id);
         
         qb.build().execute(&self.pool).await?;
-        Ok(())
+        self.invalidate_memo_queries(id).await
     }
 
     /// 更新备忘录状态
@@ -381,9 +767,40 @@ impl Storage {
             .bind(id)
             .execute(&self.pool)
             .await?;
-        Ok(())
+        self.invalidate_memo_queries(id).await
     }
     
+    /// 获取备忘录的所有后代（子项、孙项……）ID，不包含自身
+    pub async fn descendant_ids(&self, id: i64) -> Result<Vec<i64>> {
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM memos WHERE parent_id = ?
+                UNION
+                SELECT m.id FROM memos m JOIN descendants d ON m.parent_id = d.id
+            )
+            SELECT id FROM descendants
+            "#
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|r| r.get("id")).collect())
+    }
+
+    /// 将备忘录的直接子项与其解除关联（parent_id 置空），返回被解除关联的子项 ID
+    pub async fn detach_children(&self, id: i64) -> Result<Vec<i64>> {
+        let rows = sqlx::query("UPDATE memos SET parent_id = NULL WHERE parent_id = ? RETURNING id")
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+        if !rows.is_empty() {
+            self.clear_query_cache();
+        }
+        Ok(rows.iter().map(|r| r.get("id")).collect())
+    }
+
     /// 更新备忘录元数据
     pub async fn update_memo_metadata(&self, id: i64, metadata: &str) -> Result<()> {
         sqlx::query("UPDATE memos SET metadata = ? WHERE id = ?")
@@ -391,7 +808,132 @@ impl Storage {
             .bind(id)
             .execute(&self.pool)
             .await?;
-        Ok(())
+        self.invalidate_memo_queries(id).await
+    }
+
+    /// 设置备忘录的提醒投递渠道，`None` 表示使用所有者的默认渠道
+    pub async fn set_memo_notify_channel(&self, id: i64, channel: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE memos SET notify_channel = ? WHERE id = ?")
+            .bind(channel)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        self.invalidate_memo_queries(id).await
+    }
+
+    /// 手动排序：把备忘录放到同一所有者的 `after_id` 之后（`None` 表示放到最前），返回新的 `sort_order`
+    ///
+    /// 新位置取前后两个邻居的中间值，只更新被移动的这一行；
+    /// 浮点间隔耗尽时才把该所有者的已排列备忘录重新编号。
+    /// 从未排列过的 `after_id` 会先被追加到已排列列表的末尾。
+    /// 已删除的备忘录不能被移动，也不能作为邻居
+    pub async fn reorder_memo(&self, id: i64, after_id: Option<i64>) -> Result<f64> {
+        if after_id == Some(id) {
+            anyhow::bail!("cannot place item {} after itself", id);
+        }
+        let mut tx = self.pool.begin().await?;
+
+        let owner: Option<String> = sqlx::query_scalar("SELECT user_id FROM memos WHERE id = ? AND status != 'deleted'")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("item {} not found", id))?;
+
+        // 前一个邻居的位置（after_id 未排列过时先放到末尾）
+        let before = match after_id {
+            Some(after_id) => {
+                let row = sqlx::query("SELECT user_id, sort_order FROM memos WHERE id = ? AND status != 'deleted'")
+                    .bind(after_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("item {} not found", after_id))?;
+                if row.get::<Option<String>, _>("user_id") != owner {
+                    anyhow::bail!("item {} belongs to a different owner", after_id);
+                }
+                match row.get::<Option<f64>, _>("sort_order") {
+                    Some(order) => Some(order),
+                    None => {
+                        let last: Option<f64> = sqlx::query_scalar(
+                            "SELECT MAX(sort_order) FROM memos WHERE user_id IS ? AND id != ? AND status != 'deleted'"
+                        )
+                        .bind(&owner)
+                        .bind(id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                        let order = last.map_or(1.0, |last| last + 1.0);
+                        sqlx::query("UPDATE memos SET sort_order = ? WHERE id = ?")
+                            .bind(order)
+                            .bind(after_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        Some(order)
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // 后一个邻居的位置
+        let next: Option<f64> = match before {
+            Some(before) => sqlx::query_scalar(
+                "SELECT MIN(sort_order) FROM memos WHERE user_id IS ? AND id != ? AND status != 'deleted' AND sort_order > ?"
+            )
+            .bind(&owner)
+            .bind(id)
+            .bind(before)
+            .fetch_one(&mut *tx)
+            .await?,
+            None => sqlx::query_scalar("SELECT MIN(sort_order) FROM memos WHERE user_id IS ? AND id != ? AND status != 'deleted'")
+                .bind(&owner)
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?,
+        };
+
+        let mut order = match (before, next) {
+            (Some(before), Some(next)) => before + (next - before) / 2.0,
+            (Some(before), None) => before + 1.0,
+            (None, Some(next)) => next - 1.0,
+            (None, None) => 1.0,
+        };
+
+        // 间隔已经无法再二分：按当前顺序重新编号后再计算
+        if before.is_some_and(|b| order <= b) || next.is_some_and(|n| order >= n) {
+            let mut ids: Vec<i64> = sqlx::query_scalar(
+                "SELECT id FROM memos WHERE user_id IS ? AND id != ? AND status != 'deleted' AND sort_order IS NOT NULL ORDER BY sort_order, id"
+            )
+            .bind(&owner)
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await?;
+            let at = match after_id {
+                Some(after_id) => ids.iter().position(|&other| other == after_id).map_or(ids.len(), |p| p + 1),
+                None => 0,
+            };
+            ids.insert(at, id);
+            for (index, other) in ids.into_iter().enumerate() {
+                let position = (index + 1) as f64;
+                if other == id {
+                    order = position;
+                    continue;
+                }
+                sqlx::query("UPDATE memos SET sort_order = ? WHERE id = ?")
+                    .bind(position)
+                    .bind(other)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        sqlx::query("UPDATE memos SET sort_order = ? WHERE id = ?")
+            .bind(order)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.invalidate_user_queries(owner.as_deref());
+        Ok(order)
     }
 
     /// 获取备忘录元数据
@@ -404,6 +946,46 @@ impl Storage {
         Ok(row.map(|r| r.get("metadata")))
     }
 
+    /// 按创建请求的 message_id 查找已创建的备忘录（用于重复投递去重）
+    pub async fn find_memo_by_source_message(&self, message_id: &str) -> Result<Option<(i64, String)>> {
+        let row = sqlx::query(
+            "SELECT id, content FROM memos WHERE json_extract(metadata, '$.source_message_id') = ? LIMIT 1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| (r.get("id"), r.get("content"))))
+    }
+
+    /// 记录一次提醒触发
+    pub async fn log_reminder(&self, memo_id: i64, job_uuid: Option<&str>) -> Result<()> {
+        let fired_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        sqlx::query("INSERT INTO reminder_log (memo_id, job_uuid, fired_at) VALUES (?, ?, ?)")
+            .bind(memo_id)
+            .bind(job_uuid)
+            .bind(fired_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 获取备忘录的提醒触发记录（最新的在前）
+    pub async fn reminder_history(&self, memo_id: i64, limit: i64) -> Result<Vec<ReminderLogEntry>> {
+        let rows = sqlx::query(
+            "SELECT memo_id, job_uuid, fired_at FROM reminder_log WHERE memo_id = ? ORDER BY fired_at DESC, id DESC LIMIT ?"
+        )
+        .bind(memo_id)
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(ReminderLogEntry::from).collect())
+    }
+
     // --- 用户系统方法 ---
 
     pub async fn get_user_by_platform(&self, platform: &str, platform_user_id: &str) -> Result<Option<UserInfo>> {
@@ -424,7 +1006,7 @@ impl Storage {
     }
 
     pub async fn create_user(&self, name: &str, platform: &str, platform_user_id: &str) -> Result<UserInfo> {
-        let id = uuid::Uuid::new_v4().to_string();
+        let id = self.ids.next_uuid().to_string();
         let created_at = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
             .as_secs() as i64;
@@ -477,24 +1059,38 @@ impl Storage {
 
         let roles: Vec<String> = role_rows.iter().map(|r| r.get("role")).collect();
 
-        // 3. Get Permissions (from all roles)
-        let mut permissions = HashSet::new();
-        for role in &roles {
-            let perm_rows = sqlx::query(
-                "SELECT permission FROM role_permissions WHERE role = ?"
+        // 3. Get Permissions (from all roles, including inherited ones)
+        let perm_rows = sqlx::query(
+            r#"
+            WITH RECURSIVE expanded(role) AS (
+                SELECT role FROM user_roles WHERE user_id = ?
+                UNION
+                SELECT ri.inherits FROM role_inherits ri JOIN expanded e ON ri.role = e.role
             )
-            .bind(role)
-            .fetch_all(&self.pool)
-            .await?;
+            SELECT DISTINCT permission, deny FROM role_permissions
+            WHERE role IN (SELECT role FROM expanded)
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-            for r in perm_rows {
-                permissions.insert(crate::core::user::Permission::new(r.get::<String, _>("permission")));
+        // 授权与拒绝分开收集，拒绝在 has_permission 中优先生效
+        let mut permissions = HashSet::new();
+        let mut denied_permissions = HashSet::new();
+        for r in &perm_rows {
+            let permission = crate::core::user::Permission::new(r.get::<String, _>("permission"));
+            if r.get::<i64, _>("deny") != 0 {
+                denied_permissions.insert(permission);
+            } else {
+                permissions.insert(permission);
             }
         }
 
         let mut ctx = UserContext::new(user_info);
         ctx.roles = roles;
         ctx.permissions = permissions;
+        ctx.denied_permissions = denied_permissions;
 
         Ok(Some(ctx))
     }
@@ -508,6 +1104,55 @@ impl Storage {
         Ok(())
     }
 
+    /// 设置角色继承关系：`role` 拥有 `inherits` 的全部权限
+    pub async fn add_role_inheritance(&self, role: &str, inherits: &str) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO role_inherits (role, inherits) VALUES (?, ?)")
+            .bind(role)
+            .bind(inherits)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 查询拥有指定角色的所有用户
+    ///
+    /// 包含通过角色继承间接拥有该角色的用户（例如查询 `viewer` 时，
+    /// 拥有继承自 `viewer` 的 `editor` 角色的用户也会返回）
+    pub async fn users_with_role(&self, role: &str) -> Result<Vec<UserInfo>> {
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE descendants(role) AS (
+                SELECT ?
+                UNION
+                SELECT ri.role FROM role_inherits ri JOIN descendants d ON ri.inherits = d.role
+            )
+            SELECT DISTINCT u.id, u.name, u.platform, u.platform_user_id, u.created_at
+            FROM users u
+            JOIN user_roles ur ON ur.user_id = u.id
+            WHERE ur.role IN (SELECT role FROM descendants)
+            ORDER BY u.created_at, u.id
+            "#
+        )
+        .bind(role)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(|r| UserInfo {
+            id: UserId::new(r.get::<String, _>("id")),
+            name: r.get("name"),
+            platform: PlatformId(r.get("platform")),
+            platform_user_id: PlatformUserId(r.get("platform_user_id")),
+        }).collect())
+    }
+
+    /// Number of users per platform, ordered by platform name (served by `idx_users_platform`)
+    pub async fn platform_stats(&self) -> Result<Vec<(String, i64)>> {
+        let rows = sqlx::query("SELECT platform, COUNT(*) AS users FROM users GROUP BY platform ORDER BY platform")
+            .fetch_all(&self.read_pool)
+            .await?;
+        Ok(rows.iter().map(|r| (r.get("platform"), r.get("users"))).collect())
+    }
+
     pub async fn add_permission_to_role(&self, role: &str, permission: &str) -> Result<()> {
         sqlx::query("INSERT OR IGNORE INTO role_permissions (role, permission) VALUES (?, ?)")
             .bind(role)
@@ -516,4 +1161,53 @@ impl Storage {
             .await?;
         Ok(())
     }
+
+    /// 为角色添加显式拒绝规则（覆盖同一角色上已有的同名授权）
+    ///
+    /// 拒绝会随角色继承传递，并优先于用户的所有授权
+    pub async fn deny_permission_to_role(&self, role: &str, permission: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO role_permissions (role, permission, deny) VALUES (?, ?, 1) \
+             ON CONFLICT(role, permission) DO UPDATE SET deny = 1"
+        )
+        .bind(role)
+        .bind(permission)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 获取用户的全部偏好设置
+    pub async fn get_prefs(&self, user_id: &str) -> Result<HashMap<String, serde_json::Value>> {
+        let rows = sqlx::query("SELECT key, value FROM user_preferences WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(&self.read_pool)
+            .await?;
+
+        let mut prefs = HashMap::new();
+        for row in rows {
+            let value: String = row.get("value");
+            prefs.insert(row.get("key"), serde_json::from_str(&value)?);
+        }
+        Ok(prefs)
+    }
+
+    /// 写入一项用户偏好（已存在则覆盖），调用方负责校验键和值
+    pub async fn set_pref(&self, user_id: &str, key: &str, value: &serde_json::Value) -> Result<()> {
+        let updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        sqlx::query(
+            "INSERT INTO user_preferences (user_id, key, value, updated_at) VALUES (?, ?, ?, ?) \
+             ON CONFLICT(user_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"
+        )
+        .bind(user_id)
+        .bind(key)
+        .bind(serde_json::to_string(value)?)
+        .bind(updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
 }
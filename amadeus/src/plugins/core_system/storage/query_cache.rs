@@ -0,0 +1,101 @@
+//! Opt-in TTL cache for hot `query_memos` list queries.
+//!
+//! Entries are keyed by a hash of the serialized [`MemoQueryParams`], keep the serialized
+//! params to rule out hash collisions, and remember which
+//! user the query was scoped to, so a mutation only has to drop that user's entries plus
+//! any unscoped (all-users) queries.
+
+use super::types::{MemoQueryParams, MemoRecord};
+use crate::util::TtlLruCache;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Cache key of a query: the hash used for lookup plus the serialized params it was built from
+pub(crate) struct QueryKey {
+    hash: u64,
+    params: String,
+}
+
+struct CachedQuery {
+    /// Serialized params of the cached query, compared on lookup
+    params: String,
+    /// `MemoQueryParams::user_id` of the cached query; `None` means it spans all users
+    user_id: Option<String>,
+    records: Vec<MemoRecord>,
+}
+
+pub(crate) struct QueryCache {
+    entries: Mutex<TtlLruCache<u64, CachedQuery>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl std::fmt::Debug for QueryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryCache")
+            .field("hits", &self.hits())
+            .field("misses", &self.misses())
+            .finish_non_exhaustive()
+    }
+}
+
+impl QueryCache {
+    pub(crate) fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(TtlLruCache::new(capacity, ttl)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for a query; `None` if the params cannot be serialized
+    pub(crate) fn key(params: &MemoQueryParams) -> Option<QueryKey> {
+        let params = serde_json::to_string(params).ok()?;
+        let mut hasher = DefaultHasher::new();
+        params.hash(&mut hasher);
+        Some(QueryKey { hash: hasher.finish(), params })
+    }
+
+    /// Cached records for `key`; an entry cached for different params (a hash collision) is a miss
+    pub(crate) fn get(&self, key: &QueryKey) -> Option<Vec<MemoRecord>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&key.hash).filter(|cached| cached.params == key.params) {
+            Some(cached) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(cached.records.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn insert(&self, key: QueryKey, user_id: Option<String>, records: Vec<MemoRecord>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(key.hash, CachedQuery { params: key.params, user_id, records });
+    }
+
+    /// Drop every entry that may contain memos owned by `user_id`
+    pub(crate) fn invalidate_user(&self, user_id: Option<&str>) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, cached| cached.user_id.is_some() && cached.user_id.as_deref() != user_id);
+    }
+
+    /// Drop every entry (used by bulk mutations that touch many users)
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, _| false);
+    }
+
+    pub(crate) fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
@@ -1,22 +1,144 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use sqlx::sqlite::SqliteRow;
 use sqlx::Row;
 use serde::{Deserialize, Serialize};
+use crate::core::messaging::message::deserialize_priority_level;
+use crate::core::messaging::MessagePriority;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MemoQueryParams {
     pub user_id: Option<String>,
     pub status: Option<String>, // "pending", "completed", "expired", "deleted", "all"
     pub tags: Option<Vec<String>>, // tags OR logic (contain any)
+    #[serde(default, deserialize_with = "deserialize_priority_level")]
     pub min_priority: Option<i32>,
     pub from_date: Option<i64>,
     pub to_date: Option<i64>,
     pub keyword: Option<String>,
+    pub parent_id: Option<i64>, // 只返回该备忘录的直接子项
+    pub has_todo_date: Option<bool>, // true: todo_date IS NOT NULL, false: IS NULL
+    pub has_cron: Option<bool>, // true: cron_pattern IS NOT NULL, false: IS NULL
+    /// 键集分页游标（上一页返回的 `next_cursor`），空字符串表示从头开始
+    ///
+    /// 设置后按 `todo_date ASC, id ASC` 排序，不再使用默认的优先级排序
+    pub cursor: Option<String>,
+    /// 排序方式，默认按 `todo_date`、优先级、创建时间排序；`cursor` 存在时忽略
+    #[serde(default)]
+    pub sort_by: MemoSortBy,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
 }
 
-#[derive(Debug, Serialize)]
+/// 列表排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoSortBy {
+    /// `todo_date` 升序，其次优先级降序、创建时间降序
+    #[default]
+    Default,
+    /// 按用户手动排列的 `sort_order` 升序，未排列过的排在最后（按 ID）
+    Manual,
+}
+
+/// 可清空字段的更新方式，用于 [`super::Storage::update_memo`]
+///
+/// 反序列化时：字段缺失为 `Unchanged`（需配合 `#[serde(default)]`），显式的 `null` 为 `Clear`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldUpdate<T> {
+    /// 保持原值
+    #[default]
+    Unchanged,
+    /// 设置为新值
+    Set(T),
+    /// 清空（设为 NULL）
+    Clear,
+}
+
+impl<T> FieldUpdate<T> {
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Self::Unchanged)
+    }
+
+    pub fn as_ref(&self) -> FieldUpdate<&T> {
+        match self {
+            Self::Unchanged => FieldUpdate::Unchanged,
+            Self::Set(v) => FieldUpdate::Set(v),
+            Self::Clear => FieldUpdate::Clear,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> FieldUpdate<U> {
+        match self {
+            Self::Unchanged => FieldUpdate::Unchanged,
+            Self::Set(v) => FieldUpdate::Set(f(v)),
+            Self::Clear => FieldUpdate::Clear,
+        }
+    }
+}
+
+/// `None` 表示保持原值，与旧的 `Option` 参数语义一致
+impl<T> From<Option<T>> for FieldUpdate<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Unchanged, Self::Set)
+    }
+}
+
+impl<T: Serialize> Serialize for FieldUpdate<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Set(v) => v.serialize(serializer),
+            Self::Unchanged | Self::Clear => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for FieldUpdate<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Option::<T>::deserialize(deserializer)?.map_or(Self::Clear, Self::Set))
+    }
+}
+
+/// 启动时需要重新注册调度任务的备忘录（见 [`super::Storage::active_reminders_page`]）
+#[derive(Debug)]
+pub struct ActiveReminder {
+    pub id: i64,
+    pub content: String,
+    pub remind_at: Option<i64>,
+    pub cron_pattern: Option<String>,
+    pub metadata: Option<String>,
+    pub tags: Option<String>,
+    pub notify_channel: Option<String>,
+}
+
+impl From<SqliteRow> for ActiveReminder {
+    fn from(row: SqliteRow) -> Self {
+        Self {
+            id: row.get("id"),
+            content: row.get("content"),
+            remind_at: row.get("remind_at"),
+            cron_pattern: row.get("cron_pattern"),
+            metadata: row.get("metadata"),
+            tags: row.get("tags"),
+            notify_channel: row.get("notify_channel"),
+        }
+    }
+}
+
+/// 规范化标签：去除首尾空白、转为小写、去掉空标签，并按首次出现的顺序去重
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct MemoRecord {
     pub id: i64,
     pub content: String,
@@ -27,12 +149,22 @@ pub struct MemoRecord {
     pub tags: Vec<String>,
     pub todo_date: Option<i64>,
     pub priority: i32,
+    /// 优先级名称（如 `"high"`），便于客户端展示
+    pub priority_label: &'static str,
     pub user_id: Option<String>,
+    pub parent_id: Option<i64>,
+    /// 提醒投递的适配器，未设置时使用所有者的默认渠道
+    pub notify_channel: Option<String>,
+    /// 手动排序位置（见 `system.memo.reorder`），未排列过时为空
+    pub sort_order: Option<f64>,
+    /// 未删除的直接子项数量
+    pub children: i64,
 }
 
 impl From<SqliteRow> for MemoRecord {
     fn from(row: SqliteRow) -> Self {
         let tags_str: Option<String> = row.get("tags");
+        let priority: i32 = row.get("priority");
         let tags = tags_str
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default();
@@ -46,9 +178,70 @@ impl From<SqliteRow> for MemoRecord {
             status: row.get("status"),
             tags,
             todo_date: row.get("todo_date"),
-            priority: row.get("priority"),
+            priority,
+            priority_label: MessagePriority::from_level(priority.into()).unwrap_or_default().label(),
             user_id: row.get("user_id"),
+            parent_id: row.get("parent_id"),
+            notify_channel: row.try_get("notify_channel").unwrap_or(None),
+            sort_order: row.try_get("sort_order").unwrap_or(None),
+            children: row.try_get("children").unwrap_or(0),
         }
     }
 }
 
+/// 键集分页的位置：上一页最后一条记录的 `(todo_date, id)`
+///
+/// 对外编码为不透明的 base64 字符串
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoCursor {
+    pub todo_date: Option<i64>,
+    pub id: i64,
+}
+
+impl MemoCursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// 解析游标，空字符串返回 `None`（第一页）
+    pub fn decode(cursor: &str) -> Result<Option<Self>> {
+        if cursor.is_empty() {
+            return Ok(None);
+        }
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).context("invalid cursor")?;
+        Ok(Some(serde_json::from_slice(&bytes).context("invalid cursor")?))
+    }
+}
+
+impl From<&MemoRecord> for MemoCursor {
+    fn from(memo: &MemoRecord) -> Self {
+        Self { todo_date: memo.todo_date, id: memo.id }
+    }
+}
+
+/// 一页键集分页结果
+#[derive(Debug, Serialize)]
+pub struct MemoPage {
+    pub memos: Vec<MemoRecord>,
+    /// 还有更多数据时为下一页的游标
+    pub next_cursor: Option<String>,
+}
+
+/// 提醒触发记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderLogEntry {
+    pub memo_id: i64,
+    /// 触发的调度任务 UUID（启动时补发的迟到提醒为空）
+    pub job_uuid: Option<String>,
+    pub fired_at: i64,
+}
+
+impl From<SqliteRow> for ReminderLogEntry {
+    fn from(row: SqliteRow) -> Self {
+        Self {
+            memo_id: row.get("memo_id"),
+            job_uuid: row.get("job_uuid"),
+            fired_at: row.get("fired_at"),
+        }
+    }
+}
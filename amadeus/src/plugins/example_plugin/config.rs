@@ -1,7 +1,9 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 /// 插件配置结构
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PluginConfig {
     pub max_workers: usize,
     pub timeout_seconds: u64,
@@ -30,6 +32,20 @@ impl PluginConfig {
         Ok(())
     }
 
+    /// 从 JSON 文件加载配置（文件不存在时保持当前配置）
+    pub fn load_from(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            println!("[Config] 配置文件 {} 不存在，保持当前配置", path.display());
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let config: PluginConfig = serde_json::from_str(&content)?;
+        config.validate()?;
+        *self = config;
+        println!("[Config] 已从 {} 加载配置", path.display());
+        Ok(())
+    }
+
     /// 验证配置
     pub fn validate(&self) -> Result<()> {
         if self.max_workers == 0 {
@@ -26,8 +26,8 @@ impl DataHandler {
         Ok(())
     }
 
-    /// 处理数据
-    pub fn process(&mut self, data: &[&str]) -> Result<()> {
+    /// 处理数据，返回本次处理的条数
+    pub fn process(&mut self, data: &[&str]) -> Result<usize> {
         println!("[Handler] 处理 {} 条数据", data.len());
         
         for (idx, item) in data.iter().enumerate() {
@@ -36,7 +36,12 @@ impl DataHandler {
         }
 
         println!("[Handler] 累计处理 {} 条数据", self.processed_count);
-        Ok(())
+        Ok(data.len())
+    }
+
+    /// 累计处理的条数
+    pub fn processed_count(&self) -> usize {
+        self.processed_count
     }
 
     /// 清理资源
@@ -2,16 +2,28 @@
 mod config;
 mod handler;
 
+use crate::core::messaging::distribution_center::DistributionCenter;
+use crate::core::messaging::message::Message;
+use crate::core::messaging::message_context::MessageContext;
 use crate::plugin::{Plugin, PluginMetadata};
 use anyhow::Result;
 pub use config::PluginConfig;
 pub use handler::DataHandler;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::mpsc;
 
 /// 示例插件 - 展示如何构建复杂的多文件插件
+///
+/// 消息接口：
+/// - `example.process` `{items: [..]}` -> `example.processed` `{processed, total}`
+/// - `system.config.reload` `{plugin?, config?}` -> 重新加载配置，成功后发送 `example.config.reloaded`
 pub struct ExamplePlugin {
     metadata: PluginMetadata,
-    config: PluginConfig,
-    handler: DataHandler,
+    config: Arc<RwLock<PluginConfig>>,
+    config_path: PathBuf,
+    handler: Arc<Mutex<DataHandler>>,
     is_initialized: bool,
 }
 
@@ -29,11 +41,29 @@ impl ExamplePlugin {
 
         Self {
             metadata,
-            config: PluginConfig::default(),
-            handler: DataHandler::new(),
+            config: Arc::new(RwLock::new(PluginConfig::default())),
+            config_path: PathBuf::from("example_plugin_config.json"),
+            handler: Arc::new(Mutex::new(DataHandler::new())),
             is_initialized: false,
         }
     }
+
+    /// 设置配置文件路径（用于 init 与热重载）
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = path.into();
+        self
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> PluginConfig {
+        self.config.read().unwrap().clone()
+    }
+}
+
+impl Default for ExamplePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Plugin for ExamplePlugin {
@@ -47,46 +77,142 @@ impl Plugin for ExamplePlugin {
 
     fn init(&mut self) -> Result<()> {
         println!("[ExamplePlugin] 初始化中...");
-        
+
         // 加载配置
-        self.config.load()?;
-        println!("[ExamplePlugin] 配置已加载: {:?}", self.config);
-        
+        {
+            let mut config = self.config.write().unwrap();
+            config.load()?;
+            config.load_from(&self.config_path)?;
+            println!("[ExamplePlugin] 配置已加载: {:?}", config);
+        }
+
         // 初始化处理器
-        self.handler.init()?;
-        
+        self.handler.lock().unwrap().init()?;
+
         self.is_initialized = true;
         println!("[ExamplePlugin] 初始化完成!");
         Ok(())
     }
 
+    fn setup_messaging(
+        &mut self,
+        distribution_center: &DistributionCenter,
+        message_tx: mpsc::Sender<Message>,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<Option<Arc<MessageContext>>>> + Send>> {
+        let plugin_name = self.metadata.name.clone();
+        let plugin_uid = self.metadata.uid.clone();
+        let dc = Arc::new(distribution_center.clone());
+        let config = self.config.clone();
+        let config_path = self.config_path.clone();
+        let handler = self.handler.clone();
+
+        Box::pin(async move {
+            let ctx = Arc::new(MessageContext::new(dc, plugin_name.clone(), plugin_uid, message_tx));
+
+            let mut rx_process = ctx.subscribe("example.process").await?;
+            let mut rx_reload = ctx.subscribe("system.config.reload").await?;
+
+            let ctx_clone = ctx.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        Ok(msg) = rx_process.recv() => {
+                            handle_process(&msg, &ctx_clone, &handler, &config).await;
+                        }
+                        Ok(msg) = rx_reload.recv() => {
+                            handle_reload(&msg, &ctx_clone, &plugin_name, &config, &config_path).await;
+                        }
+                        else => break,
+                    }
+                }
+            });
+
+            Ok(Some(ctx))
+        })
+    }
+
     fn start(&mut self) -> Result<()> {
         if !self.is_initialized {
             anyhow::bail!("插件未初始化");
         }
 
         println!("[ExamplePlugin] 启动中...");
-        self.handler.start()?;
-        
-        // 模拟运行主要逻辑
-        println!("[ExamplePlugin] 运行主要逻辑...");
-        // 使用处理器处理数据
-        let data = vec!["数据1", "数据2", "数据3"];
-        // Clone handler if needed or ensure thread safety, but here it's simple
-        // For sync logic we can keep it here, or spawn if slow.
-        self.handler.process(&data)?;
-        println!("[ExamplePlugin] 任务完成!");
-
-        println!("[ExamplePlugin] 已启动!");
+        self.handler.lock().unwrap().start()?;
+        println!("[ExamplePlugin] 已启动! 等待 example.process 消息");
         Ok(())
     }
 
     fn stop(&mut self) -> Result<()> {
         println!("[ExamplePlugin] 停止中...");
-        self.handler.cleanup()?;
+        self.handler.lock().unwrap().cleanup()?;
         self.is_initialized = false;
         println!("[ExamplePlugin] 已停止!");
         Ok(())
     }
 }
 
+/// 在阻塞线程池中运行 `DataHandler::process`，超时时间取自配置
+async fn handle_process(
+    msg: &Message,
+    ctx: &MessageContext,
+    handler: &Arc<Mutex<DataHandler>>,
+    config: &Arc<RwLock<PluginConfig>>,
+) {
+    let items: Vec<String> = msg
+        .payload
+        .get("items")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let timeout = std::time::Duration::from_secs(config.read().unwrap().timeout_seconds);
+
+    let handler = handler.clone();
+    let task = tokio::task::spawn_blocking(move || {
+        let data: Vec<&str> = items.iter().map(String::as_str).collect();
+        let mut handler = handler.lock().unwrap();
+        handler.process(&data).map(|processed| (processed, handler.processed_count()))
+    });
+
+    let payload = match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(Ok((processed, total)))) => serde_json::json!({ "processed": processed, "total": total }),
+        Ok(Ok(Err(e))) => serde_json::json!({ "error": e.to_string() }),
+        Ok(Err(e)) => serde_json::json!({ "error": format!("处理任务失败: {}", e) }),
+        Err(_) => serde_json::json!({ "error": format!("处理超时 ({}s)", timeout.as_secs()) }),
+    };
+
+    let _ = ctx.send(Message::new("example.processed", payload)).await;
+}
+
+/// 重新加载配置：优先使用消息中携带的配置，否则从配置文件读取
+async fn handle_reload(
+    msg: &Message,
+    ctx: &MessageContext,
+    plugin_name: &str,
+    config: &Arc<RwLock<PluginConfig>>,
+    config_path: &std::path::Path,
+) {
+    // 指定了其他插件的重载请求直接忽略
+    if let Some(target) = msg.payload.get("plugin").and_then(|v| v.as_str()) {
+        if target != plugin_name {
+            return;
+        }
+    }
+
+    let mut next = config.read().unwrap().clone();
+    let result = match msg.payload.get("config") {
+        Some(inline) => serde_json::from_value::<PluginConfig>(inline.clone())
+            .map_err(anyhow::Error::from)
+            .and_then(|c| c.validate().map(|_| c))
+            .map(|c| next = c),
+        None => next.load_from(config_path),
+    };
+
+    match result {
+        Ok(()) => {
+            println!("[ExamplePlugin] 配置已重新加载: {:?}", next);
+            *config.write().unwrap() = next.clone();
+            let payload = serde_json::to_value(&next).unwrap_or_default();
+            let _ = ctx.send(Message::new("example.config.reloaded", payload)).await;
+        }
+        Err(e) => tracing::error!("[ExamplePlugin] 重新加载配置失败: {}", e),
+    }
+}
@@ -0,0 +1,59 @@
+//! Topic selection for messages bridged from the internal bus to external peers.
+//!
+//! Patterns use the same segment matching as permissions, split on `.`:
+//! `system.memo.*` matches every `system.memo.` topic and `*` matches everything.
+//!
+//! Every `system.*` topic stays internal by default (backups, preferences, user management,
+//! memo contents, rejection notices). An allowlist entry opts matching topics back in,
+//! e.g. `system.memo.created`; an entry added with [`BridgeFilter::with_denylist`] always wins.
+//! An empty allowlist admits every topic that is not denied.
+
+use crate::core::user::wildcard_matches;
+
+/// Topics that never leave the process unless explicitly allowlisted
+pub const DEFAULT_BRIDGE_DENYLIST: &[&str] = &["system.*"];
+
+#[derive(Debug, Clone)]
+pub struct BridgeFilter {
+    allow: Vec<String>,
+    /// Operator-supplied patterns; not overridable by the allowlist
+    deny: Vec<String>,
+    /// [`DEFAULT_BRIDGE_DENYLIST`], lifted for topics the allowlist names
+    default_deny: Vec<String>,
+}
+
+impl Default for BridgeFilter {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            default_deny: DEFAULT_BRIDGE_DENYLIST.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+impl BridgeFilter {
+    /// Only bridge topics matching one of `patterns`; these also override [`DEFAULT_BRIDGE_DENYLIST`]
+    pub fn with_allowlist(mut self, patterns: &[&str]) -> Self {
+        self.allow = patterns.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Never bridge topics matching `patterns`, even if they are allowlisted
+    pub fn with_denylist(mut self, patterns: &[&str]) -> Self {
+        self.deny.extend(patterns.iter().map(|p| p.to_string()));
+        self
+    }
+
+    /// Whether a message of type `topic` may be forwarded to the publisher thread
+    pub fn allows(&self, topic: &str) -> bool {
+        let matches = |patterns: &[String]| patterns.iter().any(|p| wildcard_matches(p, topic, '.'));
+        if matches(&self.deny) {
+            return false;
+        }
+        if matches(&self.allow) {
+            return true;
+        }
+        self.allow.is_empty() && !matches(&self.default_deny)
+    }
+}
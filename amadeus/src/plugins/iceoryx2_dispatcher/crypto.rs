@@ -0,0 +1,154 @@
+//! Hybrid encryption for messages bridged to external peers.
+//!
+//! The payload is encrypted with a random AES-GCM session key, which in turn is
+//! encrypted with the peer's RSA public key. The envelope carries an `alg` field
+//! so the receiver knows which key size and padding to use when decrypting.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    Aes128Gcm, Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use rand::RngCore;
+use rsa::{Oaep, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+
+/// AES-GCM session key size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AesKeySize {
+    Aes128,
+    #[default]
+    Aes256,
+}
+
+/// Padding used to encrypt the session key with RSA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RsaPadding {
+    #[default]
+    Pkcs1v15,
+    /// OAEP with SHA-256
+    Oaep,
+}
+
+/// Crypto settings for outgoing messages. The default (AES-256-GCM + PKCS#1 v1.5)
+/// matches envelopes produced before the `alg` field existed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CryptoConfig {
+    pub aes_bits: AesKeySize,
+    pub rsa_padding: RsaPadding,
+}
+
+impl CryptoConfig {
+    /// Algorithm identifier written to the envelope's `alg` field, e.g. `RSA-OAEP+A128GCM`
+    pub fn alg(&self) -> &'static str {
+        match (self.rsa_padding, self.aes_bits) {
+            (RsaPadding::Pkcs1v15, AesKeySize::Aes128) => "RSA1_5+A128GCM",
+            (RsaPadding::Pkcs1v15, AesKeySize::Aes256) => "RSA1_5+A256GCM",
+            (RsaPadding::Oaep, AesKeySize::Aes128) => "RSA-OAEP+A128GCM",
+            (RsaPadding::Oaep, AesKeySize::Aes256) => "RSA-OAEP+A256GCM",
+        }
+    }
+
+    /// Parse an `alg` identifier produced by [`CryptoConfig::alg`]
+    pub fn from_alg(alg: &str) -> Result<Self> {
+        let (padding, aes) = alg
+            .split_once('+')
+            .ok_or_else(|| anyhow!("Malformed alg: {}", alg))?;
+        let rsa_padding = match padding {
+            "RSA1_5" => RsaPadding::Pkcs1v15,
+            "RSA-OAEP" => RsaPadding::Oaep,
+            other => return Err(anyhow!("Unsupported RSA padding: {}", other)),
+        };
+        let aes_bits = match aes {
+            "A128GCM" => AesKeySize::Aes128,
+            "A256GCM" => AesKeySize::Aes256,
+            other => return Err(anyhow!("Unsupported AES mode: {}", other)),
+        };
+        Ok(Self { aes_bits, rsa_padding })
+    }
+}
+
+/// Encrypt `plaintext` for the holder of `public_key`, returning the JSON envelope
+/// `{alg, secure_key, iv, secure_payload}`.
+pub fn encrypt_envelope(
+    public_key: &RsaPublicKey,
+    config: &CryptoConfig,
+    plaintext: &[u8],
+) -> Result<serde_json::Value> {
+    // 96-bit nonce, unique per message
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let (key, encrypted_payload) = match config.aes_bits {
+        AesKeySize::Aes128 => {
+            let key = Aes128Gcm::generate_key(&mut OsRng);
+            let payload = Aes128Gcm::new(&key)
+                .encrypt(nonce, plaintext)
+                .map_err(|e| anyhow!("AES encryption failed: {}", e))?;
+            (key.to_vec(), payload)
+        }
+        AesKeySize::Aes256 => {
+            let key = Aes256Gcm::generate_key(&mut OsRng);
+            let payload = Aes256Gcm::new(&key)
+                .encrypt(nonce, plaintext)
+                .map_err(|e| anyhow!("AES encryption failed: {}", e))?;
+            (key.to_vec(), payload)
+        }
+    };
+
+    let mut rng = rand::thread_rng();
+    let encrypted_key = match config.rsa_padding {
+        RsaPadding::Pkcs1v15 => public_key.encrypt(&mut rng, Pkcs1v15Encrypt, &key),
+        RsaPadding::Oaep => public_key.encrypt(&mut rng, Oaep::new::<sha2::Sha256>(), &key),
+    }
+    .map_err(|e| anyhow!("RSA encryption of session key failed: {}", e))?;
+
+    Ok(serde_json::json!({
+        "alg": config.alg(),
+        "secure_key": BASE64.encode(encrypted_key),
+        "iv": BASE64.encode(nonce_bytes),
+        "secure_payload": BASE64.encode(encrypted_payload)
+    }))
+}
+
+/// Decrypt an envelope produced by [`encrypt_envelope`]. Envelopes without an
+/// `alg` field are treated as the default AES-256-GCM + PKCS#1 v1.5.
+pub fn decrypt_envelope(private_key: &RsaPrivateKey, envelope: &serde_json::Value) -> Result<Vec<u8>> {
+    let config = match envelope.get("alg").and_then(|a| a.as_str()) {
+        Some(alg) => CryptoConfig::from_alg(alg)?,
+        None => CryptoConfig::default(),
+    };
+    let field = |name: &str| -> Result<Vec<u8>> {
+        let value = envelope
+            .get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Envelope is missing `{}`", name))?;
+        Ok(BASE64.decode(value)?)
+    };
+    let encrypted_key = field("secure_key")?;
+    let nonce_bytes = field("iv")?;
+    let encrypted_payload = field("secure_payload")?;
+    if nonce_bytes.len() != 12 {
+        return Err(anyhow!("Invalid IV length: {}", nonce_bytes.len()));
+    }
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let key = match config.rsa_padding {
+        RsaPadding::Pkcs1v15 => private_key.decrypt(Pkcs1v15Encrypt, &encrypted_key),
+        RsaPadding::Oaep => private_key.decrypt(Oaep::new::<sha2::Sha256>(), &encrypted_key),
+    }
+    .map_err(|e| anyhow!("RSA decryption of session key failed: {}", e))?;
+
+    let plaintext = match config.aes_bits {
+        AesKeySize::Aes128 => Aes128Gcm::new_from_slice(&key)
+            .map_err(|_| anyhow!("Invalid AES-128 key length: {}", key.len()))?
+            .decrypt(nonce, encrypted_payload.as_slice()),
+        AesKeySize::Aes256 => Aes256Gcm::new_from_slice(&key)
+            .map_err(|_| anyhow!("Invalid AES-256 key length: {}", key.len()))?
+            .decrypt(nonce, encrypted_payload.as_slice()),
+    }
+    .map_err(|e| anyhow!("AES decryption failed: {}", e))?;
+
+    Ok(plaintext)
+}
@@ -0,0 +1,63 @@
+//! Inbound flood protection for messages arriving from external peers.
+//!
+//! A token bucket refilled at `rate` tokens per second, holding at most one
+//! second worth of tokens. Each inbound message takes a token; messages that
+//! find the bucket empty are dropped and counted instead of being pushed onto
+//! the internal bus.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct InboundRateLimiter {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+    dropped: Arc<AtomicU64>,
+}
+
+impl InboundRateLimiter {
+    /// Allow at most `per_second` messages per second, with bursts up to the same size.
+    /// A rate of zero is treated as one message per second.
+    pub fn new(per_second: u32) -> Self {
+        let rate = f64::from(per_second.max(1));
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Take a token for a message arriving now. Returns `false` if the message should be dropped.
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    /// Same as [`try_acquire`](Self::try_acquire) with an explicit clock, so callers
+    /// (and tests) can drive the bucket deterministically.
+    pub fn try_acquire_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = self.last_refill.max(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+
+    /// Number of messages dropped so far
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Shared handle to the drop counter, readable after the limiter moves to another thread
+    pub fn dropped_counter(&self) -> Arc<AtomicU64> {
+        self.dropped.clone()
+    }
+}
@@ -55,11 +55,11 @@ impl Plugin for MessageExamplePlugin {
             ));
             
             // 订阅所有消息（通配符）
-            let mut rx = ctx.subscribe("test.message").await;
+            let mut rx = ctx.subscribe("test.message").await?;
             
             tokio::spawn(async move {
                 while let Ok(msg) = rx.recv().await {
-                    tracing::info!("[MessageExample] 收到消息: {:?}", msg);
+                    tracing::info!("[MessageExample] 收到消息: {}", msg.redacted());
                 }
             });
 
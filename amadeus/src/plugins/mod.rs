@@ -3,28 +3,118 @@ pub mod example_plugin;
 pub mod core_system;
 pub mod message_example;
 pub mod iceoryx2_dispatcher;
+#[cfg(feature = "wasm")]
+pub mod wasm_plugin;
 
 use crate::plugin::Plugin;
 use code4rena::Code4renaPlugin;
 use example_plugin::ExamplePlugin;
 use core_system::CoreSystemPlugin;
 use message_example::MessageExamplePlugin;
+#[cfg(feature = "iceoryx2")]
 use iceoryx2_dispatcher::Iceoryx2DispatcherPlugin;
+#[cfg(feature = "wasm")]
+use wasm_plugin::WASM_PLUGIN_DIR;
 
-/// 获取所有可用的插件实例
+/// 未启用 `wasm` feature 时不扫描 WASM 插件目录，默认值与启用时保持一致
+#[cfg(not(feature = "wasm"))]
+const WASM_PLUGIN_DIR: &str = "plugins";
+
+/// 构建插件时注入的应用配置
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    /// CoreSystem 插件使用的数据库
+    pub db_url: String,
+    /// IPC 分发器的节点名
+    pub node_name: String,
+    /// WASM 插件目录（启用 `wasm` feature 时扫描）
+    pub wasm_plugin_dir: String,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            db_url: "sqlite:amadeus.db".to_string(),
+            node_name: "amadeus_node".to_string(),
+            wasm_plugin_dir: WASM_PLUGIN_DIR.to_string(),
+        }
+    }
+}
+
+/// 插件工厂：根据应用配置创建插件实例
+pub type PluginFactory = Box<dyn Fn(&AppConfig) -> Box<dyn Plugin> + Send + Sync>;
+
+/// 插件工厂注册表：(名称, 工厂)，按注册顺序构建插件
 ///
-/// 这个函数会自动创建所有插件的实例并返回
-/// 新增插件时，只需要在这里添加即可
-pub fn get_all_plugins() -> Vec<Box<dyn Plugin>> {
-    vec![
+/// 注册表由调用方持有（如传给 [`crate::App::from_factories`]），不同的应用或测试互不影响；
+/// [`Default`] 包含所有内置插件
+pub struct PluginFactories {
+    factories: Vec<(String, PluginFactory)>,
+}
+
+impl PluginFactories {
+    /// 创建空的注册表
+    pub fn empty() -> Self {
+        Self { factories: Vec::new() }
+    }
+
+    /// 注册插件工厂
+    ///
+    /// 同名工厂会被替换（保留原来的构建顺序），否则追加到末尾
+    pub fn register(&mut self, name: impl Into<String>, factory: PluginFactory) -> &mut Self {
+        let name = name.into();
+        match self.factories.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => *existing = factory,
+            None => self.factories.push((name, factory)),
+        }
+        self
+    }
+
+    /// 已注册的插件工厂名称（按构建顺序）
+    pub fn names(&self) -> Vec<String> {
+        self.factories.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// 按注册顺序调用每个插件工厂，并注入 `config`
+    ///
+    /// 启用 `wasm` feature 时，还会加载 `config.wasm_plugin_dir` 目录下的 WASM 插件
+    pub fn build(&self, config: &AppConfig) -> Vec<Box<dyn Plugin>> {
+        #[allow(unused_mut)]
+        let mut plugins: Vec<Box<dyn Plugin>> = self
+            .factories
+            .iter()
+            .map(|(_, factory)| factory(config))
+            .collect();
+
+        #[cfg(feature = "wasm")]
+        match wasm_plugin::load_wasm_plugins(&config.wasm_plugin_dir) {
+            Ok(wasm_plugins) => plugins.extend(wasm_plugins),
+            Err(e) => tracing::error!("扫描 WASM 插件目录失败: {}", e),
+        }
+
+        plugins
+    }
+}
+
+impl Default for PluginFactories {
+    fn default() -> Self {
+        let mut factories = Self::empty();
         // Core System Plugin - always active
-        Box::new(CoreSystemPlugin::new("sqlite:amadeus.db")),
+        factories.register("CoreSystem", Box::new(|config: &AppConfig| Box::new(CoreSystemPlugin::new(&config.db_url)) as Box<dyn Plugin>));
         // IPC Dispatcher Plugin - privileged
-        Box::new(Iceoryx2DispatcherPlugin::new("amadeus_node")),
-        Box::new(Code4renaPlugin::new()),
-        Box::new(ExamplePlugin::new()),
-        Box::new(MessageExamplePlugin::new()),
-        // 在这里添加更多插件
-        // Box::new(YourPlugin::new()),
-    ]
+        #[cfg(feature = "iceoryx2")]
+        factories.register("Iceoryx2Dispatcher", Box::new(|config: &AppConfig| Box::new(Iceoryx2DispatcherPlugin::new(&config.node_name)) as Box<dyn Plugin>));
+        factories.register("code4rena", Box::new(|_: &AppConfig| Box::new(Code4renaPlugin::new()) as Box<dyn Plugin>));
+        factories.register("example_plugin", Box::new(|_: &AppConfig| Box::new(ExamplePlugin::new()) as Box<dyn Plugin>));
+        factories.register("message-example", Box::new(|_: &AppConfig| Box::new(MessageExamplePlugin::new()) as Box<dyn Plugin>));
+        factories
+    }
+}
+
+/// 获取所有内置插件的实例
+///
+/// 使用默认的 [`PluginFactories`] 构建，并注入 `config`；
+/// 需要额外的插件时，向自己持有的 [`PluginFactories`] 注册后调用 [`PluginFactories::build`]
+pub fn get_all_plugins(config: &AppConfig) -> Vec<Box<dyn Plugin>> {
+    PluginFactories::default().build(config)
 }
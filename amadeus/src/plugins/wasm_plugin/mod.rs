@@ -0,0 +1,37 @@
+// WASM 插件 - 通过 extism 运行时加载 .wasm 插件（需要 `wasm` feature）
+
+pub mod wasm;
+
+pub use wasm::WasmPlugin;
+
+use crate::plugin::Plugin;
+use anyhow::Result;
+use std::path::Path;
+
+/// 默认的 WASM 插件目录
+pub const WASM_PLUGIN_DIR: &str = "plugins";
+
+/// 加载目录下所有 `.wasm` 文件为插件
+///
+/// 目录不存在时返回空列表；单个插件加载失败只记录错误，不影响其他插件
+pub fn load_wasm_plugins(dir: impl AsRef<Path>) -> Result<Vec<Box<dyn Plugin>>> {
+    let dir = dir.as_ref();
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    let mut plugins: Vec<Box<dyn Plugin>> = Vec::new();
+    for path in paths {
+        match WasmPlugin::new(&path) {
+            Ok(plugin) => plugins.push(Box::new(plugin)),
+            Err(e) => tracing::error!("加载 WASM 插件 {} 失败: {}", path.display(), e),
+        }
+    }
+    Ok(plugins)
+}
@@ -0,0 +1,73 @@
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// UUID 来源
+///
+/// 默认使用随机的 UUIDv4；测试中可以替换为可预测的实现，从而对生成的 ID 做精确断言
+pub trait IdGenerator: Send + Sync + Debug {
+    fn next_uuid(&self) -> uuid::Uuid;
+}
+
+/// 共享的 ID 生成器
+pub type SharedIdGenerator = Arc<dyn IdGenerator>;
+
+/// 默认的生成器：随机 UUIDv4
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomIdGenerator;
+
+impl IdGenerator for RandomIdGenerator {
+    fn next_uuid(&self) -> uuid::Uuid {
+        uuid::Uuid::new_v4()
+    }
+}
+
+/// 依次生成 `start`、`start + 1`、... 对应的 UUID（`Uuid::from_u128`）
+#[derive(Debug)]
+pub struct SequentialIdGenerator {
+    next: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    pub fn new(start: u64) -> Self {
+        Self { next: AtomicU64::new(start) }
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn next_uuid(&self) -> uuid::Uuid {
+        uuid::Uuid::from_u128(u128::from(self.next.fetch_add(1, Ordering::Relaxed)))
+    }
+}
+
+/// 由种子决定的 UUIDv4 序列：相同种子总是生成相同的 ID 序列
+#[derive(Debug)]
+pub struct SeededIdGenerator {
+    state: Mutex<u64>,
+}
+
+impl SeededIdGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self { state: Mutex::new(seed) }
+    }
+}
+
+impl IdGenerator for SeededIdGenerator {
+    fn next_uuid(&self) -> uuid::Uuid {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut bytes = [0u8; 16];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&splitmix64(&mut state).to_le_bytes());
+        }
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+/// SplitMix64：推进状态并返回下一个伪随机数
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
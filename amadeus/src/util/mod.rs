@@ -0,0 +1,7 @@
+// 通用工具
+
+pub mod ttl_lru_cache;
+pub mod id_generator;
+
+pub use ttl_lru_cache::TtlLruCache;
+pub use id_generator::{IdGenerator, RandomIdGenerator, SeededIdGenerator, SequentialIdGenerator, SharedIdGenerator};
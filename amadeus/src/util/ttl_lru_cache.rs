@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+struct Entry<V> {
+    value: V,
+    inserted_at: Instant,
+    /// 最近一次访问的序号，对应 `order` 中的键
+    tick: u64,
+}
+
+/// 同时受容量和存活时间约束的缓存
+///
+/// - 条目在插入 `ttl` 之后过期，过期条目在访问或插入时被清理
+/// - 超出容量时淘汰最久未访问的条目（LRU）
+///
+/// 适用于长期运行进程中的去重 / 已见ID 记录，保证内存有界
+pub struct TtlLruCache<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<K, Entry<V>>,
+    /// 访问序号 -> 键，最小的序号即最久未访问的条目
+    order: BTreeMap<u64, K>,
+    next_tick: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> TtlLruCache<K, V> {
+    /// 创建缓存，`capacity` 至少为 1
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_tick: 0,
+        }
+    }
+
+    /// 插入或替换条目（重置存活时间），返回旧值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let now = Instant::now();
+        let tick = self.bump();
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.order.remove(&entry.tick);
+            self.order.insert(tick, key);
+            entry.tick = tick;
+            entry.inserted_at = now;
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+
+        if self.entries.len() >= self.capacity {
+            self.purge_expired();
+        }
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_first() {
+                Some((_, oldest)) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+
+        self.order.insert(tick, key.clone());
+        self.entries.insert(key, Entry { value, inserted_at: now, tick });
+        None
+    }
+
+    /// 获取条目并标记为最近访问，过期条目视为不存在
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|v| &*v)
+    }
+
+    /// 获取可变条目并标记为最近访问，过期条目视为不存在
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.is_expired(key) {
+            self.remove(key);
+            return None;
+        }
+        let tick = self.bump();
+        let entry = self.entries.get_mut(key)?;
+        self.order.remove(&entry.tick);
+        self.order.insert(tick, key.clone());
+        entry.tick = tick;
+        Some(&mut entry.value)
+    }
+
+    /// 判断是否存在未过期的条目（同样标记为最近访问）
+    pub fn contains(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 移除条目
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        Some(entry.value)
+    }
+
+    /// 只保留满足 `keep` 的条目，返回移除数量
+    pub fn retain<F>(&mut self, mut keep: F) -> usize
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let removed: Vec<(K, u64)> = self
+            .entries
+            .iter()
+            .filter(|(k, e)| !keep(k, &e.value))
+            .map(|(k, e)| (k.clone(), e.tick))
+            .collect();
+        for (key, tick) in &removed {
+            self.entries.remove(key);
+            self.order.remove(tick);
+        }
+        removed.len()
+    }
+
+    /// 清理所有过期条目，返回清理数量
+    pub fn purge_expired(&mut self) -> usize {
+        let ttl = self.ttl;
+        let expired: Vec<(K, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.inserted_at.elapsed() >= ttl)
+            .map(|(k, e)| (k.clone(), e.tick))
+            .collect();
+        for (key, tick) in &expired {
+            self.entries.remove(key);
+            self.order.remove(tick);
+        }
+        expired.len()
+    }
+
+    /// 当前条目数（可能包含尚未清理的过期条目）
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn is_expired(&self, key: &K) -> bool {
+        self.entries
+            .get(key)
+            .is_some_and(|e| e.inserted_at.elapsed() >= self.ttl)
+    }
+
+    fn bump(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+}
@@ -0,0 +1,162 @@
+use amadeus::core::messaging::{DistributionCenter, Message, MessageContext};
+use amadeus::plugin::{Plugin, PluginMetadata};
+use amadeus::App;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// Records every lifecycle call it receives
+struct LifecycleRecorder {
+    metadata: PluginMetadata,
+    events: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Plugin for LifecycleRecorder {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push("init");
+        Ok(())
+    }
+
+    fn setup_messaging(
+        &mut self,
+        _distribution_center: &DistributionCenter,
+        _message_tx: mpsc::Sender<Message>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Option<Arc<MessageContext>>>> + Send>> {
+        self.events.lock().unwrap().push("setup_messaging");
+        Box::pin(async { Ok(None) })
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push("start");
+        Ok(())
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push("stop");
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_run_until_orders_startup_and_shutdown() -> anyhow::Result<()> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let plugin = LifecycleRecorder {
+        metadata: PluginMetadata::new("Recorder", "Records lifecycle calls", "0.1.0"),
+        events: events.clone(),
+    };
+
+    let shutdown_events = events.clone();
+    App::with_plugins(vec![Box::new(plugin)])
+        .with_messaging()
+        .show_startup_message(false)
+        .run_until(async move {
+            // Shutdown is requested as soon as the app is running
+            shutdown_events.lock().unwrap().push("shutdown_signal");
+        })
+        .await?;
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["init", "setup_messaging", "start", "shutdown_signal", "stop"]
+    );
+    Ok(())
+}
+
+/// Emits a farewell message from `stop()`
+struct FarewellPlugin {
+    metadata: PluginMetadata,
+    ctx: Option<Arc<MessageContext>>,
+}
+
+impl Plugin for FarewellPlugin {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn setup_messaging(
+        &mut self,
+        distribution_center: &DistributionCenter,
+        message_tx: mpsc::Sender<Message>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Option<Arc<MessageContext>>>> + Send>> {
+        let ctx = Arc::new(MessageContext::new(
+            Arc::new(distribution_center.clone()),
+            self.metadata.name.clone(),
+            self.metadata.uid.clone(),
+            message_tx,
+        ));
+        self.ctx = Some(ctx.clone());
+        Box::pin(async move { Ok(Some(ctx)) })
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        if let Some(ctx) = &self.ctx {
+            ctx.try_send(Message::new("test.farewell", serde_json::json!({ "from": "farewell" })))?;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_messages_sent_during_stop_are_delivered() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let message_manager = MessageManager::new();
+    let dc = Arc::clone(message_manager.distribution_center());
+    let mut rx = dc.subscribe("test.farewell", "observer").await?;
+
+    let plugin = FarewellPlugin {
+        metadata: PluginMetadata::new("Farewell", "Sends a message while stopping", "0.1.0"),
+        ctx: None,
+    };
+    App::with_plugins(vec![Box::new(plugin)])
+        .with_message_manager(message_manager)
+        .show_startup_message(false)
+        .run_until(async {})
+        .await?;
+
+    // The message emitted in stop() was drained before the bus was torn down
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await??;
+    assert_eq!(msg.payload["from"], "farewell");
+
+    // Afterwards the distribution center is closed
+    assert!(matches!(rx.recv().await, Err(RecvError::Closed)));
+    Ok(())
+}
+
+#[cfg(feature = "iceoryx2")]
+#[test]
+fn test_enable_ipc_bridge_registers_configured_dispatcher() {
+    let mut app = App::with_plugins(vec![])
+        .with_ipc_service("amadeus/test_bridge")
+        .enable_ipc_bridge("test_node")
+        .with_ipc_public_key("-----BEGIN PUBLIC KEY-----\ntest\n-----END PUBLIC KEY-----");
+
+    let dispatchers: Vec<&PluginMetadata> = app
+        .registry_mut()
+        .plugins()
+        .iter()
+        .map(|p| p.metadata())
+        .filter(|m| m.name == "Iceoryx2Dispatcher")
+        .collect();
+
+    // Reconfiguring after enabling replaces the plugin instead of adding a second one
+    assert_eq!(dispatchers.len(), 1);
+    let props = &dispatchers[0].properties;
+    assert_eq!(props.get("node_name").map(String::as_str), Some("test_node"));
+    assert_eq!(props.get("service_name").map(String::as_str), Some("amadeus/test_bridge"));
+    assert!(props.contains_key("external_public_key"));
+}
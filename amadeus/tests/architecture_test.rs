@@ -21,17 +21,17 @@ async fn test_core_system_architecture() -> anyhow::Result<()> {
     
     // 2. 创建 Registry 并加载插件
     let mut registry = PluginRegistry::with_enabled_plugins(
-        amadeus::plugins::get_all_plugins()
+        amadeus::plugins::get_all_plugins(&amadeus::plugins::AppConfig::default())
     );
     
-    // 3. Setup Messaging (这一步会初始化 CoreSystemPlugin 的 Storage 和 Scheduler)
+    // 3. 按 App 的顺序先初始化，再 Setup Messaging (这一步会初始化 CoreSystemPlugin 的 Storage 和 Scheduler)
+    registry.init_all()?;
     registry.setup_messaging(&message_manager).await?;
     
     // 4. 启动消息循环
     message_manager.start_message_loop();
     
-    // 5. 模拟 App 启动流程
-    registry.init_all()?;
+    // 5. 启动插件
     registry.start_all()?;
     
     // 6. 发送测试消息：创建 Memo
@@ -0,0 +1,45 @@
+use amadeus::plugins::iceoryx2_dispatcher::crypto::{
+    decrypt_envelope, encrypt_envelope, AesKeySize, CryptoConfig, RsaPadding,
+};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+
+fn keypair() -> (RsaPrivateKey, RsaPublicKey) {
+    let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 1024).expect("key generation");
+    let public_key = RsaPublicKey::from(&private_key);
+    (private_key, public_key)
+}
+
+#[test]
+fn test_envelope_round_trip_for_every_combination() -> anyhow::Result<()> {
+    let (private_key, public_key) = keypair();
+    let plaintext = br#"{"message_type":"system.memo.create","payload":{"content":"hi"}}"#;
+
+    for aes_bits in [AesKeySize::Aes128, AesKeySize::Aes256] {
+        for rsa_padding in [RsaPadding::Pkcs1v15, RsaPadding::Oaep] {
+            let config = CryptoConfig { aes_bits, rsa_padding };
+            let envelope = encrypt_envelope(&public_key, &config, plaintext)?;
+
+            assert_eq!(envelope["alg"], config.alg());
+            assert_eq!(CryptoConfig::from_alg(config.alg())?, config);
+            assert_eq!(decrypt_envelope(&private_key, &envelope)?, plaintext);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_default_crypto_matches_legacy_envelope() -> anyhow::Result<()> {
+    let (private_key, public_key) = keypair();
+    let config = CryptoConfig::default();
+    assert_eq!(config.alg(), "RSA1_5+A256GCM");
+
+    // Envelopes from peers that predate `alg` decrypt with the default settings
+    let mut envelope = encrypt_envelope(&public_key, &config, b"legacy")?;
+    envelope.as_object_mut().unwrap().remove("alg");
+    assert_eq!(decrypt_envelope(&private_key, &envelope)?, b"legacy");
+
+    // A mismatched alg is rejected rather than silently producing garbage
+    envelope["alg"] = serde_json::json!("RSA-OAEP+A256GCM");
+    assert!(decrypt_envelope(&private_key, &envelope).is_err());
+    Ok(())
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "iceoryx2")]
+
+use amadeus::core::messaging::message_manager::MessageManager;
+use amadeus::plugin::PluginRegistry;
+use amadeus::plugins::iceoryx2_dispatcher::{Iceoryx2DispatcherPlugin, BRIDGE_CONNECTED};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_bridge_emits_connected_event_when_publisher_attaches() -> anyhow::Result<()> {
+    let service = format!("amadeus/test_events_{}", uuid::Uuid::new_v4().simple());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(Iceoryx2DispatcherPlugin::with_service("events_node", &service));
+
+    let mut message_manager = MessageManager::new();
+    let mut rx = message_manager.distribution_center().subscribe(BRIDGE_CONNECTED, "monitor").await?;
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    // Both threads attach; wait for the publisher's event
+    let publisher = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = rx.recv().await?;
+            if event.payload["role"] == "publisher" {
+                return anyhow::Ok(event);
+            }
+        }
+    })
+    .await??;
+    assert_eq!(publisher.payload["service"], service.as_str());
+    assert_eq!(publisher.payload["node"], "events_node");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_denied_system_topic_does_not_reach_publisher_thread() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message::Message;
+
+    let service = format!("amadeus/test_filter_{}", uuid::Uuid::new_v4().simple());
+    let plugin = Iceoryx2DispatcherPlugin::with_service("filter_node", &service)
+        .with_bridge_allowlist(&["system.memo.*"]);
+    let filtered = plugin.bridge_filtered_counter();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(plugin);
+
+    let mut message_manager = MessageManager::new();
+    let mut rx = message_manager.distribution_center().subscribe(BRIDGE_CONNECTED, "monitor").await?;
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+    // Wait for both threads so their own (denied) connected events are already counted
+    for _ in 0..2 {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv()).await??;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let baseline = filtered.load(std::sync::atomic::Ordering::Relaxed);
+
+    let sender = message_manager.message_tx();
+    sender.send(Message::new("system.memo.created", serde_json::json!({"id": 1}))).await?;
+    sender.send(Message::new("system.user.grant_role", serde_json::json!({"role": "admin"}))).await?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Only the grant_role message was held back; the memo event went on to the publisher
+    assert_eq!(filtered.load(std::sync::atomic::Ordering::Relaxed) - baseline, 1);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
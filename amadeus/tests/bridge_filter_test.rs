@@ -0,0 +1,44 @@
+use amadeus::plugins::iceoryx2_dispatcher::bridge_filter::BridgeFilter;
+
+#[test]
+fn test_system_topics_stay_internal_by_default() {
+    let filter = BridgeFilter::default();
+
+    assert!(filter.allows("plugin.weather.update"));
+    for topic in [
+        "system.backup.created",
+        "system.user.prefs.set",
+        "system.memo.created",
+        "system.message.rejected",
+        "system.bridge.connected",
+    ] {
+        assert!(!filter.allows(topic), "{} should not be bridged by default", topic);
+    }
+}
+
+#[test]
+fn test_allowlist_opts_system_topics_back_in() {
+    let filter = BridgeFilter::default().with_allowlist(&["system.memo.created", "plugin.weather.*"]);
+
+    assert!(filter.allows("system.memo.created"));
+    assert!(filter.allows("plugin.weather.update"));
+    // Only what is named comes back; the rest of system.* and unlisted topics stay internal
+    assert!(!filter.allows("system.memo.remind"));
+    assert!(!filter.allows("system.user.grant_role"));
+    assert!(!filter.allows("plugin.news.update"));
+}
+
+#[test]
+fn test_denylist_beats_allowlist() {
+    let filter = BridgeFilter::default()
+        .with_allowlist(&["system.memo.*"])
+        .with_denylist(&["system.memo.remind", "plugin.secret.*"]);
+
+    assert!(filter.allows("system.memo.created"));
+    assert!(!filter.allows("system.memo.remind"));
+
+    let filter = BridgeFilter::default().with_denylist(&["plugin.secret.*"]);
+    assert!(filter.allows("plugin.weather.update"));
+    assert!(!filter.allows("plugin.secret.key"));
+    assert!(!filter.allows("system.memo.created"));
+}
@@ -0,0 +1,35 @@
+use amadeus::plugins::iceoryx2_dispatcher::rate_limit::InboundRateLimiter;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_inbound_flood_is_throttled_to_configured_rate() {
+    let mut limiter = InboundRateLimiter::new(10);
+    let start = Instant::now();
+
+    // A burst of 100 messages in the same instant: only one second's worth gets through
+    let forwarded = (0..100).filter(|_| limiter.try_acquire_at(start)).count();
+    assert_eq!(forwarded, 10);
+    assert_eq!(limiter.dropped(), 90);
+
+    // Tokens refill at the configured rate
+    let half_second = start + Duration::from_millis(500);
+    let forwarded = (0..100).filter(|_| limiter.try_acquire_at(half_second)).count();
+    assert_eq!(forwarded, 5);
+
+    // A long pause never banks more than one second of burst
+    let later = half_second + Duration::from_secs(60);
+    let forwarded = (0..100).filter(|_| limiter.try_acquire_at(later)).count();
+    assert_eq!(forwarded, 10);
+    assert_eq!(limiter.dropped(), 90 + 95 + 90);
+}
+
+#[test]
+fn test_drop_counter_is_shared() {
+    let mut limiter = InboundRateLimiter::new(1);
+    let counter = limiter.dropped_counter();
+    let now = Instant::now();
+
+    assert!(limiter.try_acquire_at(now));
+    assert!(!limiter.try_acquire_at(now));
+    assert_eq!(counter.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
@@ -0,0 +1,106 @@
+use amadeus::core::messaging::message::Message;
+use amadeus::core::messaging::message_manager::MessageManager;
+use amadeus::plugin::PluginRegistry;
+use amadeus::plugins::code4rena::Code4renaPlugin;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_scan_request_produces_report() -> anyhow::Result<()> {
+    let mut registry = PluginRegistry::new();
+    registry.register(Code4renaPlugin::new().with_scan_delay(Duration::from_millis(10)));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let mut rx_report = dc.subscribe("security.scan.report", "verifier").await?;
+
+    message_manager
+        .message_tx()
+        .send(Message::new("security.scan.request", serde_json::json!({ "target": "contracts/Vault.sol" })))
+        .await?;
+
+    let report = tokio::time::timeout(Duration::from_secs(2), rx_report.recv()).await??;
+    assert_eq!(report.payload["target"], "contracts/Vault.sol");
+    assert_eq!(report.payload["status"], "completed");
+    let findings = report.payload["findings"].as_array().unwrap();
+    assert_eq!(report.payload["summary"]["total"], findings.len());
+    assert!(findings.iter().all(|f| f["severity"].is_string() && f["title"].is_string()));
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_can_be_cancelled() -> anyhow::Result<()> {
+    let mut registry = PluginRegistry::new();
+    registry.register(Code4renaPlugin::new().with_scan_delay(Duration::from_secs(30)));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_started = dc.subscribe("security.scan.started", "verifier").await?;
+    let mut rx_report = dc.subscribe("security.scan.report", "verifier").await?;
+
+    tx.send(Message::new(
+        "security.scan.request",
+        serde_json::json!({ "target": "contracts/Slow.sol", "scan_id": "scan-1" })
+    )).await?;
+    let started = tokio::time::timeout(Duration::from_secs(2), rx_started.recv()).await??;
+    assert_eq!(started.payload["scan_id"], "scan-1");
+
+    tx.send(Message::new("security.scan.cancel", serde_json::json!({ "scan_id": "scan-1" }))).await?;
+    let report = tokio::time::timeout(Duration::from_secs(2), rx_report.recv()).await??;
+    assert_eq!(report.payload["scan_id"], "scan-1");
+    assert_eq!(report.payload["status"], "cancelled");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_cancel_after_fast_scan_completes_reports_nothing() -> anyhow::Result<()> {
+    let mut registry = PluginRegistry::new();
+    registry.register(Code4renaPlugin::new().with_scan_delay(Duration::ZERO));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_report = dc.subscribe("security.scan.report", "verifier").await?;
+
+    // Repeat to give the spawned scan a chance to finish before its handle is registered
+    for i in 0..20 {
+        let scan_id = format!("fast-{}", i);
+        tx.send(Message::new(
+            "security.scan.request",
+            serde_json::json!({ "target": "contracts/Fast.sol", "scan_id": scan_id })
+        )).await?;
+        let report = tokio::time::timeout(Duration::from_secs(2), rx_report.recv()).await??;
+        assert_eq!(report.payload["scan_id"], scan_id.as_str());
+        assert_eq!(report.payload["status"], "completed");
+
+        // The scan is already done: cancelling it must not claim a cancellation
+        tx.send(Message::new("security.scan.cancel", serde_json::json!({ "scan_id": scan_id }))).await?;
+    }
+    let late = tokio::time::timeout(Duration::from_millis(300), rx_report.recv()).await;
+    assert!(late.is_err(), "unexpected report after completion: {:?}", late.ok().and_then(|r| r.ok()).map(|m| m.payload));
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
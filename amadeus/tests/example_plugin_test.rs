@@ -0,0 +1,47 @@
+use amadeus::core::messaging::message::Message;
+use amadeus::core::messaging::message_manager::MessageManager;
+use amadeus::plugin::PluginRegistry;
+use amadeus::plugins::example_plugin::ExamplePlugin;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_example_process_and_config_reload() -> anyhow::Result<()> {
+    let mut registry = PluginRegistry::new();
+    registry.register(ExamplePlugin::new().with_config_path(
+        std::env::temp_dir().join(format!("example_plugin_{}.json", uuid::Uuid::new_v4()))
+    ));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_processed = dc.subscribe("example.processed", "verifier").await?;
+    let mut rx_reloaded = dc.subscribe("example.config.reloaded", "verifier").await?;
+
+    tx.send(Message::new("example.process", serde_json::json!({ "items": ["a", "b", "c"] }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_processed.recv()).await??;
+    assert_eq!(reply.payload["processed"], 3);
+    assert_eq!(reply.payload["total"], 3);
+
+    tx.send(Message::new("example.process", serde_json::json!({ "items": ["d"] }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_processed.recv()).await??;
+    assert_eq!(reply.payload["total"], 4);
+
+    // Invalid configs are rejected, valid ones are applied
+    let bad = serde_json::json!({ "max_workers": 0, "timeout_seconds": 5, "enable_logging": false });
+    tx.send(Message::new("system.config.reload", serde_json::json!({ "config": bad }))).await?;
+    let good = serde_json::json!({ "max_workers": 2, "timeout_seconds": 5, "enable_logging": false });
+    tx.send(Message::new("system.config.reload", serde_json::json!({ "plugin": "example_plugin", "config": good }))).await?;
+
+    let reloaded = tokio::time::timeout(Duration::from_secs(2), rx_reloaded.recv()).await??;
+    assert_eq!(reloaded.payload["max_workers"], 2);
+    assert_eq!(reloaded.payload["enable_logging"], false);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use amadeus::plugin::{Plugin, PluginMetadata};
+use amadeus::plugins::{get_all_plugins, AppConfig, PluginFactories};
+use std::process::Command;
+
+fn plugin_names() -> Vec<String> {
+    get_all_plugins(&AppConfig::default())
+        .iter()
+        .map(|p| p.metadata().name.clone())
+        .collect()
+}
+
+#[test]
+fn test_dispatcher_plugin_follows_iceoryx2_feature() {
+    let names = plugin_names();
+    assert_eq!(
+        names.iter().any(|n| n == "Iceoryx2Dispatcher"),
+        cfg!(feature = "iceoryx2"),
+        "plugins: {:?}",
+        names
+    );
+    // 核心插件与 feature 无关
+    assert!(names.iter().any(|n| n == "CoreSystem"));
+}
+
+struct ConfiguredPlugin {
+    metadata: PluginMetadata,
+}
+
+impl Plugin for ConfiguredPlugin {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+}
+
+#[test]
+fn test_registered_factory_is_built_with_injected_config() {
+    let mut factories = PluginFactories::default();
+    factories.register(
+        "configured",
+        Box::new(|config: &AppConfig| {
+            Box::new(ConfiguredPlugin {
+                metadata: PluginMetadata::new("configured", "factory test plugin", "0.1.0")
+                    .with_property("db_url", &config.db_url),
+            }) as Box<dyn Plugin>
+        }),
+    );
+    assert!(factories.names().iter().any(|n| n == "configured"));
+
+    let config = AppConfig { db_url: "sqlite::memory:".to_string(), ..Default::default() };
+    let plugins = factories.build(&config);
+    let configured = plugins
+        .iter()
+        .find(|p| p.metadata().name == "configured")
+        .expect("registered factory should be built");
+    assert_eq!(configured.metadata().properties.get("db_url").map(String::as_str), Some("sqlite::memory:"));
+    // 内置插件仍然在列表中
+    assert!(plugins.iter().any(|p| p.metadata().name == "CoreSystem"));
+
+    // 注册只影响这个注册表，默认的插件列表不受影响
+    assert!(!PluginFactories::default().names().iter().any(|n| n == "configured"));
+    assert!(!plugin_names().iter().any(|n| n == "configured"));
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm_loader_skips_missing_and_non_wasm_files() {
+    use amadeus::wasm::{load_wasm_plugins, WASM_PLUGIN_DIR};
+
+    assert_eq!(AppConfig::default().wasm_plugin_dir, WASM_PLUGIN_DIR);
+
+    let dir = std::env::temp_dir().join(format!("amadeus_wasm_{}", std::process::id()));
+    assert!(load_wasm_plugins(&dir).unwrap().is_empty());
+
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("readme.txt"), "not a plugin").unwrap();
+    std::fs::write(dir.join("broken.wasm"), "not wasm either").unwrap();
+    // 非 .wasm 文件被忽略，无效的 .wasm 只记录错误
+    assert!(load_wasm_plugins(&dir).unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn cargo_check(features: &[&str], target: &str) -> bool {
+    Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--no-default-features"])
+        .args(features)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("CARGO_TARGET_DIR", format!("{}/target/{}", env!("CARGO_MANIFEST_DIR"), target))
+        .status()
+        .expect("failed to run cargo")
+        .success()
+}
+
+/// 类似 CI 的检查：核心库在关闭 iceoryx2 时仍可编译
+///
+/// 会启动一次完整的 cargo 构建，默认忽略，使用 `cargo test -- --ignored` 运行
+#[test]
+#[ignore]
+fn test_core_builds_without_iceoryx2() {
+    assert!(cargo_check(&[], "no-default-features"), "cargo check --no-default-features failed");
+}
+
+/// 最小构建（不含 extism）与单独启用 `wasm` 都能编译
+#[test]
+#[ignore]
+fn test_wasm_feature_toggles_extism() {
+    assert!(cargo_check(&[], "no-default-features"), "minimal build failed");
+    assert!(cargo_check(&["--features", "wasm"], "wasm-only"), "wasm build failed");
+}
@@ -0,0 +1,29 @@
+//! Canonicalization is a process-wide switch, so these tests live in their own binary.
+
+use amadeus::core::messaging::{set_message_type_canonicalization, DistributionCenter, Message, MessageType};
+
+#[tokio::test]
+async fn test_canonicalized_type_matches_lowercase_subscription() -> anyhow::Result<()> {
+    // Off by default: types are kept exactly as given
+    assert_eq!(MessageType::new("System.Memo.Create ").as_str(), "System.Memo.Create ");
+
+    set_message_type_canonicalization(true);
+
+    let dc = DistributionCenter::new();
+    let mut rx = dc.subscribe("system.memo.create", "core").await?;
+
+    // Typed in code, and arriving as JSON from a loosely-typed external client
+    dc.distribute(&Message::new("System.Memo.Create ", serde_json::json!({ "n": 1 }))).await;
+    let external = Message::from_json(r#"{
+        "message_type": "  SYSTEM.memo.CREATE", "payload": { "n": 2 }, "priority": "normal",
+        "source": "System", "timestamp": 0, "message_id": null, "recipient": null, "user_context": null
+    }"#)?;
+    assert_eq!(external.message_type.as_str(), "system.memo.create");
+    dc.distribute(&external).await;
+
+    assert_eq!(rx.try_recv()?.payload["n"], 1);
+    assert_eq!(rx.try_recv()?.payload["n"], 2);
+
+    set_message_type_canonicalization(false);
+    Ok(())
+}
@@ -0,0 +1,779 @@
+use amadeus::core::messaging::distribution_center::{DistributionCenter, OverflowPolicy};
+use amadeus::core::messaging::message::{Message, MessagePriority, MessageType};
+use tokio::sync::broadcast::error::RecvError;
+
+#[tokio::test]
+async fn test_overflow_policy_dead_letters_dropped_messages() {
+    // Capacity 2 keeps only the two newest messages for a lagging receiver
+    let dc = DistributionCenter::with_capacity(2).with_overflow_policy(OverflowPolicy::DeadLetter);
+    let mut slow_rx = dc.subscribe("test.flood", "slow_plugin").await.unwrap();
+
+    for i in 0..5 {
+        dc.distribute(&Message::new("test.flood", serde_json::json!({ "seq": i }))).await;
+    }
+
+    // The three oldest messages were overwritten and must show up as dead letters, in order
+    let dead: Vec<i64> = dc
+        .dead_letters()
+        .await
+        .iter()
+        .map(|d| d.message.payload["seq"].as_i64().unwrap())
+        .collect();
+    assert_eq!(dead, vec![0, 1, 2]);
+    assert_eq!(dc.dropped_count(&MessageType::new("test.flood")).await, 3);
+
+    // The slow receiver observes the lag and then the surviving messages
+    assert!(matches!(slow_rx.recv().await, Err(RecvError::Lagged(3))));
+    assert_eq!(slow_rx.recv().await.unwrap().payload["seq"], 3);
+    assert_eq!(slow_rx.recv().await.unwrap().payload["seq"], 4);
+
+    // Draining empties the queue
+    assert_eq!(dc.take_dead_letters().await.len(), 3);
+    assert!(dc.dead_letters().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_overflow_policy_is_recorded_per_subscription() {
+    let dc = DistributionCenter::with_capacity(2);
+    let _skip_rx = dc.subscribe("test.flood", "default_plugin").await.unwrap();
+    let _degraded_rx = dc
+        .subscribe_with_policy("test.flood", "monitored_plugin", OverflowPolicy::MarkDegraded)
+        .await.unwrap();
+
+    for i in 0..4 {
+        dc.distribute(&Message::new("test.flood", serde_json::json!({ "seq": i }))).await;
+    }
+
+    // Default policy only counts, the opted-in subscriber is flagged
+    assert!(dc.dead_letters().await.is_empty());
+    assert_eq!(dc.dropped_count(&MessageType::new("test.flood")).await, 2);
+    assert!(dc.is_degraded("monitored_plugin").await);
+    assert!(!dc.is_degraded("default_plugin").await);
+}
+
+#[tokio::test]
+async fn test_mark_degraded_flags_only_the_lagging_subscriber() {
+    let dc = DistributionCenter::with_capacity(2);
+    let mut fast_rx = dc
+        .subscribe_with_policy("test.flood", "fast_plugin", OverflowPolicy::MarkDegraded)
+        .await.unwrap();
+    let _slow_rx = dc
+        .subscribe_with_policy("test.flood", "slow_plugin", OverflowPolicy::MarkDegraded)
+        .await.unwrap();
+
+    // The fast subscriber drains every message, the slow one never reads
+    for i in 0..4 {
+        dc.distribute(&Message::new("test.flood", serde_json::json!({ "seq": i }))).await;
+        assert_eq!(fast_rx.recv().await.unwrap().payload["seq"], i);
+    }
+
+    assert!(dc.is_degraded("slow_plugin").await);
+    assert!(!dc.is_degraded("fast_plugin").await);
+}
+
+#[tokio::test]
+async fn test_overflow_policy_follows_the_lagging_receiver() {
+    let dc = DistributionCenter::with_capacity(2);
+    let _lagging_rx = dc.subscribe("test.flood", "default_plugin").await.unwrap();
+    let mut dead_letter_rx = dc
+        .subscribe_with_policy("test.flood", "archiving_plugin", OverflowPolicy::DeadLetter)
+        .await.unwrap();
+    let mut degraded_rx = dc
+        .subscribe_with_policy("test.flood", "monitored_plugin", OverflowPolicy::MarkDegraded)
+        .await.unwrap();
+
+    // Only the skip-policy receiver falls behind; the others keep up
+    for i in 0..5 {
+        dc.distribute(&Message::new("test.flood", serde_json::json!({ "seq": i }))).await;
+        assert_eq!(dead_letter_rx.recv().await.unwrap().payload["seq"], i);
+        assert_eq!(degraded_rx.recv().await.unwrap().payload["seq"], i);
+    }
+
+    assert_eq!(dc.dropped_count(&MessageType::new("test.flood")).await, 3);
+    assert!(dc.dead_letters().await.is_empty());
+    assert!(!dc.is_degraded("monitored_plugin").await);
+}
+
+#[tokio::test]
+async fn test_distribute_skips_dropped_global_subscribers() {
+    let dc = DistributionCenter::new();
+    let mut kept_rx = dc.subscribe_all("kept_plugin").await;
+    drop(dc.subscribe_all("gone_plugin").await);
+
+    // The dropped global subscriber is neither counted nor sent to
+    let msg = Message::new("test.any", serde_json::json!({}));
+    assert_eq!(dc.distribute(&msg).await, 1);
+    assert_eq!(dc.distribute(&msg).await, 1);
+    assert_eq!(kept_rx.recv().await.unwrap().message_type.as_str(), "test.any");
+}
+
+#[tokio::test]
+async fn test_message_type_alias_rewrites_legacy_type() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::{MessageManager, ORIGINAL_TYPE_METADATA_KEY};
+
+    let mut message_manager = MessageManager::new();
+    message_manager.add_alias("system.memo.create", "memo.create");
+    message_manager.start_message_loop();
+
+    let dc = message_manager.distribution_center();
+    let mut rx_new = dc.subscribe("memo.create", "new_handler").await?;
+    let mut rx_old = dc.subscribe("system.memo.create", "legacy_listener").await?;
+
+    // A legacy client still sends the old type
+    message_manager
+        .message_tx()
+        .send(Message::new("system.memo.create", serde_json::json!({ "content": "legacy" })))
+        .await?;
+
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx_new.recv()).await??;
+    assert_eq!(msg.message_type.as_str(), "memo.create");
+    assert_eq!(msg.payload["content"], "legacy");
+    assert_eq!(msg.metadata[ORIGINAL_TYPE_METADATA_KEY], "system.memo.create");
+
+    // Nothing is distributed under the old name anymore
+    assert!(rx_old.try_recv().is_err());
+
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_try_send_reports_full_channel_instead_of_blocking() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use amadeus::core::messaging::TrySendError;
+
+    // Nobody drains this channel
+    let (tx, mut rx) = tokio::sync::mpsc::channel(2);
+    let ctx = MessageContext::new(Arc::new(DistributionCenter::new()), "burst_plugin", "burst-uid", tx);
+
+    ctx.try_send(Message::new("test.burst", serde_json::json!({ "seq": 0 })))?;
+    ctx.try_send(Message::new("test.burst", serde_json::json!({ "seq": 1 })))?;
+
+    match ctx.try_send(Message::new("test.burst", serde_json::json!({ "seq": 2 }))) {
+        Err(TrySendError::Full(msg)) => assert_eq!(msg.payload["seq"], 2),
+        other => panic!("expected a full channel, got {:?}", other),
+    }
+
+    // The awaiting variant gives up after the timeout
+    let result = tokio::time::timeout(
+        Duration::from_secs(2),
+        ctx.send_timeout(Message::new("test.burst", serde_json::json!({ "seq": 3 })), Duration::from_millis(50)),
+    )
+    .await?;
+    assert!(result.is_err());
+
+    // Accepted messages carry the sending plugin as their source
+    let first = rx.recv().await.unwrap();
+    assert!(matches!(first.source, amadeus::MessageSource::Plugin(ref name) if name == "burst_plugin"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscription_limit_rejects_flood() -> anyhow::Result<()> {
+    let dc = DistributionCenter::new().with_subscription_limit(3);
+
+    let mut held = Vec::new();
+    for i in 0..3 {
+        held.push(dc.subscribe(format!("flood.{}", i), "greedy_plugin").await?);
+    }
+    assert_eq!(dc.subscription_count("greedy_plugin").await, 3);
+
+    // Over the cap: rejected, and nothing new is recorded
+    assert!(dc.subscribe("flood.3", "greedy_plugin").await.is_err());
+    assert_eq!(dc.subscription_count("greedy_plugin").await, 3);
+    assert!(!dc.get_subscription_stats().await.contains_key("flood.3"));
+
+    // The cap is per plugin
+    let _other = dc.subscribe("flood.0", "other_plugin").await?;
+
+    // Dropped receivers free up their slots
+    held.truncate(1);
+    let _again = dc.subscribe("flood.3", "greedy_plugin").await?;
+    assert_eq!(dc.subscription_count("greedy_plugin").await, 2);
+
+    // Unsubscribing everything removes the plugin's entry
+    dc.unsubscribe("other_plugin", &MessageType::new("flood.0")).await;
+    assert!(dc.get_plugin_subscriptions("other_plugin").await.is_empty());
+    assert_eq!(dc.subscription_count("other_plugin").await, 0);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_subscription_limit_holds_under_concurrent_subscribes() -> anyhow::Result<()> {
+    let dc = DistributionCenter::new().with_subscription_limit(3);
+
+    let attempts: Vec<_> = (0..16)
+        .map(|i| {
+            let dc = dc.clone();
+            tokio::spawn(async move { dc.subscribe(format!("race.{}", i), "racing_plugin").await })
+        })
+        .collect();
+    let mut held = Vec::new();
+    for attempt in attempts {
+        if let Ok(rx) = attempt.await? {
+            held.push(rx);
+        }
+    }
+
+    assert_eq!(held.len(), 3);
+    assert_eq!(dc.subscription_count("racing_plugin").await, 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscribe_rejects_conflicting_policy_on_same_topic() -> anyhow::Result<()> {
+    let dc = DistributionCenter::new();
+    let _degraded_rx = dc.subscribe_with_policy("test.topic", "plugin_a", OverflowPolicy::MarkDegraded).await?;
+
+    // A second subscription with the same policy is fine, a different one is rejected
+    let _again = dc.subscribe_with_policy("test.topic", "plugin_a", OverflowPolicy::MarkDegraded).await?;
+    assert!(dc.subscribe_with_policy("test.topic", "plugin_a", OverflowPolicy::DeadLetter).await.is_err());
+    assert_eq!(dc.subscription_count("plugin_a").await, 2);
+
+    // Other plugins and other topics are unaffected
+    let _other = dc.subscribe_with_policy("test.topic", "plugin_b", OverflowPolicy::DeadLetter).await?;
+    let _elsewhere = dc.subscribe_with_policy("test.other", "plugin_a", OverflowPolicy::DeadLetter).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_interceptor_transforms_and_drops_messages() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+
+    let mut message_manager = MessageManager::new();
+    message_manager.set_interceptor(Box::new(|mut msg: Message| {
+        if msg.payload.get("drop").is_some() {
+            return None;
+        }
+        // Redact secrets and stamp a trace id
+        if msg.payload.get("password").is_some() {
+            msg.payload["password"] = serde_json::json!("***");
+        }
+        msg.metadata.insert("trace_id".to_string(), "trace-1".to_string());
+        Some(msg)
+    }));
+    message_manager.start_message_loop();
+
+    let dc = message_manager.distribution_center();
+    let mut rx = dc.subscribe("test.intercepted", "observer").await?;
+    let tx = message_manager.message_tx();
+
+    tx.send(Message::new("test.intercepted", serde_json::json!({ "drop": true }))).await?;
+    tx.send(Message::new("test.intercepted", serde_json::json!({ "user": "alice", "password": "hunter2" }))).await?;
+
+    // The dropped message never arrives; the first one seen is the transformed one
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await??;
+    assert_eq!(msg.payload["user"], "alice");
+    assert_eq!(msg.payload["password"], "***");
+    assert_eq!(msg.metadata["trace_id"], "trace-1");
+
+    // Without an interceptor messages pass through untouched
+    message_manager.clear_interceptor();
+    tx.send(Message::new("test.intercepted", serde_json::json!({ "drop": true }))).await?;
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await??;
+    assert_eq!(msg.payload["drop"], true);
+    assert!(rx.try_recv().is_err());
+
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_oversized_payload_is_rejected_before_distribution() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use amadeus::core::messaging::message_manager::{MessageManager, MESSAGE_REJECTED_TYPE};
+    use amadeus::core::messaging::TrySendError;
+    use std::sync::Arc;
+
+    let mut message_manager = MessageManager::new().with_max_payload_size(64);
+    message_manager.start_message_loop();
+
+    let dc = message_manager.distribution_center();
+    let mut rx = dc.subscribe("test.blob", "observer").await?;
+    let mut rx_rejected = dc.subscribe(MESSAGE_REJECTED_TYPE, "sender").await?;
+    let tx = message_manager.message_tx();
+    let blob = "x".repeat(1024);
+
+    // Raw senders get a rejection event; the message goes to the dead-letter queue instead of subscribers
+    tx.send(Message::new("test.blob", serde_json::json!({ "data": blob })).with_id("big-1")).await?;
+    let rejected = tokio::time::timeout(std::time::Duration::from_secs(2), rx_rejected.recv()).await??;
+    assert_eq!(rejected.payload["message_type"], "test.blob");
+    assert_eq!(rejected.payload["message_id"], "big-1");
+    assert_eq!(rejected.payload["limit"], 64);
+    assert!(rejected.payload["size"].as_u64().unwrap() > 1024);
+
+    let dead_letters = dc.dead_letters().await;
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].message.message_id.as_deref(), Some("big-1"));
+
+    // Plugins sending through their context get the error directly
+    let ctx = MessageContext::new(Arc::clone(dc), "big_sender", "big_sender_uid", tx.clone());
+    let err = ctx.send(Message::new("test.blob", serde_json::json!({ "data": blob }))).await.unwrap_err();
+    assert!(err.to_string().contains("64"), "unexpected error: {}", err);
+
+    // try_send enforces the same limit and hands the message back
+    match ctx.try_send(Message::new("test.blob", serde_json::json!({ "data": blob })).with_id("big-2")) {
+        Err(TrySendError::PayloadTooLarge { message, size, limit }) => {
+            assert_eq!(message.message_id.as_deref(), Some("big-2"));
+            assert!(size > 1024);
+            assert_eq!(limit, 64);
+        }
+        other => panic!("expected an oversized payload error, got {:?}", other),
+    }
+
+    // Small payloads still flow, and nothing oversized reached the subscriber
+    ctx.send(Message::new("test.blob", serde_json::json!({ "data": "ok" }))).await?;
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await??;
+    assert_eq!(msg.payload["data"], "ok");
+    assert!(rx.try_recv().is_err());
+
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_payload_size_is_unlimited_by_default() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use std::sync::Arc;
+
+    let mut message_manager = MessageManager::new();
+    message_manager.start_message_loop();
+
+    let dc = message_manager.distribution_center();
+    assert_eq!(dc.max_payload_size(), usize::MAX);
+    let mut rx = dc.subscribe("test.blob", "observer").await?;
+    let ctx = MessageContext::new(Arc::clone(dc), "big_sender", "big_sender_uid", message_manager.message_tx());
+
+    let blob = "x".repeat(2 * 1024 * 1024);
+    ctx.send(Message::new("test.blob", serde_json::json!({ "data": blob }))).await?;
+    ctx.try_send(Message::new("test.blob", serde_json::json!({ "data": "small" })))?;
+
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await??;
+    assert_eq!(msg.payload["data"].as_str().map(str::len), Some(blob.len()));
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), rx.recv()).await??;
+    assert_eq!(msg.payload["data"], "small");
+    assert!(dc.dead_letters().await.is_empty());
+
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[test]
+fn test_redacted_hides_sensitive_fields() {
+    use amadeus::core::messaging::register_sensitive_fields;
+
+    // system.user.* is sensitive by default: values are hidden, keys are kept
+    let resolve = Message::new(
+        "system.user.resolve",
+        serde_json::json!({ "platform": "discord", "platform_user_id": "8675309", "name": "Jenny" })
+    );
+    let logged = resolve.redacted().to_string();
+    assert!(!logged.contains("8675309"), "{}", logged);
+    assert!(!logged.contains("Jenny"), "{}", logged);
+    assert!(logged.contains("\"platform_user_id\":\"***\""), "{}", logged);
+    assert!(logged.contains("system.user.resolve"));
+
+    // Memo messages hide only the reminder content
+    let memo = Message::new("system.memo.create", serde_json::json!({ "content": "see the doctor", "priority": 2 }));
+    let logged = memo.redacted().to_string();
+    assert!(!logged.contains("doctor"), "{}", logged);
+    assert!(logged.contains("\"priority\":2"), "{}", logged);
+
+    // Senders can flag extra fields per message, nested paths included
+    let flagged = Message::new("test.redaction.flag", serde_json::json!({ "auth": { "token": "abc123" }, "keep": "visible" }))
+        .with_metadata("sensitive", "auth.token");
+    let logged = flagged.redacted().to_string();
+    assert!(!logged.contains("abc123"), "{}", logged);
+    assert!(logged.contains("visible"), "{}", logged);
+
+    // Rules can be registered for other message types
+    register_sensitive_fields("test.redaction.custom", &["secret"]);
+    let custom = Message::new("test.redaction.custom.event", serde_json::json!({ "secret": "hunter2" }));
+    assert!(!custom.redacted().to_string().contains("hunter2"));
+
+    // The message itself is untouched
+    assert_eq!(resolve.payload["platform_user_id"], "8675309");
+}
+
+#[tokio::test]
+async fn test_prune_idle_removes_unused_topics() -> anyhow::Result<()> {
+    use std::time::Duration;
+
+    let dc = DistributionCenter::new();
+    let rx_idle = dc.subscribe("test.idle", "plugin_a").await?;
+    let _rx_active = dc.subscribe("test.active", "plugin_a").await?;
+    let rx_recent = dc.subscribe("test.recent", "plugin_b").await?;
+
+    // A topic that lost its receivers but was active a moment ago survives
+    drop(rx_idle);
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    dc.distribute(&Message::new("test.recent", serde_json::json!({}))).await;
+    drop(rx_recent);
+
+    assert_eq!(dc.prune_idle(Duration::from_millis(50)).await, 1);
+    let topics = dc.get_subscription_stats().await;
+    assert!(!topics.contains_key("test.idle"));
+    // Topics with live receivers are never pruned, however quiet they are
+    assert!(topics.contains_key("test.active"));
+    assert!(topics.contains_key("test.recent"));
+    assert_eq!(dc.get_plugin_subscriptions("plugin_a").await, vec![MessageType::new("test.active")]);
+
+    // Once it has been quiet long enough, the recently used topic goes too
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(dc.prune_idle(Duration::from_millis(50)).await, 1);
+    assert!(!dc.get_subscription_stats().await.contains_key("test.recent"));
+    assert_eq!(dc.subscription_count("plugin_b").await, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_subscribe_filtered_skips_non_matching_messages() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let dc = Arc::new(DistributionCenter::new());
+    let (tx, _rx) = tokio::sync::mpsc::channel(8);
+    let ctx = MessageContext::new(Arc::clone(&dc), "primary_only", "primary-only-uid", tx);
+
+    let mut primary = ctx
+        .subscribe_filtered("system.memo.remind", |msg| msg.payload["type"] == "primary")
+        .await?;
+    let mut everything = ctx.subscribe("system.memo.remind").await?;
+
+    for (id, kind) in [(1, "tag_reminder"), (2, "primary"), (3, "one_shot"), (4, "primary")] {
+        dc.distribute(&Message::new("system.memo.remind", serde_json::json!({ "id": id, "type": kind }))).await;
+    }
+
+    assert_eq!(primary.recv().await?.payload["id"], 2);
+    assert_eq!(primary.recv().await?.payload["id"], 4);
+    assert!(tokio::time::timeout(Duration::from_millis(100), primary.recv()).await.is_err());
+    assert!(primary.try_recv().is_err());
+
+    // The bus itself still delivers every message to unfiltered subscribers
+    for id in 1..=4 {
+        assert_eq!(everything.recv().await?.payload["id"], id);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_partitioned_subscribers_split_keyed_messages() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use amadeus::core::messaging::{Partition, PARTITION_KEY_METADATA_KEY};
+    use amadeus::core::user::{PlatformId, PlatformUserId, UserContext, UserId, UserInfo};
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    let dc = Arc::new(DistributionCenter::new());
+    let (tx, _rx) = tokio::sync::mpsc::channel(8);
+    let worker_a = MessageContext::new(Arc::clone(&dc), "worker_a", "worker-a-uid", tx.clone());
+    let worker_b = MessageContext::new(Arc::clone(&dc), "worker_b", "worker-b-uid", tx);
+
+    let mut rx_a = worker_a.subscribe_partitioned("work.item", Partition::new("workers", 2, 0)).await?;
+    let mut rx_b = worker_b.subscribe_partitioned("work.item", Partition::new("workers", 2, 1)).await?;
+
+    // Half keyed explicitly, half routed by the sender's user id
+    for i in 0..100 {
+        let msg = Message::new("work.item", serde_json::json!({ "seq": i }));
+        let msg = if i % 2 == 0 {
+            msg.with_metadata(PARTITION_KEY_METADATA_KEY, format!("key-{}", i))
+        } else {
+            msg.with_user(UserContext::new(UserInfo {
+                id: UserId::new(format!("user-{}", i)),
+                name: format!("user-{}", i),
+                platform: PlatformId("cli".to_string()),
+                platform_user_id: PlatformUserId(i.to_string()),
+            }))
+        };
+        dc.distribute(&msg).await;
+    }
+
+    let drain = |rx: &mut amadeus::core::messaging::FilteredReceiver| {
+        let mut seqs = HashSet::new();
+        while let Ok(msg) = rx.try_recv() {
+            seqs.insert(msg.payload["seq"].as_i64().unwrap());
+        }
+        seqs
+    };
+    let a = drain(&mut rx_a);
+    let b = drain(&mut rx_b);
+
+    assert!(!a.is_empty() && !b.is_empty(), "both partitions should get work: {} / {}", a.len(), b.len());
+    assert!(a.is_disjoint(&b), "a message was delivered to both partitions");
+    let all: HashSet<i64> = a.union(&b).copied().collect();
+    assert_eq!(all, (0..100).collect::<HashSet<i64>>());
+
+    // Routing depends only on the key: another message with key-0 goes where seq 0 went
+    let keyed = Message::new("other.item", serde_json::json!({})).with_metadata(PARTITION_KEY_METADATA_KEY, "key-0");
+    assert_eq!(keyed.partition(2) == 0, a.contains(&0));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_partition_group_rejects_inconsistent_count() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use amadeus::core::messaging::Partition;
+    use std::sync::Arc;
+
+    let dc = Arc::new(DistributionCenter::new());
+    let (tx, _rx) = tokio::sync::mpsc::channel(8);
+    let ctx = MessageContext::new(Arc::clone(&dc), "worker", "worker-uid", tx);
+
+    ctx.subscribe_partitioned("work.item", Partition::new("workers", 2, 0)).await?;
+    assert!(ctx.subscribe_partitioned("work.item", Partition::new("workers", 3, 1)).await.is_err());
+    assert!(ctx.subscribe_partitioned("work.item", Partition::new("others", 2, 2)).await.is_err());
+    // Another group on the same type is independent
+    ctx.subscribe_partitioned("work.item", Partition::new("others", 3, 2)).await?;
+    Ok(())
+}
+
+#[test]
+fn test_priority_accepts_level_and_label() {
+    let from_level: MessagePriority = serde_json::from_value(serde_json::json!(2)).unwrap();
+    let from_label: MessagePriority = serde_json::from_value(serde_json::json!("high")).unwrap();
+    assert_eq!(from_level, MessagePriority::High);
+    assert_eq!(from_label, from_level);
+    assert_eq!(from_label.level(), 2);
+    assert_eq!(from_label.label(), "high");
+
+    // Variant names as serialized by older peers still parse
+    let legacy: MessagePriority = serde_json::from_value(serde_json::json!("Critical")).unwrap();
+    assert_eq!(legacy, MessagePriority::Critical);
+
+    assert!(serde_json::from_value::<MessagePriority>(serde_json::json!(7)).is_err());
+    assert!(serde_json::from_value::<MessagePriority>(serde_json::json!("urgent")).is_err());
+}
+
+#[tokio::test]
+async fn test_self_addressed_direct_message_does_not_loop() -> anyhow::Result<()> {
+    use amadeus::core::messaging::{MessageContext, MessageManager, SelfAddressPolicy};
+    use std::time::Duration;
+
+    let mut message_manager = MessageManager::new();
+    message_manager.start_message_loop();
+    let dc = message_manager.distribution_center().clone();
+
+    // A symmetric plugin that answers every direct message with a fresh one to the sender, which is itself
+    let echo = |ctx: MessageContext, mut rx: tokio::sync::mpsc::Receiver<Message>| async move {
+        let mut received = 0;
+        while let Ok(Some(msg)) = tokio::time::timeout(Duration::from_millis(300), rx.recv()).await {
+            received += 1;
+            let reply = Message::new_direct(ctx.plugin_uid(), msg.message_type.as_str(), serde_json::json!({}));
+            ctx.send(reply).await.unwrap();
+        }
+        received
+    };
+
+    // Default: self-addressed messages are delivered like any other direct message
+    let ctx = MessageContext::new(dc.clone(), "echo", "echo-uid", message_manager.message_tx());
+    let mut rx = ctx.enable_direct_messaging().await;
+    ctx.send(Message::new_direct("echo-uid", "test.ping", serde_json::json!({}))).await?;
+    let msg = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await?.unwrap();
+    assert_eq!(msg.message_type.as_str(), "test.ping");
+    drop(rx);
+
+    // Ignore: self-addressed messages are dropped
+    let ctx = MessageContext::new(dc.clone(), "echo", "echo-uid", message_manager.message_tx())
+        .with_self_address_policy(SelfAddressPolicy::Ignore);
+    let rx = ctx.enable_direct_messaging().await;
+    ctx.send(Message::new_direct("echo-uid", "test.ping", serde_json::json!({}))).await?;
+    assert_eq!(echo(ctx, rx).await, 0);
+
+    // DeliverOnce: the first send arrives, the newly built echo is dropped
+    let ctx = MessageContext::new(dc.clone(), "echo", "echo-uid", message_manager.message_tx())
+        .with_self_address_policy(SelfAddressPolicy::DeliverOnce);
+    let rx = ctx.enable_direct_messaging().await;
+    ctx.send(Message::new_direct("echo-uid", "test.ping", serde_json::json!({}))).await?;
+    assert_eq!(echo(ctx, rx).await, 1);
+
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[test]
+fn test_content_type_hint_round_trips() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message::{CONTENT_TYPE_JSON, CONTENT_TYPE_OCTET_STREAM};
+
+    let image = [0x89_u8, b'P', b'N', b'G', 0x00, 0xff];
+    let msg = Message::binary("system.memo.attachment", &image);
+    assert_eq!(msg.content_type(), CONTENT_TYPE_OCTET_STREAM);
+    assert!(!msg.is_json());
+
+    let decoded = Message::from_json(&msg.to_json()?)?;
+    assert_eq!(decoded.content_type.as_deref(), Some(CONTENT_TYPE_OCTET_STREAM));
+    assert_eq!(decoded.binary_payload().unwrap(), image);
+
+    // Binary payloads are summarized rather than dumped into logs
+    assert!(decoded.redacted().to_string().contains("application/octet-stream"));
+
+    // Messages without the hint are JSON, including ones serialized before the field existed
+    let plain = Message::new("test.plain", serde_json::json!({ "k": 1 }));
+    let json = plain.to_json()?;
+    assert!(!json.contains("content_type"));
+    let decoded = Message::from_json(&json)?;
+    assert_eq!(decoded.content_type(), CONTENT_TYPE_JSON);
+    assert!(decoded.binary_payload().is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reply_reusing_request_id_is_not_a_redelivery() -> anyhow::Result<()> {
+    use amadeus::core::messaging::MessageManager;
+    use std::time::Duration;
+
+    let mut mm = MessageManager::new();
+    let dc = mm.distribution_center().clone();
+    let mut rx_request = dc.subscribe("test.request", "verifier").await?;
+    let mut rx_reply = dc.subscribe("test.reply", "verifier").await?;
+    let mut rx_anon = dc.subscribe("test.anonymous", "verifier").await?;
+    mm.start_message_loop();
+    let tx = mm.message_tx();
+
+    // A reply keeps the request's message_id but is a different message
+    tx.send(Message::new("test.request", serde_json::json!({})).with_id("req-1")).await?;
+    tx.send(Message::new("test.reply", serde_json::json!({})).with_id("req-1")).await?;
+    let request = tokio::time::timeout(Duration::from_secs(2), rx_request.recv()).await??;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_reply.recv()).await??;
+    assert_eq!(request.delivery_attempt, 1);
+    assert_eq!(reply.delivery_attempt, 1);
+    assert!(!reply.is_redelivery());
+
+    // Empty ids are not tracked at all
+    for _ in 0..3 {
+        tx.send(Message::new("test.anonymous", serde_json::json!({})).with_id("")).await?;
+        let seen = tokio::time::timeout(Duration::from_secs(2), rx_anon.recv()).await??;
+        assert_eq!(seen.delivery_attempt, 1);
+    }
+
+    // A true redelivery of the request is still counted
+    tx.send(Message::new("test.request", serde_json::json!({})).with_id("req-1")).await?;
+    let again = tokio::time::timeout(Duration::from_secs(2), rx_request.recv()).await??;
+    assert_eq!(again.delivery_attempt, 2);
+
+    mm.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seal_detects_mutation_after_sealing() -> anyhow::Result<()> {
+    use amadeus::core::messaging::{MessageManager, MessageSource};
+
+    assert_eq!(Message::new("test.unsealed", serde_json::json!({})).verify_seal(), None);
+
+    let sealed = Message::new("test.sealed", serde_json::json!({ "amount": 10 }))
+        .with_id("req-1")
+        .seal();
+    assert_eq!(sealed.verify_seal(), Some(true));
+
+    // Fields that legitimately change in flight are not covered
+    let mut routed = sealed.clone();
+    routed.source = MessageSource::Plugin("sender".into());
+    routed.delivery_attempt = 2;
+    routed.metadata.insert("reply_to".into(), "sender#1".into());
+    assert_eq!(routed.verify_seal(), Some(true));
+
+    let mut tampered = sealed.clone();
+    tampered.payload["amount"] = serde_json::json!(1000);
+    assert_eq!(tampered.verify_seal(), Some(false));
+
+    let mut retyped = sealed.clone();
+    retyped.message_type = MessageType::new("test.other");
+    assert_eq!(retyped.verify_seal(), Some(false));
+
+    // With verification on, intact sealed messages are distributed as usual
+    let mut mm = MessageManager::new().with_seal_verification(true);
+    let mut rx = mm.distribution_center().subscribe("test.sealed", "verifier").await?;
+    mm.start_message_loop();
+    mm.message_tx().send(sealed).await?;
+    let received = tokio::time::timeout(std::time::Duration::from_secs(1), rx.recv()).await??;
+    assert_eq!(received.verify_seal(), Some(true));
+    mm.stop_message_loop().await;
+    Ok(())
+}
+
+/// Mirror of the create handler's request shape
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct CreateRequest {
+    content: String,
+    cron: Option<String>,
+    tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "amadeus::core::messaging::message::deserialize_priority_level")]
+    priority: Option<i32>,
+}
+
+#[test]
+fn test_payload_as_matches_from_value_without_cloning() {
+    let msg = Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "buy milk", "tags": ["home"], "priority": "high" }),
+    );
+
+    // Same result as the old `from_value(payload.clone())` path
+    let borrowed: CreateRequest = msg.payload_as().unwrap();
+    let cloned: CreateRequest = serde_json::from_value(msg.payload.clone()).unwrap();
+    assert_eq!(borrowed, cloned);
+    assert_eq!(borrowed.priority, Some(2));
+
+    // Borrowed fields point into the payload itself rather than a copy
+    #[derive(serde::Deserialize)]
+    struct ContentRef<'a> {
+        content: &'a str,
+    }
+    let content_ref: ContentRef = msg.payload_as().unwrap();
+    assert!(std::ptr::eq(content_ref.content, msg.payload["content"].as_str().unwrap()));
+
+    // Malformed payloads fail the same way
+    let bad = Message::new("system.memo.create", serde_json::json!({ "tags": "home" }));
+    assert!(bad.payload_as::<CreateRequest>().is_err());
+    assert!(serde_json::from_value::<CreateRequest>(bad.payload.clone()).is_err());
+}
+
+#[test]
+fn test_new_ref_serializes_borrowed_payload() {
+    #[derive(serde::Serialize)]
+    struct Reply<'a> {
+        id: i64,
+        content: &'a str,
+    }
+    let msg = Message::new_ref("system.memo.created", &Reply { id: 7, content: "buy milk" }).unwrap();
+    assert_eq!(msg.payload, serde_json::json!({ "id": 7, "content": "buy milk" }));
+    assert_eq!(msg.message_type.as_str(), "system.memo.created");
+}
+
+#[tokio::test]
+async fn test_direct_interest_delivers_broadcast_on_direct_channel() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use amadeus::core::messaging::MessageContext;
+    use std::time::Duration;
+
+    let mut manager = MessageManager::new();
+    let ctx = MessageContext::new(manager.distribution_center().clone(), "listener", "listener-uid", manager.message_tx());
+    let mut rx = ctx.enable_direct_messaging().await;
+    ctx.register_direct_interest("system.memo.created").await;
+    manager.start_message_loop();
+
+    let tx = manager.message_tx();
+    tx.send(Message::new("system.memo.updated", serde_json::json!({ "id": 1 }))).await?;
+    tx.send(Message::new("system.memo.created", serde_json::json!({ "id": 2 }))).await?;
+    tx.send(Message::new_direct("listener-uid", "plugin.ping", serde_json::json!({}))).await?;
+
+    // One mpsc receives both the chosen broadcast and the direct message, and nothing else
+    let first = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await?.unwrap();
+    assert_eq!(first.message_type.as_str(), "system.memo.created");
+    assert_eq!(first.payload["id"], 2);
+    let second = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await?.unwrap();
+    assert_eq!(second.message_type.as_str(), "plugin.ping");
+    assert!(rx.try_recv().is_err());
+
+    // Interest goes away with the direct channel
+    let dc = manager.distribution_center();
+    dc.unregister_direct_channel("listener-uid").await;
+    assert_eq!(dc.distribute(&Message::new("system.memo.created", serde_json::json!({}))).await, 0);
+
+    manager.stop_message_loop().await;
+    Ok(())
+}
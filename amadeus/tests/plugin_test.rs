@@ -0,0 +1,385 @@
+use amadeus::plugin::{Plugin, PluginMetadata, PluginRegistry, PluginType, AMADEUS_API_VERSION, REQUIRED_API_VERSION_PROPERTY};
+use amadeus::core::messaging::{DistributionCenter, Message, MessageContext};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+struct VersionedPlugin {
+    metadata: PluginMetadata,
+}
+
+impl VersionedPlugin {
+    fn new(name: &str, required: Option<&str>) -> Self {
+        let mut metadata = PluginMetadata::new(name, "API version test plugin", "0.1.0");
+        if let Some(required) = required {
+            metadata = metadata.with_property(REQUIRED_API_VERSION_PROPERTY, required);
+        }
+        Self { metadata }
+    }
+}
+
+impl Plugin for VersionedPlugin {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+}
+
+#[test]
+fn test_incompatible_api_version_is_rejected() {
+    let mut registry = PluginRegistry::new();
+
+    // Default declaration matches the host
+    let current = VersionedPlugin::new("Current", None);
+    assert_eq!(current.required_api_version(), AMADEUS_API_VERSION);
+    assert!(registry.try_register(current).is_ok());
+
+    // A compatible requirement is accepted
+    assert!(registry.try_register(VersionedPlugin::new("Compatible", Some(">=0.1, <0.2"))).is_ok());
+
+    // A plugin built against a future major API is rejected
+    let err = registry
+        .try_register(VersionedPlugin::new("Future", Some("2.0")))
+        .unwrap_err();
+    assert!(err.to_string().contains("Future"));
+
+    // Batch registration skips the incompatible plugin as well
+    registry.register_all(vec![
+        Box::new(VersionedPlugin::new("Old", Some("0.0.1"))),
+        Box::new(VersionedPlugin::new("Batch", None)),
+    ]);
+
+    let names: Vec<&str> = registry.plugins().iter().map(|p| p.id()).collect();
+    assert_eq!(names, vec!["Current", "Compatible", "Batch"]);
+}
+
+#[test]
+fn test_metadata_version_parses_as_semver() {
+    let old = PluginMetadata::new("Versioned", "", "0.1.0");
+    assert_eq!(old.semver().unwrap(), semver::Version::new(0, 1, 0));
+
+    // Upgrade detection compares parsed versions, not strings ("0.10.0" > "0.9.2")
+    let new = PluginMetadata::new("Versioned", "", "0.10.0");
+    let older = PluginMetadata::new("Versioned", "", "0.9.2");
+    assert!(new.semver().unwrap() > older.semver().unwrap());
+    assert!(new.semver().unwrap() > old.semver().unwrap());
+
+    let invalid = PluginMetadata::new("Versioned", "", "abc");
+    let err = invalid.semver().unwrap_err();
+    assert!(err.to_string().contains("abc"));
+}
+
+#[test]
+fn test_unregister_removes_plugin_by_name() {
+    let mut registry = PluginRegistry::new();
+    registry.register(VersionedPlugin::new("First", None));
+    registry.register(VersionedPlugin::new("Second", None));
+
+    let removed = registry.unregister("First").expect("First was registered");
+    assert_eq!(removed.id(), "First");
+    assert!(registry.unregister("First").is_none());
+
+    let names: Vec<&str> = registry.plugins().iter().map(|p| p.id()).collect();
+    assert_eq!(names, vec!["Second"]);
+}
+
+/// Records `<name>.<phase>` for each lifecycle call
+struct LifecycleRecorder {
+    metadata: PluginMetadata,
+    plugin_type: PluginType,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl LifecycleRecorder {
+    fn new(name: &str, plugin_type: PluginType, events: &Arc<Mutex<Vec<String>>>) -> Self {
+        Self {
+            metadata: PluginMetadata::new(name, "Lifecycle test plugin", "0.1.0"),
+            plugin_type,
+            events: events.clone(),
+        }
+    }
+
+    fn record(&self, phase: &str) {
+        self.events.lock().unwrap().push(format!("{}.{}", self.metadata.name, phase));
+    }
+}
+
+impl Plugin for LifecycleRecorder {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn plugin_type(&self) -> PluginType {
+        self.plugin_type
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.record("init");
+        Ok(())
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        self.record("start");
+        Ok(())
+    }
+
+    fn run(&mut self) -> anyhow::Result<()> {
+        self.record("run");
+        Ok(())
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        self.record("stop");
+        Ok(())
+    }
+}
+
+#[test]
+fn test_lifecycle_chain_runs_each_phase_in_order() -> anyhow::Result<()> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = PluginRegistry::new();
+    registry.register(LifecycleRecorder::new("Normal", PluginType::Normal, &events));
+    registry.register(LifecycleRecorder::new("Privileged", PluginType::Privileged, &events));
+
+    registry.init_all()?.start_all()?.run_all()?.stop_all()?;
+
+    let expected = vec![
+        "Privileged.init", "Normal.init",
+        "Privileged.start", "Normal.start",
+        "Privileged.run", "Normal.run",
+        // Stop runs in reverse order
+        "Normal.stop", "Privileged.stop",
+    ];
+    assert_eq!(*events.lock().unwrap(), expected);
+
+    // run_lifecycle performs the same chain without blocking
+    events.lock().unwrap().clear();
+    registry.run_lifecycle()?;
+    assert_eq!(*events.lock().unwrap(), expected);
+    Ok(())
+}
+
+/// Fails `setup_messaging` unless `init` has already run
+struct InitBeforeMessaging {
+    metadata: PluginMetadata,
+    inits: usize,
+    saw_init_in_setup: Arc<Mutex<Option<bool>>>,
+}
+
+impl Plugin for InitBeforeMessaging {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn init(&mut self) -> anyhow::Result<()> {
+        self.inits += 1;
+        Ok(())
+    }
+
+    fn setup_messaging(
+        &mut self,
+        _distribution_center: &DistributionCenter,
+        _message_tx: mpsc::Sender<Message>,
+    ) -> Pin<Box<dyn Future<Output = anyhow::Result<Option<Arc<MessageContext>>>> + Send>> {
+        *self.saw_init_in_setup.lock().unwrap() = Some(self.inits == 1);
+        Box::pin(async { Ok(None) })
+    }
+
+    fn start(&mut self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.inits == 1, "expected exactly one init before start, got {}", self.inits);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_setup_messaging_runs_after_init() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+
+    let saw_init = Arc::new(Mutex::new(None));
+    let mut registry = PluginRegistry::new();
+    registry.register(InitBeforeMessaging {
+        metadata: PluginMetadata::new("InitFirst", "Checks lifecycle ordering", "0.1.0"),
+        inits: 0,
+        saw_init_in_setup: saw_init.clone(),
+    });
+
+    // Even without an explicit init_all, the registry initializes before wiring messaging
+    let message_manager = MessageManager::new();
+    assert!(!registry.is_initialized());
+    registry.setup_messaging(&message_manager).await?;
+    assert_eq!(*saw_init.lock().unwrap(), Some(true));
+    assert!(registry.is_initialized());
+
+    // The legacy `setup_messaging -> startup` sequence does not initialize twice
+    registry.startup()?;
+    registry.shutdown()?;
+    assert!(!registry.is_initialized());
+    Ok(())
+}
+
+#[test]
+fn test_disabled_state_survives_restart() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("amadeus_plugin_state_{}.json", uuid::Uuid::new_v4()));
+    let path = path.to_str().unwrap();
+    let plugins = || -> Vec<Box<dyn Plugin>> {
+        vec![
+            Box::new(VersionedPlugin::new("Keep", None)),
+            Box::new(VersionedPlugin::new("Muted", None)),
+        ]
+    };
+
+    let mut registry = PluginRegistry::with_enabled_plugins(plugins());
+    registry.set_enabled("Muted", false);
+    assert!(!registry.is_enabled("Muted"));
+    registry.save_state(path)?;
+
+    // Both plugins default to enabled, but the saved operator choice wins
+    let mut restarted = PluginRegistry::new();
+    restarted.apply_state(path)?;
+    restarted.register_enabled(plugins());
+    let names: Vec<&str> = restarted.plugins().iter().map(|p| p.id()).collect();
+    assert_eq!(names, vec!["Keep"]);
+    assert!(restarted.is_enabled("Keep"));
+    assert!(!restarted.is_enabled("Muted"));
+
+    // A missing state file leaves the defaults alone
+    let mut fresh = PluginRegistry::new();
+    fresh.apply_state("/nonexistent/amadeus_plugin_state.json")?;
+    fresh.register_enabled(plugins());
+    assert_eq!(fresh.plugins().len(), 2);
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+/// Records `stop`; optionally hangs in `stop_async` far longer than any test timeout
+struct StoppablePlugin {
+    metadata: PluginMetadata,
+    hang: bool,
+    stopped: Arc<Mutex<Vec<String>>>,
+}
+
+impl Plugin for StoppablePlugin {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        self.stopped.lock().unwrap().push(self.metadata.name.clone());
+        Ok(())
+    }
+
+    fn stop_async(&mut self) -> amadeus::plugin::StopFuture<'_> {
+        if !self.hang {
+            let result = self.stop();
+            return Box::pin(async move { result });
+        }
+        Box::pin(async {
+            tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_hanging_stop_times_out_and_remaining_plugins_stop() -> anyhow::Result<()> {
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let plugin = |name: &str, hang: bool| StoppablePlugin {
+        metadata: PluginMetadata::new(name, "stop timeout test plugin", "0.1.0"),
+        hang,
+        stopped: stopped.clone(),
+    };
+
+    let mut registry = PluginRegistry::new();
+    registry.register(plugin("First", false));
+    registry.register(plugin("Hanging", true));
+    registry.register(plugin("Last", false));
+    registry.start_all()?;
+
+    let started = std::time::Instant::now();
+    tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        registry.shutdown_async(std::time::Duration::from_millis(100)),
+    )
+    .await??;
+
+    // Stop runs in reverse order and skips past the hanging plugin
+    assert_eq!(*stopped.lock().unwrap(), vec!["Last", "First"]);
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert!(!registry.is_initialized());
+    Ok(())
+}
+
+/// Relies on the default `stop_async`; its sync `stop` either blocks the thread or fails
+struct SyncStopPlugin {
+    metadata: PluginMetadata,
+    block_for: Option<std::time::Duration>,
+    fail: bool,
+    stopped: Arc<Mutex<Vec<String>>>,
+}
+
+impl Plugin for SyncStopPlugin {
+    fn id(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn metadata(&self) -> &PluginMetadata {
+        &self.metadata
+    }
+
+    fn stop(&mut self) -> anyhow::Result<()> {
+        if let Some(duration) = self.block_for {
+            std::thread::sleep(duration);
+        }
+        if self.fail {
+            anyhow::bail!("disk on fire");
+        }
+        self.stopped.lock().unwrap().push(self.metadata.name.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_blocking_and_failing_sync_stops_do_not_abort_shutdown() -> anyhow::Result<()> {
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let plugin = |name: &str, block_for: Option<u64>, fail: bool| SyncStopPlugin {
+        metadata: PluginMetadata::new(name, "sync stop test plugin", "0.1.0"),
+        block_for: block_for.map(std::time::Duration::from_millis),
+        fail,
+        stopped: stopped.clone(),
+    };
+
+    let mut registry = PluginRegistry::new();
+    registry.register(plugin("First", None, false));
+    registry.register(plugin("Failing", None, true));
+    registry.register(plugin("Blocking", Some(1000), false));
+    registry.register(plugin("Last", None, false));
+    registry.start_all()?;
+
+    let started = std::time::Instant::now();
+    let result = registry.shutdown_async(std::time::Duration::from_millis(100)).await;
+
+    // The blocked plugin is bounded by the timeout, the failure is reported after everyone stopped
+    assert!(started.elapsed() < std::time::Duration::from_millis(800));
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("Failing") && err.contains("disk on fire"), "{}", err);
+    assert_eq!(*stopped.lock().unwrap(), vec!["Last", "First"]);
+    assert!(!registry.is_initialized());
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use amadeus::plugins::core_system::scheduler::Scheduler;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_cron_task_panic_does_not_kill_schedule() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let (tx, _rx) = mpsc::channel(16);
+    let scheduler = Scheduler::new(tx).await?;
+    scheduler.start().await?;
+
+    let fires = Arc::new(AtomicUsize::new(0));
+    let fires_in_job = fires.clone();
+    scheduler
+        .add_cron_task("* * * * * *", move |_uuid| {
+            let fires = fires_in_job.clone();
+            async move {
+                // The first fire blows up, later ones succeed
+                if fires.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("simulated serialization bug");
+                }
+            }
+        })
+        .await?;
+
+    // Wait for the job to fire again after the panic
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while fires.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("job should keep firing after a panic");
+
+    assert_eq!(scheduler.panic_count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_fires_respect_limit() -> anyhow::Result<()> {
+    let (tx, _rx) = mpsc::channel(16);
+    let scheduler = Scheduler::new(tx).await?.with_max_concurrent_jobs(3);
+    scheduler.start().await?;
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let completed = Arc::new(AtomicUsize::new(0));
+
+    // Twenty jobs on the same schedule all become due in the same second
+    for _ in 0..20 {
+        let in_flight = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        let completed = completed.clone();
+        scheduler
+            .add_cron_task("* * * * * *", move |_uuid| {
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                let completed = completed.clone();
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    completed.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .await?;
+    }
+
+    // Every job still gets to run, just not all at once
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while completed.load(Ordering::SeqCst) < 20 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("queued fires should all complete");
+
+    let observed = max_in_flight.load(Ordering::SeqCst);
+    assert!(observed <= 3, "observed {} concurrent job bodies", observed);
+    assert!(observed >= 2, "jobs never overlapped, so the bound was not exercised");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_in_flight_fire() -> anyhow::Result<()> {
+    let (tx, mut rx) = mpsc::channel(16);
+    let scheduler = Scheduler::new(tx.clone()).await?;
+    scheduler.start().await?;
+
+    let started = Arc::new(tokio::sync::Notify::new());
+    let started_in_job = started.clone();
+    scheduler
+        .add_cron_task("* * * * * *", move |_uuid| {
+            let tx = tx.clone();
+            let started = started_in_job.clone();
+            async move {
+                started.notify_one();
+                // Still working when shutdown is requested
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                let _ = tx.send(amadeus::core::messaging::Message::new("test.fired", serde_json::json!({}))).await;
+            }
+        })
+        .await?;
+
+    tokio::time::timeout(Duration::from_secs(3), started.notified())
+        .await
+        .expect("job should fire");
+    assert_eq!(scheduler.in_flight(), 1);
+
+    scheduler.shutdown(Duration::from_secs(2)).await?;
+
+    // The fire finished sending before shutdown returned
+    assert_eq!(scheduler.in_flight(), 0);
+    let msg = rx.try_recv().expect("in-flight job should have sent its message");
+    assert_eq!(msg.message_type.as_str(), "test.fired");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shutdown_times_out_on_stuck_fire() -> anyhow::Result<()> {
+    let (tx, _rx) = mpsc::channel(16);
+    let scheduler = Scheduler::new(tx).await?;
+    scheduler.start().await?;
+
+    let started = Arc::new(tokio::sync::Notify::new());
+    let started_in_job = started.clone();
+    scheduler
+        .add_cron_task("* * * * * *", move |_uuid| {
+            let started = started_in_job.clone();
+            async move {
+                started.notify_one();
+                tokio::time::sleep(Duration::from_secs(30)).await;
+            }
+        })
+        .await?;
+
+    tokio::time::timeout(Duration::from_secs(3), started.notified())
+        .await
+        .expect("job should fire");
+
+    let err = scheduler.shutdown(Duration::from_millis(100)).await.unwrap_err();
+    assert!(err.to_string().contains("still running"), "unexpected error: {}", err);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_job_uuids_come_from_id_generator() -> anyhow::Result<()> {
+    use amadeus::core::messaging::Message;
+    use amadeus::plugins::core_system::scheduler::JOB_UUID_METADATA_KEY;
+    use amadeus::util::SequentialIdGenerator;
+
+    let (tx, mut rx) = mpsc::channel(16);
+    let scheduler = Scheduler::new(tx)
+        .await?
+        .with_id_generator(Arc::new(SequentialIdGenerator::new(1)));
+    scheduler.start().await?;
+
+    let cron = scheduler.add_cron_job("0 0 0 1 1 *", Message::new("test.cron", serde_json::json!({}))).await?;
+    let at = chrono::Utc::now().timestamp();
+    let one_shot = scheduler.add_one_shot_job(at, Message::new("test.once", serde_json::json!({}))).await?;
+    assert_eq!(cron, uuid::Uuid::from_u128(1));
+    assert_eq!(one_shot, uuid::Uuid::from_u128(2));
+
+    // The fired message carries the generated id too
+    let fired = tokio::time::timeout(Duration::from_secs(3), rx.recv()).await?.expect("one-shot should fire");
+    assert_eq!(fired.metadata[JOB_UUID_METADATA_KEY], "00000000-0000-0000-0000-000000000002");
+    Ok(())
+}
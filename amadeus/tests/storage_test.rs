@@ -0,0 +1,420 @@
+use amadeus::plugins::core_system::storage::Storage;
+use sqlx::sqlite::SqlitePoolOptions;
+
+async fn index_exists(pool: &sqlx::SqlitePool, name: &str) -> anyhow::Result<bool> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'index' AND name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.is_some())
+}
+
+#[tokio::test]
+async fn test_missing_index_is_repaired_on_startup() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_index_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    // A database migrated from the legacy schema, where the user/status index never got created
+    let storage = Storage::new(&db_url).await?;
+    sqlx::query("DROP INDEX idx_memos_user_status").execute(storage.pool()).await?;
+    assert!(!index_exists(storage.pool(), "idx_memos_user_status").await?);
+    storage.pool().close().await;
+
+    // Reopening verifies and repairs the indexes
+    let storage = Storage::new(&db_url).await?;
+    assert!(index_exists(storage.pool(), "idx_memos_user_status").await?);
+
+    // Explicit verification reports exactly what it repaired
+    sqlx::query("DROP INDEX idx_memos_user_status").execute(storage.pool()).await?;
+    assert_eq!(storage.verify_indexes().await?, vec!["idx_memos_user_status".to_string()]);
+    assert!(storage.verify_indexes().await?.is_empty());
+
+    storage.pool().close().await;
+    let pool = SqlitePoolOptions::new().connect(&db_url).await?;
+    assert!(index_exists(&pool, "idx_memos_user_status").await?);
+    pool.close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replica_serves_reads_while_writes_use_primary() -> anyhow::Result<()> {
+    let primary_path = std::env::temp_dir().join(format!("amadeus_primary_{}.db", uuid::Uuid::new_v4()));
+    let replica_path = std::env::temp_dir().join(format!("amadeus_replica_{}.db", uuid::Uuid::new_v4()));
+    let primary_url = format!("sqlite:{}", primary_path.display());
+    let replica_url = format!("sqlite:{}", replica_path.display());
+
+    // Seed the "replica" with a memo the primary doesn't have, so we can tell the pools apart
+    let seed = Storage::new(&replica_url).await?;
+    let replica_id = seed.add_memo("from replica", None, None, None, None, None, None, None).await?;
+    seed.pool().close().await;
+
+    let storage = Storage::new_with_replica(&primary_url, &replica_url).await?;
+    storage.add_memo("from primary", None, None, None, None, None, None, None).await?;
+
+    // Writes landed in the primary only
+    let (primary_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM memos").fetch_one(storage.pool()).await?;
+    assert_eq!(primary_count, 1);
+
+    // Reads are served by the replica
+    let listed = storage.query_memos(Default::default()).await?;
+    assert_eq!(listed.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), vec!["from replica"]);
+    assert_eq!(storage.get_memo(replica_id).await?.unwrap().content, "from replica");
+    assert_eq!(storage.memo_stats(None).await?.get("pending"), Some(&1));
+
+    // The replica pool is read-only
+    assert!(sqlx::query("DELETE FROM memos").execute(storage.read_pool()).await.is_err());
+
+    storage.pool().close().await;
+    storage.read_pool().close().await;
+    let _ = std::fs::remove_file(&primary_path);
+    let _ = std::fs::remove_file(&replica_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_same_file_replica_sees_committed_writes() -> anyhow::Result<()> {
+    let db_path = std::env::temp_dir().join(format!("amadeus_ro_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let storage = Storage::new_with_replica(&db_url, &db_url).await?;
+    let id = storage.add_memo("shared file", None, None, None, None, None, None, None).await?;
+    storage.update_memo_status(id, "completed").await?;
+
+    let memo = storage.get_memo(id).await?.expect("memo visible through read pool");
+    assert_eq!(memo.status, "completed");
+    assert_eq!(storage.memo_stats(None).await?.get("completed"), Some(&1));
+
+    storage.pool().close().await;
+    storage.read_pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_source_message_lookup_uses_index() -> anyhow::Result<()> {
+    let storage = Storage::new("sqlite::memory:").await?;
+    let plan: Vec<String> = sqlx::query(
+        "EXPLAIN QUERY PLAN SELECT id, content FROM memos WHERE json_extract(metadata, '$.source_message_id') = ? LIMIT 1",
+    )
+    .bind("create-1")
+    .fetch_all(storage.pool())
+    .await?
+    .iter()
+    .map(|row| sqlx::Row::get::<String, _>(row, "detail"))
+    .collect();
+    assert!(
+        plan.iter().any(|detail| detail.contains("idx_memos_source_message")),
+        "dedup lookup scans the table: {:?}",
+        plan
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_presence_filters_on_optional_fields() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let due = 4102444800_i64;
+    storage.add_memo("dated", None, None, None, Some(due), None, None, None).await?;
+    storage.add_memo("undated", None, None, None, None, None, None, None).await?;
+    storage.add_memo("recurring", None, Some("0 0 9 * * * *"), None, None, None, None, None).await?;
+    storage.add_memo("dated recurring", None, Some("0 0 9 * * * *"), None, Some(due), Some(3), None, None).await?;
+
+    let contents = |memos: Vec<amadeus::plugins::core_system::storage::types::MemoRecord>| {
+        let mut contents: Vec<String> = memos.into_iter().map(|m| m.content).collect();
+        contents.sort();
+        contents
+    };
+
+    let undated = storage.query_memos(MemoQueryParams { has_todo_date: Some(false), ..Default::default() }).await?;
+    assert_eq!(contents(undated), vec!["recurring", "undated"]);
+
+    let dated = storage.query_memos(MemoQueryParams { has_todo_date: Some(true), ..Default::default() }).await?;
+    assert_eq!(contents(dated), vec!["dated", "dated recurring"]);
+
+    let with_cron = storage.query_memos(MemoQueryParams { has_cron: Some(true), ..Default::default() }).await?;
+    assert_eq!(contents(with_cron), vec!["dated recurring", "recurring"]);
+
+    // Composes with the other filters
+    let combined = storage.query_memos(MemoQueryParams {
+        has_todo_date: Some(true),
+        has_cron: Some(true),
+        min_priority: Some(2),
+        ..Default::default()
+    }).await?;
+    assert_eq!(contents(combined), vec!["dated recurring"]);
+
+    let plain = storage.query_memos(MemoQueryParams { has_todo_date: Some(false), has_cron: Some(false), ..Default::default() }).await?;
+    assert_eq!(contents(plain), vec!["undated"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_keyset_cursor_pages_without_duplicates_or_skips() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+    use std::collections::HashSet;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let base = 4102444800_i64;
+    let mut expected = HashSet::new();
+    for i in 0..30_i64 {
+        // Shared dates and undated memos exercise the id tie-break and NULL ordering
+        let todo_date = if i % 5 == 0 { None } else { Some(base + (i % 4) * 86400) };
+        expected.insert(storage.add_memo(&format!("memo {}", i), None, None, None, todo_date, None, None, None).await?);
+    }
+
+    let mut seen = Vec::new();
+    let mut cursor = String::new();
+    let mut pages = 0;
+    loop {
+        let page = storage.query_memos_page(MemoQueryParams {
+            cursor: Some(cursor.clone()),
+            limit: Some(7),
+            ..Default::default()
+        }).await?;
+        seen.extend(page.memos.iter().map(|m| m.id));
+        pages += 1;
+
+        if pages == 2 {
+            // Inserted mid-scroll: one sorts before the current position, one after it
+            storage.add_memo("early", None, None, None, None, None, None, None).await?;
+            expected.insert(storage.add_memo("late", None, None, None, Some(base + 10 * 86400), None, None, None).await?);
+        }
+
+        match page.next_cursor {
+            Some(next) => cursor = next,
+            None => break,
+        }
+    }
+
+    assert_eq!(pages, 5);
+    let unique: HashSet<i64> = seen.iter().copied().collect();
+    assert_eq!(unique.len(), seen.len(), "duplicate ids across pages: {:?}", seen);
+    assert_eq!(unique, expected);
+
+    // Garbage cursors are rejected rather than silently restarting
+    assert!(storage.query_memos(MemoQueryParams { cursor: Some("not-a-cursor".into()), ..Default::default() }).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tags_are_normalized_on_write() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::{FieldUpdate, MemoQueryParams};
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let id = storage.add_memo("normalized", None, None, Some(r#"["Work", "work", " WORK "]"#), None, None, None, None).await?;
+    assert_eq!(storage.get_memo(id).await?.unwrap().tags, vec!["work"]);
+
+    // Updates are normalized as well, keeping first-seen order
+    storage.update_memo(id, None, FieldUpdate::Unchanged, FieldUpdate::Unchanged, Some(r#"[" Home", "work", "HOME", ""]"#), FieldUpdate::Unchanged, None).await?;
+    assert_eq!(storage.get_memo(id).await?.unwrap().tags, vec!["home", "work"]);
+
+    // Filters match regardless of the caller's casing
+    let found = storage.query_memos(MemoQueryParams { tags: Some(vec![" Home ".into()]), ..Default::default() }).await?;
+    assert_eq!(found.len(), 1);
+
+    // Normalization can be switched off to store tags verbatim
+    let raw = Storage::new("sqlite::memory:").await?.with_tag_normalization(false);
+    let id = raw.add_memo("verbatim", None, None, Some(r#"["Work", "work"]"#), None, None, None, None).await?;
+    assert_eq!(raw.get_memo(id).await?.unwrap().tags, vec!["Work", "work"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_cache_hits_and_invalidates_on_mutation() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+    use std::time::Duration;
+
+    let storage = Storage::new("sqlite::memory:").await?.with_query_cache(Duration::from_secs(60), 16);
+    let id = storage.add_memo("first", None, None, None, None, None, Some("alice"), None).await?;
+    storage.add_memo("bob's", None, None, None, None, None, Some("bob"), None).await?;
+
+    let alice = || MemoQueryParams { user_id: Some("alice".into()), ..Default::default() };
+    assert_eq!(storage.query_memos(alice()).await?.len(), 1);
+    assert_eq!(storage.query_memos(alice()).await?.len(), 1);
+    assert_eq!(storage.query_cache_hits(), 1);
+    assert_eq!(storage.query_cache_misses(), 1);
+
+    // Another user's writes leave alice's entry alone
+    storage.add_memo("bob's second", None, None, None, None, None, Some("bob"), None).await?;
+    storage.query_memos(alice()).await?;
+    assert_eq!(storage.query_cache_hits(), 2);
+
+    // Mutating one of alice's memos drops her cached list
+    storage.update_memo_status(id, "completed").await?;
+    let after = storage.query_memos(alice()).await?;
+    assert_eq!(after[0].status, "completed");
+    assert_eq!(storage.query_cache_hits(), 2);
+    assert_eq!(storage.query_cache_misses(), 2);
+
+    // A new memo for alice is visible immediately
+    storage.add_memo("second", None, None, None, None, None, Some("alice"), None).await?;
+    assert_eq!(storage.query_memos(alice()).await?.len(), 2);
+    assert_eq!(storage.query_cache_misses(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_query_cache_invalidated_by_metadata_update() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+    use std::time::Duration;
+
+    let storage = Storage::new("sqlite::memory:").await?.with_query_cache(Duration::from_secs(60), 16);
+    let id = storage.add_memo("first", None, None, None, None, None, Some("alice"), None).await?;
+
+    let alice = || MemoQueryParams { user_id: Some("alice".into()), ..Default::default() };
+    storage.query_memos(alice()).await?;
+    storage.update_memo_metadata(id, r#"{"tag":"new"}"#).await?;
+
+    // Metadata writes drop the owner's cached lists like any other memo mutation
+    storage.query_memos(alice()).await?;
+    assert_eq!(storage.query_cache_hits(), 0);
+    assert_eq!(storage.query_cache_misses(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_count_memos_matches_query_filters() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    for i in 0..6 {
+        let id = storage.add_memo(&format!("alice {}", i), None, None, None, None, Some(i % 3), Some("alice"), None).await?;
+        if i % 2 == 0 {
+            storage.update_memo_status(id, "completed").await?;
+        }
+    }
+    storage.add_memo("bob", None, None, None, None, None, Some("bob"), None).await?;
+
+    for params in [
+        MemoQueryParams { user_id: Some("alice".into()), status: Some("pending".into()), ..Default::default() },
+        MemoQueryParams { user_id: Some("alice".into()), min_priority: Some(1), ..Default::default() },
+        MemoQueryParams { status: Some("all".into()), ..Default::default() },
+    ] {
+        let listed = storage.query_memos(params.clone()).await?.len() as i64;
+        assert_eq!(storage.count_memos(&params).await?, listed);
+    }
+
+    // Pagination does not affect the total
+    let page = MemoQueryParams { user_id: Some("alice".into()), limit: Some(2), offset: Some(2), ..Default::default() };
+    assert_eq!(storage.count_memos(&page).await?, 6);
+    Ok(())
+}
+
+/// Benchmark-style check on a large table; run with `cargo test --test storage_test -- --ignored`
+#[tokio::test]
+#[ignore]
+async fn test_count_memos_user_status_on_large_table() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+    use std::time::{Duration, Instant};
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_count_{}.db", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&format!("sqlite:{}", db_path.display())).await?;
+
+    // 200k memos spread over 100 users and three statuses
+    sqlx::query(
+        "WITH RECURSIVE seq(n) AS (SELECT 0 UNION ALL SELECT n + 1 FROM seq WHERE n < 199999) \
+         INSERT INTO memos (content, created_at, status, priority, user_id) \
+         SELECT 'memo ' || n, n, CASE n % 3 WHEN 0 THEN 'pending' WHEN 1 THEN 'completed' ELSE 'expired' END, \
+                n % 4, 'user' || (n % 100) \
+         FROM seq"
+    )
+    .execute(storage.pool())
+    .await?;
+    sqlx::query("ANALYZE").execute(storage.pool()).await?;
+
+    let params = MemoQueryParams { user_id: Some("user7".into()), status: Some("pending".into()), ..Default::default() };
+    let started = Instant::now();
+    let count = storage.count_memos(&params).await?;
+    let elapsed = started.elapsed();
+
+    // n % 100 == 7 and n % 3 == 0: every 300th row starting at 207
+    assert_eq!(count, (0..200_000).filter(|n| n % 100 == 7 && n % 3 == 0).count() as i64);
+    assert!(elapsed < Duration::from_millis(200), "count took {:?}", elapsed);
+
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reorder_renumbers_when_gaps_run_out() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::{MemoQueryParams, MemoSortBy};
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let head = storage.add_memo("head", None, None, None, None, None, Some("alice"), None).await?;
+    let tail = storage.add_memo("tail", None, None, None, None, None, Some("alice"), None).await?;
+    storage.reorder_memo(head, None).await?;
+    storage.reorder_memo(tail, Some(head)).await?;
+
+    // Keep inserting right after the head until the floating-point gap is exhausted
+    let mut expected = vec![head];
+    let mut inserted = Vec::new();
+    for i in 0..80 {
+        let id = storage.add_memo(&format!("item {}", i), None, None, None, None, None, Some("alice"), None).await?;
+        storage.reorder_memo(id, Some(head)).await?;
+        inserted.push(id);
+    }
+    expected.extend(inserted.iter().rev());
+    expected.push(tail);
+
+    let listed: Vec<i64> = storage
+        .query_memos(MemoQueryParams { user_id: Some("alice".into()), sort_by: MemoSortBy::Manual, ..Default::default() })
+        .await?
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(listed, expected);
+
+    // Neighbours must share the owner
+    let other = storage.add_memo("bob's", None, None, None, None, None, Some("bob"), None).await?;
+    assert!(storage.reorder_memo(other, Some(head)).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reorder_skips_deleted_memos() -> anyhow::Result<()> {
+    let storage = Storage::new("sqlite::memory:").await?;
+    let first = storage.add_memo("first", None, None, None, None, None, Some("alice"), None).await?;
+    let gone = storage.add_memo("gone", None, None, None, None, None, Some("alice"), None).await?;
+    let last = storage.add_memo("last", None, None, None, None, None, Some("alice"), None).await?;
+    assert_eq!(storage.reorder_memo(first, None).await?, 1.0);
+    assert_eq!(storage.reorder_memo(gone, Some(first)).await?, 2.0);
+    assert_eq!(storage.reorder_memo(last, Some(gone)).await?, 3.0);
+    storage.update_memo_status(gone, "deleted").await?;
+
+    // Deleted memos can neither be moved nor used as a neighbour
+    assert!(storage.reorder_memo(gone, None).await.is_err());
+    let moved = storage.add_memo("moved", None, None, None, None, None, Some("alice"), None).await?;
+    assert!(storage.reorder_memo(moved, Some(gone)).await.is_err());
+
+    // The deleted row's slot does not count as the next neighbour
+    assert_eq!(storage.reorder_memo(moved, Some(first)).await?, 2.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_seeded_id_generator_gives_deterministic_user_ids() -> anyhow::Result<()> {
+    use amadeus::util::{IdGenerator, SeededIdGenerator};
+    use std::sync::Arc;
+
+    let storage = Storage::new("sqlite::memory:")
+        .await?
+        .with_id_generator(Arc::new(SeededIdGenerator::new(42)));
+    let alice = storage.create_user("alice", "test", "alice").await?;
+    let bob = storage.create_user("bob", "test", "bob").await?;
+
+    // The same seed replays the same ids, in order
+    let expected = SeededIdGenerator::new(42);
+    assert_eq!(alice.id.0, expected.next_uuid().to_string());
+    assert_eq!(bob.id.0, expected.next_uuid().to_string());
+    assert_eq!(uuid::Uuid::parse_str(&alice.id.0)?.get_version_num(), 4);
+
+    // Ids are also stable across runs, not just within one
+    assert_eq!(alice.id.0, "956eeb2f-2632-47bd-83f1-66b233e3ef28");
+    Ok(())
+}
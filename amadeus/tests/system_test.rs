@@ -2,6 +2,7 @@ use amadeus::plugin::PluginRegistry;
 use amadeus::plugins::core_system::CoreSystemPlugin;
 use amadeus::core::messaging::message_manager::MessageManager;
 use amadeus::core::messaging::message::Message;
+use amadeus::core::user::{PlatformId, PlatformUserId, UserContext, UserId, UserInfo};
 use std::time::Duration;
 
 #[tokio::test]
@@ -15,17 +16,18 @@ async fn test_todo_with_tags_lifecycle() -> anyhow::Result<()> {
     registry.register(CoreSystemPlugin::new(db_url));
 
     let mut message_manager = MessageManager::new();
+    registry.init_all()?;
     registry.setup_messaging(&message_manager).await?;
     message_manager.start_message_loop();
-    registry.startup()?;
+    registry.start_all()?;
 
     let dc = message_manager.distribution_center();
     let tx = message_manager.message_tx();
 
     // Subscriptions
-    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await;
-    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await;
-    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await;
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
 
     // 2. Create TODO with Tags and Schedule
     tracing::info!("--- Testing Create TODO with Tags ---");
@@ -35,7 +37,10 @@ async fn test_todo_with_tags_lifecycle() -> anyhow::Result<()> {
             "content": "Prepare for Interview",
             "cron": "1/1 * * * * *", // Main reminder every second
             "tags": ["work", "urgent", "stage_goal"],
-            "todo_date": 1700000000,
+            // Must lie in the future: the expiration sweep that runs at startup expires and then
+            // recycles pending memos whose todo_date has passed, so the 2023 date used originally
+            // (1700000000) removed the memo before it could be listed below
+            "todo_date": 4102444800_i64, // 2100-01-01
             "priority": 1
         })
     );
@@ -75,6 +80,7 @@ async fn test_todo_with_tags_lifecycle() -> anyhow::Result<()> {
         let tags = item["tags"].as_array().unwrap();
         assert!(tags.contains(&serde_json::json!("stage_goal")));
         assert_eq!(item["priority"], 1);
+        assert_eq!(item["priority_label"], "normal");
     } else {
         panic!("Failed to list memos");
     }
@@ -84,3 +90,1615 @@ async fn test_todo_with_tags_lifecycle() -> anyhow::Result<()> {
     message_manager.stop_message_loop().await;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_memo_handler_metrics() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    let core = CoreSystemPlugin::new("sqlite::memory:");
+    let metrics = core.metrics();
+    registry.register(core);
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_completed = dc.subscribe("system.memo.complete.success", "verifier").await?;
+    let mut rx_deleted = dc.subscribe("system.memo.delete.success", "verifier").await?;
+    let mut rx_metrics = dc.subscribe("system.memo.metrics.reply", "verifier").await?;
+
+    // Two creates, one complete, one delete
+    let mut ids = Vec::new();
+    for content in ["first", "second"] {
+        tx.send(Message::new("system.memo.create", serde_json::json!({ "content": content }))).await?;
+        let msg = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+        ids.push(msg.payload["id"].as_i64().unwrap());
+    }
+
+    tx.send(Message::new("system.memo.complete", serde_json::json!({ "id": ids[0] }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_completed.recv()).await??;
+
+    tx.send(Message::new("system.memo.delete", serde_json::json!({ "id": ids[1] }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_deleted.recv()).await??;
+
+    // Replies are sent from inside the handler, so give the timing wrapper a moment to record
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    tx.send(Message::new("system.memo.metrics", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_metrics.recv()).await??;
+    let m = &reply.payload["metrics"];
+
+    assert_eq!(m["system.memo.create"]["count"], 2);
+    assert_eq!(m["system.memo.complete"]["count"], 1);
+    assert_eq!(m["system.memo.delete"]["count"], 1);
+    assert!(m.get("system.memo.list").is_none());
+
+    // Histogram buckets add up to the count
+    let bucket_total: u64 = m["system.memo.create"]["histogram"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["count"].as_u64().unwrap())
+        .sum();
+    assert_eq!(bucket_total, 2);
+
+    // The shared struct exposes the same numbers
+    assert_eq!(metrics.count("system.memo.create"), 2);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memo_with_remind_at_and_cron_schedules_both() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_both_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    let remind_at = chrono::Utc::now().timestamp() + 1;
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({
+            "content": "Both reminders",
+            "remind_at": remind_at,
+            "cron": "0 0 0 1 1 *" // Yearly, will not fire during the test
+        })
+    )).await?;
+
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    // Both jobs are recorded in the memo metadata
+    let pool = sqlx::sqlite::SqlitePoolOptions::new().connect(&db_url).await?;
+    let (meta,): (String,) = sqlx::query_as("SELECT metadata FROM memos WHERE id = ?")
+        .bind(id)
+        .fetch_one(&pool)
+        .await?;
+    let meta: serde_json::Value = serde_json::from_str(&meta)?;
+    assert!(meta["job_uuid"].is_string(), "cron job should be registered");
+    assert!(meta["one_shot_job_uuid"].is_string(), "one-shot job should be registered");
+    assert_ne!(meta["job_uuid"], meta["one_shot_job_uuid"]);
+
+    // The one-shot actually fires
+    let remind = tokio::time::timeout(Duration::from_secs(4), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["id"], id);
+    assert_eq!(remind.payload["type"], "one_shot");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    pool.close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memo_parent_children() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+    let mut rx_deleted = dc.subscribe("system.memo.delete.success", "verifier").await?;
+
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Project" }))).await?;
+    let parent = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let parent_id = parent.payload["id"].as_i64().unwrap();
+
+    for content in ["Step 1", "Step 2"] {
+        tx.send(Message::new(
+            "system.memo.create",
+            serde_json::json!({ "content": content, "parent_id": parent_id })
+        )).await?;
+        tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    }
+
+    // Filtering by parent returns only the children
+    tx.send(Message::new("system.memo.list", serde_json::json!({ "parent_id": parent_id }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let children = reply.payload["memos"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+    assert!(children.iter().all(|m| m["parent_id"] == parent_id));
+
+    // The parent reports its child count
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let memos = reply.payload["memos"].as_array().unwrap();
+    let parent = memos.iter().find(|m| m["id"] == parent_id).unwrap();
+    assert_eq!(parent["children"], 2);
+
+    // Default policy detaches the children when the parent is deleted
+    tx.send(Message::new("system.memo.delete", serde_json::json!({ "id": parent_id }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_deleted.recv()).await??;
+
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let memos = reply.payload["memos"].as_array().unwrap();
+    assert_eq!(memos.iter().filter(|m| m["parent_id"].is_null()).count(), memos.len());
+    assert_eq!(memos.iter().filter(|m| m["status"] != "deleted").count(), 2);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_redelivered_create_is_deduplicated() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_create = dc.subscribe("system.memo.create", "verifier").await?;
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    let create = Message::new("system.memo.create", serde_json::json!({ "content": "Only once" }))
+        .with_id("create-1");
+
+    // First delivery
+    tx.send(create.clone()).await?;
+    let seen = tokio::time::timeout(Duration::from_secs(2), rx_create.recv()).await??;
+    assert_eq!(seen.delivery_attempt, 1);
+    let first = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+
+    // Redelivery of the same message_id
+    tx.send(create).await?;
+    let seen = tokio::time::timeout(Duration::from_secs(2), rx_create.recv()).await??;
+    assert_eq!(seen.delivery_attempt, 2);
+    let second = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    assert_eq!(second.payload["id"], first.payload["id"]);
+    assert_eq!(second.payload["duplicate"], true);
+
+    // Only one memo was stored
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert_eq!(reply.payload["memos"].as_array().unwrap().len(), 1);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_missed_one_shot_fires_late_on_reload() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::{CoreSystemConfig, MissedReminderPolicy};
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_missed_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    // Memos whose one-shot reminders passed while the process was down
+    let now = chrono::Utc::now().timestamp();
+    let storage = Storage::new(&db_url).await?;
+    let recent = storage.add_memo("Recently missed", Some(now - 60), None, None, None, None, None, None).await?;
+    let stale = storage.add_memo("Long gone", Some(now - 7200), None, None, None, None, None, None).await?;
+    drop(storage);
+
+    let mut config = CoreSystemConfig::default();
+    config.memos.missed_reminder_policy = MissedReminderPolicy::FireIfWithin(3600);
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url).with_config(config));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    let mut rx_remind = message_manager.distribution_center().subscribe("system.memo.remind", "verifier").await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let remind = tokio::time::timeout(Duration::from_secs(2), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["id"], recent);
+    assert_eq!(remind.payload["late"], true);
+    assert_eq!(remind.payload["type"], "one_shot");
+
+    // The reminder outside the grace window is not replayed
+    let next = tokio::time::timeout(Duration::from_millis(500), rx_remind.recv()).await;
+    assert!(next.is_err(), "item {} should not fire", stale);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_created_reply_includes_next_fire_time() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+
+    // Cron memo: next fire is at the top of the next hour
+    let now = chrono::Utc::now().timestamp();
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Hourly check", "cron": "0 0 * * * *" })
+    )).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let next = reply.payload["next_fire_time"].as_i64().expect("cron memo should report next_fire_time");
+    assert!(next > now && next <= now + 3600, "next_fire_time {} not within the next hour", next);
+    assert_eq!(next % 3600, 0);
+
+    // One-shot memo: next fire is the remind_at itself
+    let remind_at = now + 600;
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Call back", "remind_at": remind_at })
+    )).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    assert_eq!(reply.payload["next_fire_time"], remind_at);
+
+    // Plain memo: nothing scheduled
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Just a note" }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    assert!(reply.payload.get("next_fire_time").is_none());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+fn user_context(id: &str) -> UserContext {
+    UserContext::new(UserInfo {
+        id: UserId::new(id),
+        name: id.to_string(),
+        platform: PlatformId("cli".to_string()),
+        platform_user_id: PlatformUserId(id.to_string()),
+    })
+    .with_role("user")
+}
+
+#[tokio::test]
+async fn test_duplicate_memo_with_new_date() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_duplicated = dc.subscribe("system.memo.duplicated", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    let original_date = 4102444800_i64;
+    let new_date = original_date + 86400;
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Weekly review", "tags": ["work", "review"], "priority": 2, "todo_date": original_date })
+    ).with_user(user_context("alice"))).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let source_id = created.payload["id"].as_i64().unwrap();
+
+    tx.send(Message::new(
+        "system.memo.duplicate",
+        serde_json::json!({ "id": source_id, "overrides": { "todo_date": new_date } })
+    ).with_user(user_context("alice"))).await?;
+    let duplicated = tokio::time::timeout(Duration::from_secs(2), rx_duplicated.recv()).await??;
+    let clone_id = duplicated.payload["id"].as_i64().unwrap();
+    assert_ne!(clone_id, source_id);
+    assert_eq!(duplicated.payload["source_id"], source_id);
+
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let memos = reply.payload["memos"].as_array().unwrap();
+    let source = memos.iter().find(|m| m["id"] == source_id).unwrap();
+    let clone = memos.iter().find(|m| m["id"] == clone_id).unwrap();
+    assert_eq!(clone["tags"], serde_json::json!(["work", "review"]));
+    assert_eq!(clone["priority"], 2);
+    assert_eq!(clone["content"], "Weekly review");
+    assert_eq!(clone["todo_date"], new_date);
+    assert_eq!(clone["user_id"], "alice");
+    assert_eq!(source["todo_date"], original_date);
+
+    // Someone else's memo can't be duplicated
+    tx.send(Message::new(
+        "system.memo.duplicate",
+        serde_json::json!({ "id": source_id })
+    ).with_user(user_context("mallory"))).await?;
+    let denied = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(denied.payload["request"], "system.memo.duplicate");
+    assert_eq!(denied.payload["error"], "permission denied");
+    assert!(rx_duplicated.try_recv().is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_rejects_other_users_and_missing_items() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_updated = dc.subscribe("system.memo.update.success", "verifier").await?;
+    let mut rx_changed = dc.subscribe("system.memo.changed", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Alice's plan" }))
+        .with_user(user_context("alice"))).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+    tokio::time::timeout(Duration::from_secs(2), rx_changed.recv()).await??;
+
+    // Someone else can't edit it
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "content": "Mallory's plan" }))
+        .with_user(user_context("mallory"))).await?;
+    let denied = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(denied.payload["request"], "system.memo.update");
+    assert_eq!(denied.payload["id"], id);
+    assert_eq!(denied.payload["error"], "permission denied");
+
+    // A missing item is reported, not acknowledged
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": 9999, "content": "Ghost" }))
+        .with_user(user_context("alice"))).await?;
+    let missing = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(missing.payload["id"], 9999);
+    assert_eq!(missing.payload["error"], "item not found");
+
+    assert!(rx_updated.try_recv().is_err());
+    assert!(rx_changed.try_recv().is_err());
+
+    // The owner and admins still can
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "priority": 3 }))
+        .with_user(user_context("root").with_permission("system:admin"))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert_eq!(reply.payload["memos"][0]["content"], "Alice's plan");
+    assert_eq!(reply.payload["memos"][0]["priority"], 3);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memo_change_feed() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_changed = dc.subscribe("system.memo.changed", "verifier").await?;
+    let mut rx_updated = dc.subscribe("system.memo.update.success", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Draft", "tags": ["notes"], "todo_date": 4102444800_i64 })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_changed.recv()).await??;
+    assert_eq!(created.payload["change_type"], "created");
+    assert_eq!(created.payload["fields"]["content"], "Draft");
+    let id = created.payload["id"].as_i64().unwrap();
+
+    tx.send(Message::new(
+        "system.memo.update",
+        serde_json::json!({ "id": id, "content": "Final", "priority": 3 })
+    )).await?;
+    let updated = tokio::time::timeout(Duration::from_secs(2), rx_changed.recv()).await??;
+    assert_eq!(updated.payload["change_type"], "updated");
+    assert_eq!(updated.payload["id"], id);
+    // Only the fields that changed are reported
+    assert_eq!(updated.payload["fields"], serde_json::json!({ "content": "Final", "priority": 3 }));
+    tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    assert!(rx_changed.try_recv().is_err());
+
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let memo = &reply.payload["memos"][0];
+    assert_eq!(memo["content"], "Final");
+    assert_eq!(memo["priority"], 3);
+    assert_eq!(memo["tags"], serde_json::json!(["notes"]));
+
+    tx.send(Message::new("system.memo.complete", serde_json::json!({ "id": id }))).await?;
+    let completed = tokio::time::timeout(Duration::from_secs(2), rx_changed.recv()).await??;
+    assert_eq!(completed.payload["change_type"], "completed");
+    assert_eq!(completed.payload["fields"]["status"], "completed");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cron_fires_accumulate_in_reminder_log() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_reminder_log_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Every second", "cron": "*/1 * * * * *" })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    for _ in 0..3 {
+        tokio::time::timeout(Duration::from_secs(3), rx_remind.recv()).await??;
+    }
+
+    let storage = Storage::new(&db_url).await?;
+    let history = storage.reminder_history(id, 10).await?;
+    assert!(history.len() >= 3, "expected at least 3 logged fires, got {:?}", history);
+    assert!(history.iter().all(|entry| entry.memo_id == id && entry.job_uuid.is_some()));
+    assert!(history.windows(2).all(|w| w[0].fired_at >= w[1].fired_at));
+    // The limit is honoured
+    assert_eq!(storage.reminder_history(id, 2).await?.len(), 2);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_snooze_until_absolute_time() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_snoozed = dc.subscribe("system.memo.snoozed", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Call back" }))).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    // Times in the past are rejected
+    let now = chrono::Utc::now().timestamp();
+    tx.send(Message::new("system.memo.snooze_until", serde_json::json!({ "id": id, "at": now - 60 }))).await?;
+    let error = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(error.payload["request"], "system.memo.snooze_until");
+
+    // A first snooze (ISO form) is replaced by a second one (epoch form)
+    let first = chrono::DateTime::from_timestamp(now + 2, 0).unwrap().to_rfc3339();
+    tx.send(Message::new("system.memo.snooze_until", serde_json::json!({ "id": id, "at": first }))).await?;
+    let snoozed = tokio::time::timeout(Duration::from_secs(2), rx_snoozed.recv()).await??;
+    assert_eq!(snoozed.payload["until"], now + 2);
+
+    let until = now + 3;
+    tx.send(Message::new("system.memo.snooze_until", serde_json::json!({ "id": id, "at": until }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_snoozed.recv()).await??;
+
+    let remind = tokio::time::timeout(Duration::from_secs(6), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["id"], id);
+    assert_eq!(remind.payload["type"], "snooze");
+    assert_eq!(remind.payload["remind_at"], until);
+    assert!(chrono::Utc::now().timestamp() >= until);
+
+    // The cancelled snooze never fires
+    assert!(tokio::time::timeout(Duration::from_millis(1500), rx_remind.recv()).await.is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_notify_channel_routes_reminder_to_adapter() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_sms = dc.subscribe("system.notify.sms", "verifier").await?;
+    let mut rx_discord = dc.subscribe("system.notify.discord", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+
+    // Unknown adapters are rejected at creation
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Page me", "notify_channel": "pager" })
+    )).await?;
+    let rejected = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(rejected.payload["request"], "system.memo.create");
+    assert_eq!(rejected.payload["error"], "unknown notify_channel: pager");
+    assert!(rejected.payload["id"].is_null());
+    assert!(rx_created.try_recv().is_err());
+
+    let remind_at = chrono::Utc::now().timestamp() + 1;
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Server down", "remind_at": remind_at, "priority": 3, "notify_channel": "sms" })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    let routed = tokio::time::timeout(Duration::from_secs(5), rx_sms.recv()).await??;
+    assert_eq!(routed.payload["id"], id);
+    assert_eq!(routed.payload["type"], "one_shot");
+    assert_eq!(routed.payload["notify_channel"], "sms");
+
+    // Only the requested adapter receives it
+    assert!(rx_discord.try_recv().is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_changes_notify_channel() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_updated = dc.subscribe("system.memo.update.success", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Rotate keys", "notify_channel": "sms" }))).await?;
+    let id = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??.payload["id"].as_i64().unwrap();
+
+    // Unknown adapters are rejected on update too
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "notify_channel": "pager" }))).await?;
+    let rejected = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(rejected.payload["error"], "unknown notify_channel: pager");
+
+    // notify_channel alone is a valid update
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "notify_channel": "email" }))).await?;
+    let updated = tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    assert_eq!(updated.payload["fields"], serde_json::json!({ "notify_channel": "email" }));
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert_eq!(reply.payload["memos"][0]["notify_channel"], "email");
+
+    // null falls back to the owner's default channel
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "notify_channel": null }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert!(reply.payload["memos"][0]["notify_channel"].is_null());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reload_streams_active_reminders_in_pages() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_reload_pages_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let at = chrono::Utc::now().timestamp() + 3600;
+    let storage = Storage::new(&db_url).await?;
+    // An expired snooze drops out of the active set while the first page is processed
+    let expired = storage.add_memo("Expired snooze", None, None, None, None, None, None, None).await?;
+    storage.update_memo_metadata(expired, r#"{"snoozed_until": 1}"#).await?;
+    let mut ids = Vec::new();
+    for i in 0..25 {
+        ids.push(storage.add_memo(&format!("Reminder {}", i), Some(at), None, None, None, None, None, None).await?);
+    }
+
+    // Three pages of ten, observed through the storage API the reload uses
+    let mut pages = Vec::new();
+    let mut after_id = 0;
+    loop {
+        let page = storage.active_reminders_page(10, after_id).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after_id = last.id;
+        pages.push(page.len());
+    }
+    assert_eq!(pages, vec![10, 10, 6]);
+
+    let mut config = CoreSystemConfig::default();
+    config.scheduler.reload_page_size = 10;
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url).with_config(config));
+
+    let message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+
+    // Every reminder got a job, including the ones on later pages
+    for id in &ids {
+        let meta: serde_json::Value = serde_json::from_str(&storage.get_memo_metadata(*id).await?.unwrap())?;
+        assert!(meta["one_shot_job_uuid"].is_string(), "item {} was not reloaded", id);
+    }
+    let meta: serde_json::Value = serde_json::from_str(&storage.get_memo_metadata(expired).await?.unwrap())?;
+    assert!(meta["snoozed_until"].is_null());
+    assert_eq!(storage.active_reminders_page(100, 0).await?.len(), 25);
+
+    registry.shutdown()?;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reload_skips_past_rows_with_malformed_metadata() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_reload_malformed_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    // The first page is made up entirely of rows whose snooze cannot be parsed, so reloading leaves them untouched
+    let storage = Storage::new(&db_url).await?;
+    for _ in 0..2 {
+        let broken = storage.add_memo("Broken snooze", None, None, None, None, None, None, None).await?;
+        storage.update_memo_metadata(broken, r#"{"snoozed_until": "abc"}"#).await?;
+    }
+    let at = chrono::Utc::now().timestamp() + 3600;
+    let id = storage.add_memo("Reminder", Some(at), None, None, None, None, None, None).await?;
+
+    let mut config = CoreSystemConfig::default();
+    config.scheduler.reload_page_size = 2;
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url).with_config(config));
+
+    let message_manager = MessageManager::new();
+    registry.init_all()?;
+    tokio::time::timeout(Duration::from_secs(5), registry.setup_messaging(&message_manager))
+        .await
+        .expect("reload should not revisit the same page")?;
+
+    let meta: serde_json::Value = serde_json::from_str(&storage.get_memo_metadata(id).await?.unwrap())?;
+    assert!(meta["one_shot_job_uuid"].is_string());
+
+    registry.shutdown()?;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_memo_priority_accepts_label() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Ship release", "priority": "high", "remind_at": chrono::Utc::now().timestamp() + 1 })
+    )).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "Tidy desk", "priority": 2 }))).await?;
+    tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+
+    // Both forms end up as the same level, and replies carry the label too
+    tx.send(Message::new("system.memo.list", serde_json::json!({}))).await?;
+    let list = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let memos = list.payload["memos"].as_array().unwrap();
+    assert_eq!(memos.len(), 2);
+    for memo in memos {
+        assert_eq!(memo["priority"], 2);
+        assert_eq!(memo["priority_label"], "high");
+    }
+
+    let remind = tokio::time::timeout(Duration::from_secs(5), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["priority"], 2);
+    assert_eq!(remind.payload["priority_label"], "high");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[test]
+fn test_partial_config_falls_back_per_field() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+
+    // Only one customised priority; everything else is left out of the file
+    let content = r#"{
+        "memos": {
+            "priorities": {
+                "3": { "name": "Blocker", "color": "purple", "default_reminder_message": "BLOCKER: {content}" }
+            }
+        }
+    }"#;
+    let (config, defaulted) = CoreSystemConfig::from_json_with_defaults(content)?;
+    let defaults = CoreSystemConfig::default();
+
+    assert_eq!(config.memos.priorities[&3].name, "Blocker");
+    // The priorities map is taken as written, not merged entry by entry
+    assert_eq!(config.memos.priorities.len(), 1);
+    assert_eq!(config.memos.expiration_days, defaults.memos.expiration_days);
+    assert_eq!(config.memos.tag_schedules, defaults.memos.tag_schedules);
+    assert_eq!(config.scheduler.max_concurrent_jobs, defaults.scheduler.max_concurrent_jobs);
+
+    assert!(defaulted.contains(&"memos.expiration_days".to_string()));
+    assert!(defaulted.contains(&"memos.tag_schedules".to_string()));
+    assert!(defaulted.contains(&"scheduler".to_string()));
+    assert!(!defaulted.iter().any(|path| path.starts_with("memos.priorities")));
+    Ok(())
+}
+
+#[test]
+fn test_config_can_drop_default_tag_schedule() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+
+    // The operator replaced the default stage_goal schedule with their own
+    let content = r#"{ "memos": { "tag_schedules": { "weekly": "0 0 9 * * Mon" } } }"#;
+    let (config, defaulted) = CoreSystemConfig::from_json_with_defaults(content)?;
+
+    assert_eq!(config.memos.tag_schedules.len(), 1);
+    assert_eq!(config.memos.tag_schedules["weekly"], "0 0 9 * * Mon");
+    assert!(!config.memos.tag_schedules.contains_key("stage_goal"));
+    assert!(!defaulted.iter().any(|path| path.starts_with("memos.tag_schedules")));
+
+    // An explicitly empty map also stays empty
+    let (config, _) = CoreSystemConfig::from_json_with_defaults(r#"{ "memos": { "tag_schedules": {} } }"#)?;
+    assert!(config.memos.tag_schedules.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_validate_flags_bad_tag_cron_and_zero_expiration() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+
+    let content = r#"{
+        "memos": {
+            "expiration_days": 0,
+            "tag_schedules": { "stage_goal": "0 0 10 * * *", "weekly": "every monday" }
+        }
+    }"#;
+    let (config, _) = CoreSystemConfig::from_json_with_defaults(content)?;
+    let errors = config.validate().unwrap_err();
+
+    assert_eq!(errors.len(), 2, "unexpected errors: {:?}", errors);
+    assert_eq!(errors[0].field, "memos.tag_schedules.weekly");
+    assert!(
+        errors[0].to_string().starts_with("memos.tag_schedules.weekly: invalid cron expression 'every monday'"),
+        "imprecise message: {}",
+        errors[0]
+    );
+    assert_eq!(errors[1].field, "memos.expiration_days");
+
+    // The shipped defaults are valid
+    assert!(CoreSystemConfig::default().validate().is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_plugin_drops_only_invalid_config_fields() -> anyhow::Result<()> {
+    let config_path = std::env::temp_dir().join(format!("amadeus_config_{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(&config_path, r#"{
+        "memos": {
+            "expiration_days": 0,
+            "query_cache_ttl_secs": 60,
+            "tag_schedules": { "stage_goal": "0 0 10 * * *", "weekly": "every monday" }
+        },
+        "notifications": { "adapters": ["cli", "pager"], "default_channel": "pager" }
+    }"#)?;
+
+    let plugin = CoreSystemPlugin::from_config_file("sqlite::memory:", &config_path);
+    let config = plugin.config();
+
+    // The bad cron and the out-of-range number are dropped or defaulted...
+    assert!(!config.memos.tag_schedules.contains_key("weekly"));
+    assert_eq!(config.memos.expiration_days, 30);
+    // ...while everything valid the operator wrote is kept
+    assert_eq!(config.memos.tag_schedules["stage_goal"], "0 0 10 * * *");
+    assert_eq!(config.memos.query_cache_ttl_secs, Some(60));
+    assert_eq!(config.notifications.adapters, vec!["cli", "pager"]);
+    assert_eq!(config.notifications.default_channel.as_deref(), Some("pager"));
+    assert!(config.validate().is_ok());
+
+    let _ = std::fs::remove_file(&config_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_update_with_null_clears_cron_and_removes_job() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_clear_cron_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_updated = dc.subscribe("system.memo.update.success", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Stand up", "cron": "* * * * * *", "todo_date": 4102444800_i64 })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+    tokio::time::timeout(Duration::from_secs(3), rx_remind.recv()).await??;
+
+    // An explicit null clears the field, an omitted one is left alone
+    tx.send(Message::new("system.memo.update", serde_json::json!({ "id": id, "cron": null }))).await?;
+    let updated = tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    assert_eq!(updated.payload["fields"], serde_json::json!({ "cron": null }));
+
+    let storage = Storage::new(&db_url).await?;
+    let memo = storage.get_memo(id).await?.unwrap();
+    assert!(memo.cron_pattern.is_none());
+    assert_eq!(memo.todo_date, Some(4102444800));
+    let meta: serde_json::Value = serde_json::from_str(&storage.get_memo_metadata(id).await?.unwrap())?;
+    assert!(meta["job_uuid"].is_null());
+
+    // The cron job is gone: no more fires once in-flight ones have drained
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    while rx_remind.try_recv().is_ok() {}
+    assert!(tokio::time::timeout(Duration::from_millis(1500), rx_remind.recv()).await.is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_remind_now_fires_templated_reminder() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.memo.error", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    // No schedule at all: the reminder only fires on demand
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "renew passport", "priority": 3 })
+    ).with_user(user_context("alice"))).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    // Someone else's memo cannot be triggered
+    tx.send(Message::new("system.memo.remind_now", serde_json::json!({ "id": id })).with_user(user_context("bob"))).await?;
+    let error = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(error.payload["request"], "system.memo.remind_now");
+    assert_eq!(error.payload["error"], "permission denied");
+    assert!(rx_remind.try_recv().is_err());
+
+    tx.send(Message::new("system.memo.remind_now", serde_json::json!({ "id": id })).with_user(user_context("alice"))).await?;
+    let remind = tokio::time::timeout(Duration::from_millis(500), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["id"], id);
+    assert_eq!(remind.payload["type"], "manual");
+    assert_eq!(remind.payload["message"], "URGENT: renew passport is due!");
+    assert_eq!(remind.payload["priority_label"], "critical");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_warns_about_invalid_tag_schedule() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut config = CoreSystemConfig::default();
+    config.memos.tag_schedules.insert("weekly_review".to_string(), "every friday".to_string());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:").with_config(config));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+
+    // The memo is still created, but the reply says the tag reminder was not scheduled
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "review the week", "tags": ["weekly_review"] })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    assert!(created.payload["id"].as_i64().is_some());
+    let warnings = created.payload["warnings"].as_array().expect("warnings should be reported");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().contains("weekly_review"));
+
+    // Valid tag schedules and known priorities produce no warnings
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "quarterly goal", "tags": ["stage_goal"], "priority": 2 })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    assert!(created.payload.get("warnings").is_none());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tag_schedules_match_normalized_tags() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut config = CoreSystemConfig::default();
+    config.memos.tag_schedules.insert("stage_goal".to_string(), "1/1 * * * * *".to_string());
+    config.memos.tag_schedules.insert("weekly_review".to_string(), "every friday".to_string());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:").with_config(config));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    // Tags are stored normalized, so the schedules must match them the same way
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "quarterly goal", "tags": ["Stage_Goal ", " Weekly_Review"] })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+    let warnings = created.payload["warnings"].as_array().expect("warnings should be reported");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().contains("weekly_review"));
+
+    let remind = tokio::time::timeout(Duration::from_secs(3), rx_remind.recv()).await??;
+    assert_eq!(remind.payload["id"], id);
+    assert_eq!(remind.payload["type"], "tag_reminder");
+    assert_eq!(remind.payload["tag"], "stage_goal");
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_reply_reports_total_across_pages() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    for i in 0..3 {
+        tx.send(Message::new("system.memo.create", serde_json::json!({ "content": format!("memo {}", i) }))).await?;
+        tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    }
+
+    // Offset pagination: one page, but the total covers every match
+    tx.send(Message::new("system.memo.list", serde_json::json!({ "limit": 2 }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert_eq!(reply.payload["memos"].as_array().unwrap().len(), 2);
+    assert_eq!(reply.payload["total"], 3);
+
+    // Keyset pagination reports the same total
+    tx.send(Message::new("system.memo.list", serde_json::json!({ "limit": 2, "cursor": "" }))).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    assert_eq!(reply.payload["memos"].as_array().unwrap().len(), 2);
+    assert!(reply.payload["next_cursor"].is_string());
+    assert_eq!(reply.payload["total"], 3);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[test]
+fn test_handler_logs_redact_sensitive_payloads() -> anyhow::Result<()> {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let logs = Capture::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .finish();
+    let logged = || String::from_utf8_lossy(&logs.0.lock().unwrap()).into_owned();
+
+    // A current-thread runtime keeps the handler tasks on this thread, under the capturing subscriber
+    tracing::subscriber::with_default(subscriber, || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(async {
+            let mut registry = PluginRegistry::new();
+            registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+            let mut message_manager = MessageManager::new();
+            registry.init_all()?;
+            registry.setup_messaging(&message_manager).await?;
+            message_manager.start_message_loop();
+            registry.start_all()?;
+
+            let dc = message_manager.distribution_center();
+            let tx = message_manager.message_tx();
+            let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+
+            tx.send(Message::new("system.memo.create", serde_json::json!({ "content": "see the doctor" }))).await?;
+            tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+
+            tx.send(Message::new(
+                "system.user.resolve",
+                serde_json::json!({ "platform": "discord", "platform_user_id": "8675309", "name": "Jenny" })
+            )).await?;
+            tokio::time::timeout(Duration::from_secs(2), async {
+                while !logged().contains("Creating new user") {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            }).await?;
+
+            registry.shutdown()?;
+            message_manager.stop_message_loop().await;
+            anyhow::Ok(())
+        })
+    })?;
+
+    let logged = logged();
+    assert!(logged.contains("Creating item: [system.memo.create]"), "{}", logged);
+    assert!(!logged.contains("see the doctor"), "{}", logged);
+    assert!(!logged.contains("8675309"), "{}", logged);
+    assert!(!logged.contains("Jenny"), "{}", logged);
+
+    // Debug output of a message follows the same rules
+    let msg = Message::new("system.memo.create", serde_json::json!({ "content": "see the doctor", "priority": 2 }));
+    let debugged = format!("{:?}", msg);
+    assert!(!debugged.contains("doctor"), "{}", debugged);
+    assert!(debugged.contains("\"priority\": Number(2)"), "{}", debugged);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_today_uses_the_users_timezone() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+    use chrono::TimeZone;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_today_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    // UTC-10 all year: late evening locally is already tomorrow in UTC
+    let tz = chrono_tz::Pacific::Honolulu;
+    let storage = Storage::new(&db_url).await?;
+    let ivan = storage.create_user("ivan", "cli", "9").await?;
+    storage.set_pref(&ivan.id.0, "timezone", &serde_json::json!("Pacific/Honolulu")).await?;
+    let ivan_ctx = storage.get_user_context(&ivan.id.0).await?.unwrap();
+
+    let today = chrono::Utc::now().with_timezone(&tz).date_naive();
+    let at = |date: chrono::NaiveDate, h, m| tz.from_local_datetime(&date.and_hms_opt(h, m, 0).unwrap()).unwrap().timestamp();
+    let late_tonight = storage.add_memo("late tonight", None, None, None, Some(at(today, 23, 30)), None, Some(&ivan.id.0), None).await?;
+    let this_morning = storage.add_memo("this morning", None, None, None, Some(at(today, 0, 15)), None, Some(&ivan.id.0), None).await?;
+    storage.add_memo("tomorrow", None, None, None, Some(at(today.succ_opt().unwrap(), 0, 30)), None, Some(&ivan.id.0), None).await?;
+    storage.add_memo("yesterday", None, None, None, Some(at(today.pred_opt().unwrap(), 23, 0)), None, Some(&ivan.id.0), None).await?;
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_today = dc.subscribe("system.memo.today.reply", "verifier").await?;
+
+    tx.send(Message::new("system.memo.today", serde_json::json!({})).with_user(ivan_ctx)).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_today.recv()).await??;
+    assert_eq!(reply.payload["timezone"], "Pacific/Honolulu");
+    assert_eq!(reply.payload["date"], today.to_string());
+
+    let mut ids: Vec<i64> = reply.payload["memos"].as_array().unwrap().iter().map(|m| m["id"].as_i64().unwrap()).collect();
+    ids.sort();
+    assert_eq!(ids, vec![late_tonight, this_morning]);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fired_reminder_carries_memo_and_job_correlation() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_correlation_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+    let mut rx_cli = dc.subscribe("system.notify.cli", "verifier").await?;
+
+    let remind_at = chrono::Utc::now().timestamp() + 1;
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "stretch", "remind_at": remind_at, "notify_channel": "cli" })
+    )).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    let remind = tokio::time::timeout(Duration::from_secs(5), rx_remind.recv()).await??;
+    assert_eq!(remind.metadata["caused_by_memo"], id.to_string());
+    let job_uuid = remind.metadata["job_uuid"].clone();
+    assert!(uuid::Uuid::parse_str(&job_uuid).is_ok());
+
+    // The routed notification keeps the correlation, and it matches the reminder log
+    let routed = tokio::time::timeout(Duration::from_secs(2), rx_cli.recv()).await??;
+    assert_eq!(routed.metadata["caused_by_memo"], id.to_string());
+    assert_eq!(routed.metadata["job_uuid"], job_uuid);
+
+    let storage = Storage::new(&db_url).await?;
+    let history = storage.reminder_history(id, 10).await?;
+    assert_eq!(history[0].job_uuid.as_deref(), Some(job_uuid.as_str()));
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reorder_places_memo_between_neighbors() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_reordered = dc.subscribe("system.memo.reordered", "verifier").await?;
+    let mut rx_list = dc.subscribe("system.memo.list.reply", "verifier").await?;
+
+    let mut ids = Vec::new();
+    for content in ["first", "second", "third"] {
+        tx.send(Message::new("system.memo.create", serde_json::json!({ "content": content })).with_user(user_context("alice"))).await?;
+        let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+        ids.push(created.payload["id"].as_i64().unwrap());
+    }
+    let (first, second, third) = (ids[0], ids[1], ids[2]);
+
+    // Lay out first, second, third, then drag third between first and second
+    let mut orders = std::collections::HashMap::new();
+    for (id, after_id) in [(first, None), (second, Some(first)), (third, Some(second)), (third, Some(first))] {
+        tx.send(Message::new("system.memo.reorder", serde_json::json!({ "id": id, "after_id": after_id })).with_user(user_context("alice"))).await?;
+        let reordered = tokio::time::timeout(Duration::from_secs(2), rx_reordered.recv()).await??;
+        assert_eq!(reordered.payload["id"], id);
+        orders.insert(id, reordered.payload["sort_order"].as_f64().unwrap());
+    }
+    assert!(orders[&first] < orders[&third] && orders[&third] < orders[&second]);
+
+    tx.send(Message::new(
+        "system.memo.list",
+        serde_json::json!({ "sort_by": "manual" })
+    ).with_user(user_context("alice"))).await?;
+    let list = tokio::time::timeout(Duration::from_secs(2), rx_list.recv()).await??;
+    let listed: Vec<i64> = list.payload["memos"].as_array().unwrap().iter().map(|m| m["id"].as_i64().unwrap()).collect();
+    assert_eq!(listed, vec![first, third, second]);
+    // Only the moved memo was rewritten
+    assert_eq!(list.payload["memos"][2]["sort_order"].as_f64(), Some(orders[&second]));
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_backup_snapshots_live_database() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::types::MemoQueryParams;
+    use amadeus::plugins::core_system::storage::Storage;
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_backup_src_{}.db", uuid::Uuid::new_v4()));
+    let backup_path = std::env::temp_dir().join(format!("amadeus_backup_copy_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_backup = dc.subscribe("system.backup.created", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.backup.error", "verifier").await?;
+
+    for content in ["first", "second"] {
+        tx.send(Message::new("system.memo.create", serde_json::json!({ "content": content })).with_user(user_context("alice"))).await?;
+        tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    }
+
+    // Regular users cannot take backups
+    let request = serde_json::json!({ "path": backup_path.to_string_lossy() });
+    tx.send(Message::new("system.backup.create", request.clone()).with_user(user_context("alice"))).await?;
+    let rejected = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert!(rejected.payload["error"].as_str().unwrap().contains("permission denied"));
+    assert!(!backup_path.exists());
+
+    let admin = user_context("root").with_permission("system:admin");
+    tx.send(Message::new("system.backup.create", request).with_user(admin)).await?;
+    let created = tokio::time::timeout(Duration::from_secs(5), rx_backup.recv()).await??;
+    assert_eq!(created.payload["path"], backup_path.to_string_lossy().as_ref());
+    assert_eq!(created.payload["size"].as_u64(), Some(std::fs::metadata(&backup_path)?.len()));
+
+    // The copy opens on its own and holds the memos
+    let copy = Storage::new(&format!("sqlite:{}", backup_path.display())).await?;
+    let memos = copy.query_memos(MemoQueryParams { user_id: Some("alice".to_string()), ..Default::default() }).await?;
+    let mut contents: Vec<_> = memos.iter().map(|m| m.content.as_str()).collect();
+    contents.sort();
+    assert_eq!(contents, vec!["first", "second"]);
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    copy.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    let _ = std::fs::remove_file(&backup_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schedule_add_with_memo_id_uses_memo_template() -> anyhow::Result<()> {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new("sqlite::memory:"));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_created = dc.subscribe("system.memo.created", "verifier").await?;
+    let mut rx_added = dc.subscribe("system.schedule.added", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.schedule.error", "verifier").await?;
+    let mut rx_remind = dc.subscribe("system.memo.remind", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.memo.create",
+        serde_json::json!({ "content": "Pay rent", "priority": "critical" })
+    ).with_user(user_context("alice"))).await?;
+    let created = tokio::time::timeout(Duration::from_secs(2), rx_created.recv()).await??;
+    let id = created.payload["id"].as_i64().unwrap();
+
+    // Someone else cannot attach jobs to alice's memo
+    tx.send(Message::new("system.schedule.add", serde_json::json!({ "cron": "* * * * * *", "memo_id": id }))
+        .with_user(user_context("mallory"))).await?;
+    let rejected = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(rejected.payload["error"], "permission denied");
+
+    tx.send(Message::new("system.schedule.add", serde_json::json!({ "cron": "* * * * * *", "memo_id": id }))
+        .with_user(user_context("alice"))).await?;
+    let added = tokio::time::timeout(Duration::from_secs(2), rx_added.recv()).await??;
+    assert_eq!(added.payload["memo_id"], id);
+
+    // The fired reminder is built like the memo's own: critical priority template and correlation
+    let fired = tokio::time::timeout(Duration::from_secs(3), rx_remind.recv()).await??;
+    assert_eq!(fired.payload["type"], "scheduled");
+    assert_eq!(fired.payload["id"], id);
+    assert_eq!(fired.payload["message"], "URGENT: Pay rent is due!");
+    assert_eq!(fired.payload["priority_label"], "critical");
+    assert_eq!(fired.metadata["caused_by_memo"], id.to_string());
+    assert_eq!(fired.metadata["job_uuid"], added.payload["uuid"].as_str().unwrap());
+
+    // Ad-hoc cron + message jobs keep working
+    tx.send(Message::new("system.schedule.add", serde_json::json!({
+        "cron": "0 0 0 1 1 *",
+        "message": Message::new("plugin.custom.tick", serde_json::json!({}))
+    }))).await?;
+    let adhoc = tokio::time::timeout(Duration::from_secs(2), rx_added.recv()).await??;
+    assert!(adhoc.payload.get("memo_id").is_none());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_digest_mode_coalesces_reminders_per_user() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::config::CoreSystemConfig;
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_digest_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let storage = Storage::new(&db_url).await?;
+    let alice = storage.create_user("alice", "cli", "1").await?;
+    let bob = storage.create_user("bob", "cli", "2").await?;
+    storage.set_pref(&alice.id.0, "reminder_mode", &serde_json::json!("digest")).await?;
+    let mut alice_ids = Vec::new();
+    for content in ["standup", "review", "deploy"] {
+        alice_ids.push(storage.add_memo(content, None, None, None, None, None, Some(&alice.id.0), None).await?);
+    }
+    let bob_id = storage.add_memo("lunch", None, None, None, None, None, Some(&bob.id.0), None).await?;
+
+    let mut config = CoreSystemConfig::default();
+    config.notifications.default_channel = Some("cli".to_string());
+    config.notifications.digest_window_ms = 300;
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url).with_config(config));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_digest = dc.subscribe("system.memo.remind.digest", "verifier").await?;
+    let mut rx_cli = dc.subscribe("system.notify.cli", "verifier").await?;
+
+    // Alice's three reminders fire together; bob (individual mode) gets his straight away
+    for id in alice_ids.iter().copied().chain([bob_id]) {
+        tx.send(Message::new("system.memo.remind", serde_json::json!({ "id": id, "type": "primary" }))).await?;
+    }
+
+    let individual = tokio::time::timeout(Duration::from_secs(2), rx_cli.recv()).await??;
+    assert_eq!(individual.payload["id"], bob_id);
+
+    let digest = tokio::time::timeout(Duration::from_secs(2), rx_digest.recv()).await??;
+    assert_eq!(digest.payload["user_id"], alice.id.0.as_str());
+    assert_eq!(digest.payload["count"], 3);
+    let items: Vec<i64> = digest.payload["items"].as_array().unwrap().iter().map(|i| i["id"].as_i64().unwrap()).collect();
+    assert_eq!(items, alice_ids);
+
+    // The adapter receives one digest instead of three reminders
+    let routed = tokio::time::timeout(Duration::from_secs(2), rx_cli.recv()).await??;
+    assert_eq!(routed.payload["type"], "digest");
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(rx_cli.try_recv().is_err());
+    assert!(rx_digest.try_recv().is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
@@ -35,9 +35,9 @@ async fn test_user_system_flow() -> anyhow::Result<()> {
     // 使用 amadeus::plugins::message_example::MessageExamplePlugin;
     // registry.register(Box::new(MessageExamplePlugin::default()));
 
+    registry.init_all()?;
     registry.setup_messaging(&message_manager).await?;
     message_manager.start_message_loop();
-    registry.init_all()?;
     registry.start_all()?;
     
     // 2. 模拟外部适配器发送 resolve 请求
@@ -104,3 +104,279 @@ async fn test_user_system_flow() -> anyhow::Result<()> {
     Ok(())
 }
 
+
+#[tokio::test]
+async fn test_users_with_role_expands_inheritance() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+
+    let alice = storage.create_user("alice", "discord", "1").await?;
+    let bob = storage.create_user("bob", "qq", "2").await?;
+    let carol = storage.create_user("carol", "cli", "3").await?;
+    let dave = storage.create_user("dave", "cli", "4").await?;
+
+    // editor inherits viewer, admin inherits editor
+    storage.add_role_inheritance("editor", "viewer").await?;
+    storage.add_role_inheritance("admin", "editor").await?;
+
+    storage.add_role_to_user(&alice.id.0, "viewer").await?;
+    storage.add_role_to_user(&bob.id.0, "editor").await?;
+    storage.add_role_to_user(&carol.id.0, "admin").await?;
+    storage.add_role_to_user(&dave.id.0, "user").await?;
+    // A user holding several matching roles is only listed once
+    storage.add_role_to_user(&carol.id.0, "viewer").await?;
+
+    let names = |users: Vec<amadeus::core::UserInfo>| {
+        let mut names: Vec<String> = users.into_iter().map(|u| u.name).collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(names(storage.users_with_role("viewer").await?), vec!["alice", "bob", "carol"]);
+    assert_eq!(names(storage.users_with_role("editor").await?), vec!["bob", "carol"]);
+    assert_eq!(names(storage.users_with_role("admin").await?), vec!["carol"]);
+    assert_eq!(names(storage.users_with_role("user").await?), vec!["dave"]);
+    assert!(storage.users_with_role("nobody").await?.is_empty());
+
+    // Inherited roles also contribute permissions to the user context
+    storage.add_permission_to_role("viewer", "memo:read").await?;
+    let ctx = storage.get_user_context(&bob.id.0).await?.unwrap();
+    assert!(ctx.has_permission("memo:read"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_direct_awaits_user_resolved() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_context::MessageContext;
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use amadeus::plugin::{Plugin, PluginRegistry};
+    use amadeus::plugins::core_system::CoreSystemPlugin;
+    use std::sync::Arc;
+
+    let mut message_manager = MessageManager::new();
+    let mut registry = PluginRegistry::new();
+
+    let core = CoreSystemPlugin::new("sqlite::memory:");
+    let core_uid = core.uid().to_string();
+    registry.register(core);
+
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    // Act as an adapter plugin talking to CoreSystem
+    let adapter = MessageContext::new(
+        Arc::clone(message_manager.distribution_center()),
+        "TestAdapter",
+        "test-adapter-uid",
+        message_manager.message_tx(),
+    );
+
+    let request = Message::new(
+        "system.user.resolve",
+        serde_json::json!({ "platform": "discord", "platform_user_id": "42", "name": "Direct User" })
+    );
+    let reply = adapter.request_direct(&core_uid, request, Duration::from_secs(2)).await?;
+
+    assert_eq!(reply.message_type.as_str(), "system.user.resolved");
+    assert_eq!(reply.payload["user"]["name"], "Direct User");
+
+    // The temporary reply channel is gone once the request resolved
+    let stray = Message::new("system.user.resolved", serde_json::json!({}));
+    assert!(message_manager
+        .distribution_center()
+        .send_direct(&format!("test-adapter-uid#{}", reply.message_id.unwrap()), stray)
+        .await
+        .is_err());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_overrides_wildcard_grant() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let erin = storage.create_user("erin", "cli", "5").await?;
+
+    storage.add_permission_to_role("moderator", "memo:*").await?;
+    storage.deny_permission_to_role("moderator", "memo:delete").await?;
+    storage.add_role_to_user(&erin.id.0, "moderator").await?;
+
+    let ctx = storage.get_user_context(&erin.id.0).await?.unwrap();
+    assert!(ctx.has_permission("memo:read"));
+    assert!(ctx.has_permission("memo:create"));
+    assert!(!ctx.has_permission("memo:delete"));
+    assert!(ctx.is_denied("memo:delete"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_specific_grant_loses_to_inherited_deny() -> anyhow::Result<()> {
+    use amadeus::plugins::core_system::storage::Storage;
+
+    let storage = Storage::new("sqlite::memory:").await?;
+    let frank = storage.create_user("frank", "cli", "6").await?;
+
+    // "contractor" explicitly grants memo:delete, but inherits a blanket deny on memo:*
+    storage.add_permission_to_role("contractor", "memo:delete").await?;
+    storage.deny_permission_to_role("restricted", "memo:*").await?;
+    storage.add_role_inheritance("contractor", "restricted").await?;
+    storage.add_permission_to_role("contractor", "report:read").await?;
+    storage.add_role_to_user(&frank.id.0, "contractor").await?;
+
+    let ctx = storage.get_user_context(&frank.id.0).await?.unwrap();
+    assert!(!ctx.has_permission("memo:delete"));
+    assert!(!ctx.has_permission("memo:read"));
+    assert!(ctx.has_permission("report:read"));
+
+    Ok(())
+}
+
+#[test]
+fn test_deny_is_absolute_for_admin() {
+    use amadeus::core::user::{PlatformId, PlatformUserId, UserContext, UserId, UserInfo};
+
+    let ctx = UserContext::new(UserInfo {
+        id: UserId::new("root-user"),
+        name: "root".to_string(),
+        platform: PlatformId("cli".to_string()),
+        platform_user_id: PlatformUserId("0".to_string()),
+    })
+    .with_role("admin")
+    .with_denied_permission("system:shutdown");
+
+    assert!(ctx.has_permission("memo:delete"));
+    assert!(!ctx.has_permission("system:shutdown"));
+}
+
+#[tokio::test]
+async fn test_user_prefs_round_trip_and_validation() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use amadeus::plugin::PluginRegistry;
+    use amadeus::plugins::core_system::storage::Storage;
+    use amadeus::plugins::core_system::CoreSystemPlugin;
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_prefs_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let storage = Storage::new(&db_url).await?;
+    let grace = storage.create_user("grace", "cli", "7").await?;
+    let heidi = storage.create_user("heidi", "cli", "8").await?;
+    storage.add_role_to_user(&grace.id.0, "user").await?;
+    let grace_ctx = storage.get_user_context(&grace.id.0).await?.unwrap();
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let tx = message_manager.message_tx();
+    let mut rx_updated = dc.subscribe("system.user.prefs.updated", "verifier").await?;
+    let mut rx_reply = dc.subscribe("system.user.prefs.reply", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.user.error", "verifier").await?;
+
+    tx.send(Message::new(
+        "system.user.prefs.set",
+        serde_json::json!({ "key": "timezone", "value": "Asia/Shanghai" })
+    ).with_user(grace_ctx.clone())).await?;
+    let updated = tokio::time::timeout(Duration::from_secs(2), rx_updated.recv()).await??;
+    assert_eq!(updated.payload["user_id"], grace.id.0);
+
+    tx.send(Message::new("system.user.prefs.get", serde_json::json!({})).with_user(grace_ctx.clone())).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_reply.recv()).await??;
+    assert_eq!(reply.payload["prefs"]["timezone"], "Asia/Shanghai");
+
+    // An unknown timezone is rejected and the stored value is untouched
+    tx.send(Message::new(
+        "system.user.prefs.set",
+        serde_json::json!({ "key": "timezone", "value": "Mars/Olympus" })
+    ).with_user(grace_ctx.clone())).await?;
+    let error = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(error.payload["request"], "system.user.prefs.set");
+    assert!(error.payload["error"].as_str().unwrap().contains("Mars/Olympus"));
+
+    // Non-admins cannot write someone else's preferences
+    tx.send(Message::new(
+        "system.user.prefs.set",
+        serde_json::json!({ "user_id": heidi.id.0, "key": "timezone", "value": "UTC" })
+    ).with_user(grace_ctx)).await?;
+    let error = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert!(error.payload["error"].as_str().unwrap().contains("permission denied"));
+
+    assert_eq!(storage.get_prefs(&grace.id.0).await?["timezone"], "Asia/Shanghai");
+    assert!(storage.get_prefs(&heidi.id.0).await?.is_empty());
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_platform_stats_counts_users_per_platform() -> anyhow::Result<()> {
+    use amadeus::core::messaging::message_manager::MessageManager;
+    use amadeus::plugin::PluginRegistry;
+    use amadeus::plugins::core_system::storage::Storage;
+    use amadeus::plugins::core_system::CoreSystemPlugin;
+
+    let db_path = std::env::temp_dir().join(format!("amadeus_platforms_{}.db", uuid::Uuid::new_v4()));
+    let db_url = format!("sqlite:{}", db_path.display());
+
+    let storage = Storage::new(&db_url).await?;
+    for (name, platform) in [("a", "discord"), ("b", "discord"), ("c", "discord"), ("d", "qq"), ("e", "cli"), ("f", "cli")] {
+        storage.create_user(name, platform, name).await?;
+    }
+    let admin = storage.create_user("root", "cli", "root").await?;
+    storage.add_permission_to_role("admin", "system:admin").await?;
+    storage.add_role_to_user(&admin.id.0, "admin").await?;
+
+    let stats = storage.platform_stats().await?;
+    assert_eq!(stats, vec![("cli".to_string(), 3), ("discord".to_string(), 3), ("qq".to_string(), 1)]);
+
+    let mut registry = PluginRegistry::new();
+    registry.register(CoreSystemPlugin::new(&db_url));
+    let mut message_manager = MessageManager::new();
+    registry.init_all()?;
+    registry.setup_messaging(&message_manager).await?;
+    message_manager.start_message_loop();
+    registry.start_all()?;
+
+    let dc = message_manager.distribution_center();
+    let mut rx_reply = dc.subscribe("system.user.platform_stats.reply", "verifier").await?;
+    let mut rx_error = dc.subscribe("system.user.error", "verifier").await?;
+    let tx = message_manager.message_tx();
+
+    // Non-admins are turned away
+    let user_ctx = storage.get_user_context(&storage.get_user_by_platform("qq", "d").await?.unwrap().id.0).await?.unwrap();
+    tx.send(Message::new("system.user.platform_stats", serde_json::json!({})).with_user(user_ctx)).await?;
+    let rejected = tokio::time::timeout(Duration::from_secs(2), rx_error.recv()).await??;
+    assert_eq!(rejected.payload["request"], "system.user.platform_stats");
+
+    let admin_ctx = storage.get_user_context(&admin.id.0).await?.unwrap();
+    tx.send(Message::new("system.user.platform_stats", serde_json::json!({})).with_user(admin_ctx)).await?;
+    let reply = tokio::time::timeout(Duration::from_secs(2), rx_reply.recv()).await??;
+    assert_eq!(reply.payload["platforms"], serde_json::json!([
+        { "platform": "cli", "users": 3 },
+        { "platform": "discord", "users": 3 },
+        { "platform": "qq", "users": 1 },
+    ]));
+
+    registry.shutdown()?;
+    message_manager.stop_message_loop().await;
+    storage.pool().close().await;
+    let _ = std::fs::remove_file(&db_path);
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use amadeus::util::TtlLruCache;
+use std::time::Duration;
+
+#[test]
+fn test_ttl_lru_cache_evicts_after_ttl() {
+    let mut cache: TtlLruCache<String, ()> = TtlLruCache::new(16, Duration::from_millis(50));
+    cache.insert("a".to_string(), ());
+    assert!(cache.contains(&"a".to_string()));
+
+    std::thread::sleep(Duration::from_millis(80));
+    cache.insert("b".to_string(), ());
+
+    // Access does not extend the lifetime, only re-insertion does
+    assert!(!cache.contains(&"a".to_string()));
+    assert!(cache.contains(&"b".to_string()));
+    assert_eq!(cache.len(), 1);
+
+    std::thread::sleep(Duration::from_millis(80));
+    assert_eq!(cache.purge_expired(), 1);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_ttl_lru_cache_evicts_least_recently_used() {
+    let mut cache: TtlLruCache<String, u32> = TtlLruCache::new(2, Duration::from_secs(60));
+    cache.insert("a".to_string(), 1);
+    cache.insert("b".to_string(), 2);
+
+    // Touch "a" so "b" becomes the least recently used
+    assert_eq!(cache.get(&"a".to_string()), Some(&1));
+    cache.insert("c".to_string(), 3);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.contains(&"a".to_string()));
+    assert!(!cache.contains(&"b".to_string()));
+    assert!(cache.contains(&"c".to_string()));
+
+    // Replacing an existing key does not evict anything
+    assert_eq!(cache.insert("c".to_string(), 30), Some(3));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"c".to_string()), Some(&30));
+}